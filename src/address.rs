@@ -0,0 +1,84 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+use error::BluetoothError;
+use std::fmt;
+use std::str::FromStr;
+
+// https://www.bluetooth.com/specifications/bluetooth-core-specification
+// Vol 6, Part B, 1.3: Device address.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum BluetoothAddressType {
+    Public,
+    RandomStatic,
+    RandomResolvablePrivate,
+    RandomNonResolvablePrivate,
+}
+
+// The bytes are stored in the order they are written, most significant first.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct BluetoothAddress([u8; 6]);
+
+impl BluetoothAddress {
+    pub fn new(bytes: [u8; 6]) -> BluetoothAddress {
+        BluetoothAddress(bytes)
+    }
+
+    // Accepts "01:2A:00:4D:00:04" and "01-2a-00-4d-00-04", in any case.
+    pub fn parse(address: &str) -> Result<BluetoothAddress, BluetoothError> {
+        let invalid = || BluetoothError::InvalidArgument(address.to_owned());
+        let trimmed = address.trim();
+        let separator = if trimmed.contains('-') { '-' } else { ':' };
+        let mut bytes = [0u8; 6];
+        let mut count = 0;
+        for part in trimmed.split(separator) {
+            if count == bytes.len() || part.len() != 2 || !part.chars().all(|c| c.is_ascii_hexdigit()) {
+                return Err(invalid());
+            }
+            bytes[count] = u8::from_str_radix(part, 16).map_err(|_| invalid())?;
+            count += 1;
+        }
+        if count != bytes.len() {
+            return Err(invalid());
+        }
+        Ok(BluetoothAddress(bytes))
+    }
+
+    pub fn bytes(&self) -> [u8; 6] {
+        self.0
+    }
+
+    // Whether this address is assigned by the IEEE, or is a random address is not part
+    // of the address itself, so the caller has to tell it.
+    pub fn address_type(&self, is_random: bool) -> Result<BluetoothAddressType, BluetoothError> {
+        if !is_random {
+            return Ok(BluetoothAddressType::Public);
+        }
+        let random_part_is = |value: u8| {
+            self.0[0] & 0x3F == value & 0x3F && self.0[1..].iter().all(|byte| *byte == value)
+        };
+        match self.0[0] >> 6 {
+            0b11 if !random_part_is(0x00) && !random_part_is(0xFF) => Ok(BluetoothAddressType::RandomStatic),
+            0b01 => Ok(BluetoothAddressType::RandomResolvablePrivate),
+            0b00 if !random_part_is(0x00) && !random_part_is(0xFF) =>
+                Ok(BluetoothAddressType::RandomNonResolvablePrivate),
+            _ => Err(BluetoothError::InvalidArgument(self.to_string())),
+        }
+    }
+}
+
+impl FromStr for BluetoothAddress {
+    type Err = BluetoothError;
+
+    fn from_str(address: &str) -> Result<BluetoothAddress, BluetoothError> {
+        BluetoothAddress::parse(address)
+    }
+}
+
+impl fmt::Display for BluetoothAddress {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:02X}:{:02X}:{:02X}:{:02X}:{:02X}:{:02X}",
+               self.0[0], self.0[1], self.0[2], self.0[3], self.0[4], self.0[5])
+    }
+}
@@ -0,0 +1,336 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+// https://www.bluetooth.com/specifications/bluetooth-core-specification
+// Vol 3, Part C, 11: Advertising and scan response data format.
+// The AD types are listed in the Core Specification Supplement, Part A.
+
+use error::BluetoothError;
+use std::collections::HashMap;
+use uuid::BluetoothUuid;
+
+const AD_FLAGS: u8 = 0x01;
+const AD_INCOMPLETE_UUIDS_16: u8 = 0x02;
+const AD_COMPLETE_UUIDS_16: u8 = 0x03;
+const AD_INCOMPLETE_UUIDS_32: u8 = 0x04;
+const AD_COMPLETE_UUIDS_32: u8 = 0x05;
+const AD_INCOMPLETE_UUIDS_128: u8 = 0x06;
+const AD_COMPLETE_UUIDS_128: u8 = 0x07;
+const AD_SHORTENED_LOCAL_NAME: u8 = 0x08;
+const AD_COMPLETE_LOCAL_NAME: u8 = 0x09;
+const AD_TX_POWER_LEVEL: u8 = 0x0A;
+const AD_SERVICE_DATA_16: u8 = 0x16;
+const AD_APPEARANCE: u8 = 0x19;
+const AD_SERVICE_DATA_32: u8 = 0x20;
+const AD_SERVICE_DATA_128: u8 = 0x21;
+const AD_URI: u8 = 0x24;
+const AD_MANUFACTURER_SPECIFIC_DATA: u8 = 0xFF;
+
+// The length octet counts the AD type too.
+const MAX_DATA_LENGTH: usize = 254;
+
+// https://www.bluetooth.com/specifications/assigned-numbers/uri-scheme-name-string-mapping
+const URI_SCHEMES: &[(char, &str)] = &[
+    ('\u{01}', ""),
+    ('\u{16}', "http:"),
+    ('\u{17}', "https:"),
+];
+
+// One AD structure. The ones whose data does not fit their type are kept as `Other`,
+// so every parsed structure is written back to the same bytes.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum AdStructure {
+    Flags(u8),
+    Uuids16 { complete: bool, uuids: Vec<u16> },
+    Uuids32 { complete: bool, uuids: Vec<u32> },
+    Uuids128 { complete: bool, uuids: Vec<BluetoothUuid> },
+    LocalName { complete: bool, name: String },
+    TxPowerLevel(i8),
+    ServiceData16(u16, Vec<u8>),
+    ServiceData32(u32, Vec<u8>),
+    ServiceData128(BluetoothUuid, Vec<u8>),
+    Appearance(u16),
+    // The scheme is encoded by its first character, see `AdvertisementData::get_uris`.
+    Uri(String),
+    ManufacturerData(u16, Vec<u8>),
+    Other { ad_type: u8, data: Vec<u8> },
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct AdvertisementData {
+    pub structures: Vec<AdStructure>,
+}
+
+fn read_u16(data: &[u8]) -> u16 {
+    u16::from_le_bytes([data[0], data[1]])
+}
+
+fn read_u32(data: &[u8]) -> u32 {
+    u32::from_le_bytes([data[0], data[1], data[2], data[3]])
+}
+
+// 128-bit UUIDs are sent least significant octet first.
+fn read_uuid128(data: &[u8]) -> BluetoothUuid {
+    let mut bytes = [0u8; 16];
+    for (index, byte) in data[..16].iter().rev().enumerate() {
+        bytes[index] = *byte;
+    }
+    BluetoothUuid::from_bytes(bytes)
+}
+
+fn write_uuid128(uuid: &BluetoothUuid, data: &mut Vec<u8>) {
+    data.extend(uuid.to_bytes().iter().rev());
+}
+
+impl AdStructure {
+    fn parse(ad_type: u8, data: &[u8]) -> AdStructure {
+        let complete = ad_type & 1 == 1;
+        match (ad_type, data.len()) {
+            (AD_FLAGS, 1) => AdStructure::Flags(data[0]),
+            (AD_INCOMPLETE_UUIDS_16, length) | (AD_COMPLETE_UUIDS_16, length) if length % 2 == 0 =>
+                AdStructure::Uuids16 {
+                    complete,
+                    uuids: data.chunks(2).map(read_u16).collect(),
+                },
+            (AD_INCOMPLETE_UUIDS_32, length) | (AD_COMPLETE_UUIDS_32, length) if length % 4 == 0 =>
+                AdStructure::Uuids32 {
+                    complete,
+                    uuids: data.chunks(4).map(read_u32).collect(),
+                },
+            (AD_INCOMPLETE_UUIDS_128, length) | (AD_COMPLETE_UUIDS_128, length) if length % 16 == 0 =>
+                AdStructure::Uuids128 {
+                    complete,
+                    uuids: data.chunks(16).map(read_uuid128).collect(),
+                },
+            (AD_SHORTENED_LOCAL_NAME, _) | (AD_COMPLETE_LOCAL_NAME, _) => match String::from_utf8(data.to_vec()) {
+                Ok(name) => AdStructure::LocalName {
+                    complete,
+                    name,
+                },
+                Err(_) => AdStructure::other(ad_type, data),
+            },
+            (AD_TX_POWER_LEVEL, 1) => AdStructure::TxPowerLevel(data[0] as i8),
+            (AD_SERVICE_DATA_16, length) if length >= 2 => AdStructure::ServiceData16(read_u16(data), data[2..].to_vec()),
+            (AD_SERVICE_DATA_32, length) if length >= 4 => AdStructure::ServiceData32(read_u32(data), data[4..].to_vec()),
+            (AD_SERVICE_DATA_128, length) if length >= 16 =>
+                AdStructure::ServiceData128(read_uuid128(data), data[16..].to_vec()),
+            (AD_APPEARANCE, 2) => AdStructure::Appearance(read_u16(data)),
+            (AD_URI, _) => match String::from_utf8(data.to_vec()) {
+                Ok(uri) => AdStructure::Uri(uri),
+                Err(_) => AdStructure::other(ad_type, data),
+            },
+            (AD_MANUFACTURER_SPECIFIC_DATA, length) if length >= 2 =>
+                AdStructure::ManufacturerData(read_u16(data), data[2..].to_vec()),
+            _ => AdStructure::other(ad_type, data),
+        }
+    }
+
+    fn other(ad_type: u8, data: &[u8]) -> AdStructure {
+        AdStructure::Other {
+            ad_type,
+            data: data.to_vec(),
+        }
+    }
+
+    pub fn get_ad_type(&self) -> u8 {
+        let complete_or = |complete: bool, incomplete_type: u8| if complete { incomplete_type + 1 } else { incomplete_type };
+        match *self {
+            AdStructure::Flags(_) => AD_FLAGS,
+            AdStructure::Uuids16 { complete, .. } => complete_or(complete, AD_INCOMPLETE_UUIDS_16),
+            AdStructure::Uuids32 { complete, .. } => complete_or(complete, AD_INCOMPLETE_UUIDS_32),
+            AdStructure::Uuids128 { complete, .. } => complete_or(complete, AD_INCOMPLETE_UUIDS_128),
+            AdStructure::LocalName { complete, .. } => complete_or(complete, AD_SHORTENED_LOCAL_NAME),
+            AdStructure::TxPowerLevel(_) => AD_TX_POWER_LEVEL,
+            AdStructure::ServiceData16(..) => AD_SERVICE_DATA_16,
+            AdStructure::ServiceData32(..) => AD_SERVICE_DATA_32,
+            AdStructure::ServiceData128(..) => AD_SERVICE_DATA_128,
+            AdStructure::Appearance(_) => AD_APPEARANCE,
+            AdStructure::Uri(_) => AD_URI,
+            AdStructure::ManufacturerData(..) => AD_MANUFACTURER_SPECIFIC_DATA,
+            AdStructure::Other { ad_type, .. } => ad_type,
+        }
+    }
+
+    pub fn get_data(&self) -> Vec<u8> {
+        let mut data = vec![];
+        match *self {
+            AdStructure::Flags(flags) => data.push(flags),
+            AdStructure::Uuids16 { ref uuids, .. } => {
+                for uuid in uuids {
+                    data.extend_from_slice(&uuid.to_le_bytes());
+                }
+            },
+            AdStructure::Uuids32 { ref uuids, .. } => {
+                for uuid in uuids {
+                    data.extend_from_slice(&uuid.to_le_bytes());
+                }
+            },
+            AdStructure::Uuids128 { ref uuids, .. } => {
+                for uuid in uuids {
+                    write_uuid128(uuid, &mut data);
+                }
+            },
+            AdStructure::LocalName { ref name, .. } => data.extend_from_slice(name.as_bytes()),
+            AdStructure::TxPowerLevel(level) => data.push(level as u8),
+            AdStructure::ServiceData16(uuid, ref service_data) => {
+                data.extend_from_slice(&uuid.to_le_bytes());
+                data.extend_from_slice(service_data);
+            },
+            AdStructure::ServiceData32(uuid, ref service_data) => {
+                data.extend_from_slice(&uuid.to_le_bytes());
+                data.extend_from_slice(service_data);
+            },
+            AdStructure::ServiceData128(ref uuid, ref service_data) => {
+                write_uuid128(uuid, &mut data);
+                data.extend_from_slice(service_data);
+            },
+            AdStructure::Appearance(appearance) => data.extend_from_slice(&appearance.to_le_bytes()),
+            AdStructure::Uri(ref uri) => data.extend_from_slice(uri.as_bytes()),
+            AdStructure::ManufacturerData(company, ref manufacturer_data) => {
+                data.extend_from_slice(&company.to_le_bytes());
+                data.extend_from_slice(manufacturer_data);
+            },
+            AdStructure::Other { data: ref other_data, .. } => data.extend_from_slice(other_data),
+        }
+        data
+    }
+}
+
+impl AdvertisementData {
+    pub fn new(structures: Vec<AdStructure>) -> AdvertisementData {
+        AdvertisementData {
+            structures,
+        }
+    }
+
+    // Parsing stops at the first zero length octet, the rest of the data is padding.
+    pub fn parse(bytes: &[u8]) -> Result<AdvertisementData, BluetoothError> {
+        let mut structures = vec![];
+        let mut position = 0;
+        while position < bytes.len() {
+            let length = bytes[position] as usize;
+            if length == 0 {
+                break;
+            }
+            let end = position + 1 + length;
+            if end > bytes.len() {
+                return Err(BluetoothError::InvalidArgument(
+                    format!("AD structure at {} overflows the data by {} bytes", position, end - bytes.len())));
+            }
+            structures.push(AdStructure::parse(bytes[position + 1], &bytes[position + 2..end]));
+            position = end;
+        }
+        Ok(AdvertisementData::new(structures))
+    }
+
+    pub fn to_bytes(&self) -> Result<Vec<u8>, BluetoothError> {
+        let mut bytes = vec![];
+        for structure in &self.structures {
+            let data = structure.get_data();
+            if data.len() > MAX_DATA_LENGTH {
+                return Err(BluetoothError::InvalidArgument(
+                    format!("AD structure of type 0x{:02X} is {} bytes long", structure.get_ad_type(), data.len())));
+            }
+            bytes.push(data.len() as u8 + 1);
+            bytes.push(structure.get_ad_type());
+            bytes.extend(data);
+        }
+        Ok(bytes)
+    }
+
+    pub fn get_flags(&self) -> Option<u8> {
+        self.structures.iter().filter_map(|structure| match *structure {
+            AdStructure::Flags(flags) => Some(flags),
+            _ => None,
+        }).next()
+    }
+
+    // The service UUIDs of every list, in every width.
+    pub fn get_uuids(&self) -> Vec<BluetoothUuid> {
+        let mut result = vec![];
+        for structure in &self.structures {
+            match *structure {
+                AdStructure::Uuids16 { ref uuids, .. } => result.extend(uuids.iter().map(|uuid| BluetoothUuid::from_u16(*uuid))),
+                AdStructure::Uuids32 { ref uuids, .. } => result.extend(uuids.iter().map(|uuid| BluetoothUuid::from_u32(*uuid))),
+                AdStructure::Uuids128 { ref uuids, .. } => result.extend(uuids.iter().cloned()),
+                _ => (),
+            }
+        }
+        result
+    }
+
+    // The complete local name, or the shortened one if the complete one is missing.
+    pub fn get_local_name(&self) -> Option<String> {
+        let mut shortened_name = None;
+        for structure in &self.structures {
+            if let AdStructure::LocalName { complete, ref name } = *structure {
+                if complete {
+                    return Some(name.clone());
+                }
+                shortened_name = shortened_name.or_else(|| Some(name.clone()));
+            }
+        }
+        shortened_name
+    }
+
+    pub fn get_tx_power_level(&self) -> Option<i8> {
+        self.structures.iter().filter_map(|structure| match *structure {
+            AdStructure::TxPowerLevel(level) => Some(level),
+            _ => None,
+        }).next()
+    }
+
+    pub fn get_appearance(&self) -> Option<u16> {
+        self.structures.iter().filter_map(|structure| match *structure {
+            AdStructure::Appearance(appearance) => Some(appearance),
+            _ => None,
+        }).next()
+    }
+
+    pub fn get_manufacturer_data(&self) -> HashMap<u16, Vec<u8>> {
+        let mut result = HashMap::new();
+        for structure in &self.structures {
+            if let AdStructure::ManufacturerData(company, ref data) = *structure {
+                result.insert(company, data.clone());
+            }
+        }
+        result
+    }
+
+    pub fn get_service_data(&self) -> HashMap<BluetoothUuid, Vec<u8>> {
+        let mut result = HashMap::new();
+        for structure in &self.structures {
+            match *structure {
+                AdStructure::ServiceData16(uuid, ref data) => {
+                    result.insert(BluetoothUuid::from_u16(uuid), data.clone());
+                },
+                AdStructure::ServiceData32(uuid, ref data) => {
+                    result.insert(BluetoothUuid::from_u32(uuid), data.clone());
+                },
+                AdStructure::ServiceData128(uuid, ref data) => {
+                    result.insert(uuid, data.clone());
+                },
+                _ => (),
+            }
+        }
+        result
+    }
+
+    // The URIs with their scheme expanded, unknown scheme codes are kept as they are.
+    pub fn get_uris(&self) -> Vec<String> {
+        self.structures.iter().filter_map(|structure| match *structure {
+            AdStructure::Uri(ref uri) => {
+                let mut characters = uri.chars();
+                let scheme = characters.next().and_then(|code| {
+                    URI_SCHEMES.iter().find(|&&(scheme_code, _)| scheme_code == code).map(|&(_, scheme)| scheme)
+                });
+                Some(match scheme {
+                    Some(scheme) => format!("{}{}", scheme, characters.as_str()),
+                    None => uri.clone(),
+                })
+            },
+            _ => None,
+        }).collect()
+    }
+}
@@ -0,0 +1,553 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+// Future returning wrappers of the types in `bluetooth.rs`. Every call runs the blocking
+// function on a new thread, and wakes the task polling the returned future when it is done,
+// so the futures can be awaited on any executor. That costs a thread spawn per call, and a
+// thread per call in flight: batch the work into fewer calls on a busy adapter.
+// A discovery session can not leave the thread it was created on (the BlueZ one owns a D-Bus
+// connection), so it lives on a thread of its own, which runs the calls on it in order.
+
+use bluetooth::{BluetoothAdapter, BluetoothDevice, BluetoothDiscoverySession, BluetoothGATTCharacteristic};
+use bluetooth::{BluetoothGATTDescriptor, BluetoothGATTService, BluetoothReliableWrite, WriteType};
+use address::BluetoothAddress;
+use discovery_filter::DiscoveryFilter;
+use error::BluetoothError;
+use properties::{CharacteristicProperties, DescriptorPermissions};
+use std::collections::{HashMap, VecDeque};
+use std::future::Future;
+use std::panic::{self, AssertUnwindSafe};
+use std::pin::Pin;
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::task::{Context, Poll, Waker};
+use std::thread;
+use std::time::Duration;
+use uuid::BluetoothUuid;
+
+pub struct BluetoothFuture<T> {
+    state: Arc<Mutex<FutureState<T>>>,
+}
+
+struct FutureState<T> {
+    result: Option<Result<T, BluetoothError>>,
+    waker: Option<Waker>,
+}
+
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    match mutex.lock() {
+        Ok(guard) => guard,
+        Err(poisoned) => poisoned.into_inner(),
+    }
+}
+
+impl<T: Send + 'static> BluetoothFuture<T> {
+    // The future, and the state to complete it with.
+    fn pending() -> (BluetoothFuture<T>, Arc<Mutex<FutureState<T>>>) {
+        let state = Arc::new(Mutex::new(FutureState {
+            result: None,
+            waker: None,
+        }));
+        (BluetoothFuture { state: state.clone() }, state)
+    }
+
+    fn spawn<F>(delay: Duration, function: F) -> BluetoothFuture<T>
+        where F: FnOnce() -> Result<T, BluetoothError> + Send + 'static
+    {
+        let (future, state) = BluetoothFuture::pending();
+        thread::spawn(move || {
+            wait(delay);
+            complete(&state, run(function));
+        });
+        future
+    }
+}
+
+fn wait(delay: Duration) {
+    if delay > Duration::from_secs(0) {
+        thread::sleep(delay);
+    }
+}
+
+fn run<T, F>(function: F) -> Result<T, BluetoothError>
+    where F: FnOnce() -> Result<T, BluetoothError>
+{
+    panic::catch_unwind(AssertUnwindSafe(function)).unwrap_or_else(|_| {
+        Err(BluetoothError::Backend(String::from("The backend call panicked.")))
+    })
+}
+
+fn complete<T>(state: &Mutex<FutureState<T>>, result: Result<T, BluetoothError>) {
+    let mut state = lock(state);
+    state.result = Some(result);
+    if let Some(waker) = state.waker.take() {
+        waker.wake();
+    }
+}
+
+impl<T> Future for BluetoothFuture<T> {
+    type Output = Result<T, BluetoothError>;
+
+    fn poll(self: Pin<&mut Self>, context: &mut Context<'_>) -> Poll<Result<T, BluetoothError>> {
+        let mut state = lock(&self.state);
+        match state.result.take() {
+            Some(result) => Poll::Ready(result),
+            None => {
+                state.waker = Some(context.waker().clone());
+                Poll::Pending
+            },
+        }
+    }
+}
+
+macro_rules! async_call(
+    ($function_name: ident, $return_type: ty) => {
+        pub fn $function_name(&self) -> BluetoothFuture<$return_type> {
+            let inner = self.inner.clone();
+            BluetoothFuture::spawn(self.inner.context.get_mock_delay(), move || inner.$function_name())
+        }
+    };
+
+    ($function_name: ident, $return_type: ty, $value: ident: $value_type: ty) => {
+        pub fn $function_name(&self, $value: $value_type) -> BluetoothFuture<$return_type> {
+            let inner = self.inner.clone();
+            BluetoothFuture::spawn(self.inner.context.get_mock_delay(), move || inner.$function_name($value))
+        }
+    };
+
+    ($function_name: ident, $return_type: ty, $value: ident: $value_type: ty, $value2: ident: $value2_type: ty) => {
+        pub fn $function_name(&self, $value: $value_type, $value2: $value2_type) -> BluetoothFuture<$return_type> {
+            let inner = self.inner.clone();
+            BluetoothFuture::spawn(self.inner.context.get_mock_delay(), move || inner.$function_name($value, $value2))
+        }
+    };
+);
+
+#[derive(Clone, Debug)]
+pub struct AsyncBluetoothAdapter {
+    inner: BluetoothAdapter,
+}
+
+type SessionCall = Box<dyn FnOnce(&BluetoothDiscoverySession) + Send>;
+
+#[derive(Debug)]
+pub struct AsyncBluetoothDiscoverySession {
+    calls: Sender<SessionCall>,
+    filter: DiscoveryFilter,
+}
+
+#[derive(Clone, Debug)]
+pub struct AsyncBluetoothDevice {
+    inner: BluetoothDevice,
+}
+
+#[derive(Clone, Debug)]
+pub struct AsyncBluetoothGATTService {
+    inner: BluetoothGATTService,
+}
+
+#[derive(Clone, Debug)]
+pub struct AsyncBluetoothGATTCharacteristic {
+    inner: BluetoothGATTCharacteristic,
+}
+
+#[derive(Clone, Debug)]
+pub struct AsyncBluetoothGATTDescriptor {
+    inner: BluetoothGATTDescriptor,
+}
+
+impl AsyncBluetoothAdapter {
+    pub fn new(inner: BluetoothAdapter) -> AsyncBluetoothAdapter {
+        AsyncBluetoothAdapter {
+            inner,
+        }
+    }
+
+    pub fn get_inner(&self) -> &BluetoothAdapter {
+        &self.inner
+    }
+
+    pub fn get_id(&self) -> String {
+        self.inner.get_id()
+    }
+
+    pub fn get_devices(&self) -> BluetoothFuture<Vec<AsyncBluetoothDevice>> {
+        let inner = self.inner.clone();
+        BluetoothFuture::spawn(self.inner.context.get_mock_delay(), move || {
+            Ok(inner.get_devices()?.into_iter().map(AsyncBluetoothDevice::new).collect())
+        })
+    }
+
+    pub fn get_device(&self, address: BluetoothAddress) -> BluetoothFuture<Option<AsyncBluetoothDevice>> {
+        let inner = self.inner.clone();
+        BluetoothFuture::spawn(self.inner.context.get_mock_delay(), move || {
+            Ok(inner.get_device(address)?.map(AsyncBluetoothDevice::new))
+        })
+    }
+
+    pub fn get_device_by_id(&self, id: String) -> BluetoothFuture<Option<AsyncBluetoothDevice>> {
+        let inner = self.inner.clone();
+        BluetoothFuture::spawn(self.inner.context.get_mock_delay(), move || {
+            Ok(inner.get_device_by_id(&id)?.map(AsyncBluetoothDevice::new))
+        })
+    }
+
+    pub fn create_discovery_session(&self) -> BluetoothFuture<AsyncBluetoothDiscoverySession> {
+        AsyncBluetoothDiscoverySession::spawn(self.inner.clone(), DiscoveryFilter::default())
+    }
+
+    pub fn create_discovery_session_with_filter(&self,
+                                                filter: DiscoveryFilter)
+                                                -> BluetoothFuture<AsyncBluetoothDiscoverySession> {
+        AsyncBluetoothDiscoverySession::spawn(self.inner.clone(), filter)
+    }
+
+    async_call!(poll_events, ());
+    async_call!(get_address, BluetoothAddress);
+    async_call!(get_name, String);
+    async_call!(get_alias, String);
+    async_call!(get_class, u32);
+    async_call!(is_powered, bool);
+    async_call!(is_discoverable, bool);
+    async_call!(is_pairable, bool);
+    async_call!(get_pairable_timeout, u32);
+    async_call!(get_discoverable_timeout, u32);
+    async_call!(is_discovering, bool);
+    async_call!(get_uuids, Vec<BluetoothUuid>);
+    async_call!(get_vendor_id_source, String);
+    async_call!(get_vendor_id, u32);
+    async_call!(get_product_id, u32);
+    async_call!(get_device_id, u32);
+    async_call!(get_modalias, (String, u32, u32, u32));
+}
+
+impl AsyncBluetoothDiscoverySession {
+    // The thread ends when the session is dropped.
+    fn spawn(adapter: BluetoothAdapter, filter: DiscoveryFilter) -> BluetoothFuture<AsyncBluetoothDiscoverySession> {
+        let (future, state) = BluetoothFuture::pending();
+        let (sender, receiver) = mpsc::channel::<SessionCall>();
+        thread::spawn(move || {
+            wait(adapter.context.get_mock_delay());
+            let session_filter = filter.clone();
+            let session = match run(|| adapter.create_discovery_session_with_filter(session_filter)) {
+                Ok(session) => session,
+                Err(error) => return complete(&state, Err(error)),
+            };
+            complete(&state, Ok(AsyncBluetoothDiscoverySession {
+                calls: sender,
+                filter,
+            }));
+            for call in receiver {
+                wait(adapter.context.get_mock_delay());
+                call(&session);
+            }
+        });
+        future
+    }
+
+    fn call<T, F>(&self, function: F) -> BluetoothFuture<T>
+        where T: Send + 'static,
+              F: FnOnce(&BluetoothDiscoverySession) -> Result<T, BluetoothError> + Send + 'static
+    {
+        let (future, state) = BluetoothFuture::pending();
+        let call_state = state.clone();
+        let call: SessionCall = Box::new(move |session| complete(&call_state, run(|| function(session))));
+        if self.calls.send(call).is_err() {
+            complete(&state, Err(BluetoothError::Backend(String::from("The discovery session thread ended."))));
+        }
+        future
+    }
+
+    pub fn get_filter(&self) -> &DiscoveryFilter {
+        &self.filter
+    }
+
+    pub fn start_discovery(&self) -> BluetoothFuture<()> {
+        self.call(BluetoothDiscoverySession::start_discovery)
+    }
+
+    pub fn stop_discovery(&self) -> BluetoothFuture<()> {
+        self.call(BluetoothDiscoverySession::stop_discovery)
+    }
+
+    pub fn get_discovered_devices(&self) -> BluetoothFuture<Vec<AsyncBluetoothDevice>> {
+        self.call(|session| Ok(session.get_discovered_devices()?.into_iter().map(AsyncBluetoothDevice::new).collect()))
+    }
+
+    pub fn get_new_devices(&self) -> BluetoothFuture<Vec<AsyncBluetoothDevice>> {
+        self.call(|session| Ok(session.get_new_devices()?.into_iter().map(AsyncBluetoothDevice::new).collect()))
+    }
+}
+
+impl AsyncBluetoothDevice {
+    pub fn new(inner: BluetoothDevice) -> AsyncBluetoothDevice {
+        AsyncBluetoothDevice {
+            inner,
+        }
+    }
+
+    pub fn get_inner(&self) -> &BluetoothDevice {
+        &self.inner
+    }
+
+    pub fn get_id(&self) -> String {
+        self.inner.get_id()
+    }
+
+    pub fn get_gatt_services(&self) -> BluetoothFuture<Vec<AsyncBluetoothGATTService>> {
+        let inner = self.inner.clone();
+        BluetoothFuture::spawn(self.inner.context.get_mock_delay(), move || {
+            Ok(inner.get_gatt_services()?.into_iter().map(AsyncBluetoothGATTService::new).collect())
+        })
+    }
+
+    async_call!(get_address, BluetoothAddress);
+    async_call!(get_name, String);
+    async_call!(get_icon, String);
+    async_call!(get_class, u32);
+    async_call!(get_appearance, u16);
+    async_call!(get_uuids, Vec<BluetoothUuid>);
+    async_call!(is_paired, bool);
+    async_call!(is_connected, bool);
+    async_call!(is_trusted, bool);
+    async_call!(is_blocked, bool);
+    async_call!(get_alias, String);
+    async_call!(is_legacy_pairing, bool);
+    async_call!(get_vendor_id_source, String);
+    async_call!(get_vendor_id, u32);
+    async_call!(get_product_id, u32);
+    async_call!(get_device_id, u32);
+    async_call!(get_modalias, (String, u32, u32, u32));
+    async_call!(get_rssi, i16);
+    async_call!(get_tx_power, i16);
+    async_call!(get_manufacturer_data, HashMap<u16, Vec<u8>>);
+    async_call!(get_service_data, HashMap<BluetoothUuid, Vec<u8>>);
+    async_call!(connect, ());
+    async_call!(disconnect, ());
+    async_call!(connect_profile, (), uuid: BluetoothUuid);
+    async_call!(disconnect_profile, (), uuid: BluetoothUuid);
+    async_call!(get_mtu, u16);
+    async_call!(request_mtu, u16, mtu: u16);
+    async_call!(pair, ());
+    async_call!(cancel_pairing, ());
+}
+
+impl AsyncBluetoothGATTService {
+    pub fn new(inner: BluetoothGATTService) -> AsyncBluetoothGATTService {
+        AsyncBluetoothGATTService {
+            inner,
+        }
+    }
+
+    pub fn get_inner(&self) -> &BluetoothGATTService {
+        &self.inner
+    }
+
+    pub fn get_id(&self) -> String {
+        self.inner.get_id()
+    }
+
+    pub fn get_includes(&self, device: AsyncBluetoothDevice) -> BluetoothFuture<Vec<AsyncBluetoothGATTService>> {
+        let inner = self.inner.clone();
+        BluetoothFuture::spawn(self.inner.context.get_mock_delay(), move || {
+            Ok(inner.get_includes(device.inner)?.into_iter().map(AsyncBluetoothGATTService::new).collect())
+        })
+    }
+
+    pub fn get_gatt_characteristics(&self) -> BluetoothFuture<Vec<AsyncBluetoothGATTCharacteristic>> {
+        let inner = self.inner.clone();
+        BluetoothFuture::spawn(self.inner.context.get_mock_delay(), move || {
+            Ok(inner.get_gatt_characteristics()?.into_iter().map(AsyncBluetoothGATTCharacteristic::new).collect())
+        })
+    }
+
+    async_call!(get_uuid, BluetoothUuid);
+    async_call!(is_primary, bool);
+}
+
+impl AsyncBluetoothGATTCharacteristic {
+    pub fn new(inner: BluetoothGATTCharacteristic) -> AsyncBluetoothGATTCharacteristic {
+        AsyncBluetoothGATTCharacteristic {
+            inner,
+        }
+    }
+
+    pub fn get_inner(&self) -> &BluetoothGATTCharacteristic {
+        &self.inner
+    }
+
+    pub fn get_id(&self) -> String {
+        self.inner.get_id()
+    }
+
+    pub fn get_gatt_descriptors(&self) -> BluetoothFuture<Vec<AsyncBluetoothGATTDescriptor>> {
+        let inner = self.inner.clone();
+        BluetoothFuture::spawn(self.inner.context.get_mock_delay(), move || {
+            Ok(inner.get_gatt_descriptors()?.into_iter().map(AsyncBluetoothGATTDescriptor::new).collect())
+        })
+    }
+
+    pub fn begin_reliable_write(&self) -> BluetoothFuture<AsyncBluetoothReliableWrite> {
+        let inner = self.inner.clone();
+        BluetoothFuture::spawn(self.inner.context.get_mock_delay(), move || {
+            Ok(AsyncBluetoothReliableWrite::new(inner.begin_reliable_write()?))
+        })
+    }
+
+    async_call!(get_uuid, BluetoothUuid);
+    async_call!(get_value, Vec<u8>);
+    async_call!(is_notifying, bool);
+    async_call!(get_flags, Vec<String>);
+    async_call!(get_properties, CharacteristicProperties);
+    async_call!(read_value, Vec<u8>);
+    async_call!(write_value, (), values: Vec<u8>);
+    async_call!(write_value_with_type, (), values: Vec<u8>, write_type: WriteType);
+    async_call!(read_value_at, Vec<u8>, offset: u16);
+    async_call!(write_long_value, (), values: Vec<u8>);
+    async_call!(start_notify, ());
+    async_call!(stop_notify, ());
+
+    pub fn get_notifications(&self) -> Result<AsyncBluetoothNotifications, BluetoothError> {
+        Ok(AsyncBluetoothNotifications::new(self.inner.get_notifications()?))
+    }
+}
+
+impl AsyncBluetoothGATTDescriptor {
+    pub fn new(inner: BluetoothGATTDescriptor) -> AsyncBluetoothGATTDescriptor {
+        AsyncBluetoothGATTDescriptor {
+            inner,
+        }
+    }
+
+    pub fn get_inner(&self) -> &BluetoothGATTDescriptor {
+        &self.inner
+    }
+
+    pub fn get_id(&self) -> String {
+        self.inner.get_id()
+    }
+
+    async_call!(get_uuid, BluetoothUuid);
+    async_call!(get_value, Vec<u8>);
+    async_call!(get_flags, Vec<String>);
+    async_call!(get_permissions, DescriptorPermissions);
+    async_call!(read_value, Vec<u8>);
+    async_call!(read_value_at, Vec<u8>, offset: u16);
+    async_call!(write_value, (), values: Vec<u8>);
+}
+
+// Preparing a write only queues it, so only `execute` returns a future.
+#[derive(Debug)]
+pub struct AsyncBluetoothReliableWrite {
+    inner: BluetoothReliableWrite,
+}
+
+impl AsyncBluetoothReliableWrite {
+    pub fn new(inner: BluetoothReliableWrite) -> AsyncBluetoothReliableWrite {
+        AsyncBluetoothReliableWrite {
+            inner,
+        }
+    }
+
+    pub fn get_inner(&self) -> &BluetoothReliableWrite {
+        &self.inner
+    }
+
+    pub fn get_prepared_writes(&self) -> &[(u16, Vec<u8>)] {
+        self.inner.get_prepared_writes()
+    }
+
+    pub fn prepare_write(&mut self, offset: u16, values: Vec<u8>) -> Result<(), BluetoothError> {
+        self.inner.prepare_write(offset, values)
+    }
+
+    pub fn execute(self) -> BluetoothFuture<()> {
+        let delay = self.inner.get_characteristic().context.get_mock_delay();
+        let inner = self.inner;
+        BluetoothFuture::spawn(delay, move || inner.execute())
+    }
+
+    pub fn abort(self) {
+        self.inner.abort()
+    }
+}
+
+// The notifications of a characteristic, like the receiver of
+// `BluetoothGATTCharacteristic::get_notifications`. A thread waits for them, until `stop_notify`
+// is called, or the next notification after this is dropped.
+#[derive(Debug)]
+pub struct AsyncBluetoothNotifications {
+    state: Arc<Mutex<NotificationState>>,
+}
+
+#[derive(Debug)]
+struct NotificationState {
+    values: VecDeque<Vec<u8>>,
+    stopped: bool,
+    waker: Option<Waker>,
+}
+
+impl AsyncBluetoothNotifications {
+    fn new(receiver: Receiver<Vec<u8>>) -> AsyncBluetoothNotifications {
+        let state = Arc::new(Mutex::new(NotificationState {
+            values: VecDeque::new(),
+            stopped: false,
+            waker: None,
+        }));
+        let thread_state = Arc::downgrade(&state);
+        thread::spawn(move || {
+            let mut value = receiver.recv().ok();
+            while let Some(state) = thread_state.upgrade() {
+                let mut state = lock(&state);
+                match value {
+                    Some(value) => state.values.push_back(value),
+                    None => state.stopped = true,
+                }
+                if let Some(waker) = state.waker.take() {
+                    waker.wake();
+                }
+                if state.stopped {
+                    return;
+                }
+                drop(state);
+                value = receiver.recv().ok();
+            }
+        });
+        AsyncBluetoothNotifications {
+            state,
+        }
+    }
+
+    // `Ready(None)` once the notifications stopped, like `Stream::poll_next`.
+    pub fn poll_next(&mut self, context: &mut Context<'_>) -> Poll<Option<Vec<u8>>> {
+        let mut state = lock(&self.state);
+        if let Some(value) = state.values.pop_front() {
+            return Poll::Ready(Some(value));
+        }
+        if state.stopped {
+            return Poll::Ready(None);
+        }
+        state.waker = Some(context.waker().clone());
+        Poll::Pending
+    }
+
+    // The next notification, or `None` once the notifications stopped.
+    pub fn recv(&mut self) -> NextNotification<'_> {
+        NextNotification {
+            notifications: self,
+        }
+    }
+}
+
+pub struct NextNotification<'a> {
+    notifications: &'a mut AsyncBluetoothNotifications,
+}
+
+impl<'a> Future for NextNotification<'a> {
+    type Output = Option<Vec<u8>>;
+
+    fn poll(self: Pin<&mut Self>, context: &mut Context<'_>) -> Poll<Option<Vec<u8>>> {
+        self.get_mut().notifications.poll_next(context)
+    }
+}
@@ -0,0 +1,512 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+// The interface between the types of `bluetooth.rs` and a Bluetooth stack. BlueZ (through blurz),
+// Android (through blurdroid), the empty backend and the mock backend (through blurmock)
+// implement it, and so can a backend outside of this crate: pass it to
+// `BluetoothAdapter::init_with_backend`, or register it by name with `register_backend`.
+//
+// The ids, addresses and UUIDs are strings, as the stacks report them. Except for the ids and
+// the creation of the backends of the children, every call fails with `NotSupported`, unless
+// the backend implements it.
+
+use bluetooth::{BluetoothDevice, WriteType};
+use error::BluetoothError;
+#[cfg(feature = "bluetooth-test")]
+use mock;
+use pairing::PairingAgent;
+use std::any::Any;
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::Debug;
+use std::sync::{Arc, Mutex, MutexGuard};
+
+#[cfg(all(target_os = "linux", feature = "bluetooth"))]
+use blurz::bluetooth_adapter::BluetoothAdapter as BluetoothAdapterBluez;
+#[cfg(all(target_os = "android", feature = "bluetooth"))]
+use blurdroid::bluetooth_adapter::Adapter as BluetoothAdapterAndroid;
+#[cfg(not(any(all(target_os = "linux", feature = "bluetooth"), all(target_os = "android", feature = "bluetooth"))))]
+use empty::BluetoothAdapter as BluetoothAdapterEmpty;
+#[cfg(feature = "bluetooth-test")]
+use blurmock::fake_adapter::FakeBluetoothAdapter;
+#[cfg(all(target_os = "linux", feature = "bluetooth"))]
+use blurz::bluetooth_device::BluetoothDevice as BluetoothDeviceBluez;
+#[cfg(all(target_os = "android", feature = "bluetooth"))]
+use blurdroid::bluetooth_device::Device as BluetoothDeviceAndroid;
+#[cfg(not(any(all(target_os = "linux", feature = "bluetooth"), all(target_os = "android", feature = "bluetooth"))))]
+use empty::BluetoothDevice as BluetoothDeviceEmpty;
+#[cfg(feature = "bluetooth-test")]
+use blurmock::fake_device::FakeBluetoothDevice;
+#[cfg(all(target_os = "linux", feature = "bluetooth"))]
+use blurz::bluetooth_gatt_characteristic::BluetoothGATTCharacteristic as BluetoothGATTCharacteristicBluez;
+#[cfg(all(target_os = "android", feature = "bluetooth"))]
+use blurdroid::bluetooth_gatt_characteristic::Characteristic as BluetoothGATTCharacteristicAndroid;
+#[cfg(not(any(all(target_os = "linux", feature = "bluetooth"), all(target_os = "android", feature = "bluetooth"))))]
+use empty::BluetoothGATTCharacteristic as BluetoothGATTCharacteristicEmpty;
+#[cfg(feature = "bluetooth-test")]
+use blurmock::fake_characteristic::FakeBluetoothGATTCharacteristic;
+#[cfg(all(target_os = "linux", feature = "bluetooth"))]
+use blurz::bluetooth_gatt_descriptor::BluetoothGATTDescriptor as BluetoothGATTDescriptorBluez;
+#[cfg(all(target_os = "android", feature = "bluetooth"))]
+use blurdroid::bluetooth_gatt_descriptor::Descriptor as BluetoothGATTDescriptorAndroid;
+#[cfg(not(any(all(target_os = "linux", feature = "bluetooth"), all(target_os = "android", feature = "bluetooth"))))]
+use empty::BluetoothGATTDescriptor as BluetoothGATTDescriptorEmpty;
+#[cfg(feature = "bluetooth-test")]
+use blurmock::fake_descriptor::FakeBluetoothGATTDescriptor;
+#[cfg(all(target_os = "linux", feature = "bluetooth"))]
+use blurz::bluetooth_gatt_service::BluetoothGATTService as BluetoothGATTServiceBluez;
+#[cfg(all(target_os = "android", feature = "bluetooth"))]
+use blurdroid::bluetooth_gatt_service::Service as BluetoothGATTServiceAndroid;
+#[cfg(not(any(all(target_os = "linux", feature = "bluetooth"), all(target_os = "android", feature = "bluetooth"))))]
+use empty::BluetoothGATTService as BluetoothGATTServiceEmpty;
+#[cfg(feature = "bluetooth-test")]
+use blurmock::fake_service::FakeBluetoothGATTService;
+#[cfg(all(target_os = "linux", feature = "bluetooth"))]
+use blurz::bluetooth_discovery_session::BluetoothDiscoverySession as BluetoothDiscoverySessionBluez;
+#[cfg(all(target_os = "android", feature = "bluetooth"))]
+use blurdroid::bluetooth_discovery_session::DiscoverySession as BluetoothDiscoverySessionAndroid;
+#[cfg(not(any(all(target_os = "linux", feature = "bluetooth"), all(target_os = "android", feature = "bluetooth"))))]
+use empty::BluetoothDiscoverySession as BluetoothDiscoverySessionEmpty;
+#[cfg(feature = "bluetooth-test")]
+use blurmock::fake_discovery_session::FakeBluetoothDiscoverySession;
+
+// Default implementations failing with `NotSupported`.
+macro_rules! not_supported(
+    ($($function_name: ident($($arg: ident: $arg_type: ty),*) -> $result: ty;)*) => {
+        $(
+            fn $function_name(&self $(, $arg: $arg_type)*) -> Result<$result, BluetoothError> {
+                $(let _ = $arg;)*
+                Err(BluetoothError::NotSupported)
+            }
+        )*
+    };
+);
+
+// Implementations calling the function of the same name of the backend library.
+macro_rules! forward(
+    ($type_name: ty, $($function_name: ident($($arg: ident: $arg_type: ty),*) -> $result: ty;)*) => {
+        $(
+            fn $function_name(&self $(, $arg: $arg_type)*) -> Result<$result, BluetoothError> {
+                <$type_name>::$function_name(self $(, $arg)*).map_err(BluetoothError::from)
+            }
+        )*
+    };
+);
+
+pub trait AdapterBackend: Any + Debug + Send + Sync {
+    fn get_id(&self) -> String;
+
+    // The backend of the device with the id, one of the ids of `get_device_list`.
+    fn create_device(self: Arc<Self>, id: String) -> Arc<dyn DeviceBackend>;
+
+    fn create_discovery_session(self: Arc<Self>) -> Result<Arc<dyn SessionBackend>, BluetoothError> {
+        Err(BluetoothError::NotSupported)
+    }
+
+    not_supported! {
+        get_device_list() -> Vec<String>;
+        get_address() -> String;
+        get_name() -> String;
+        get_alias() -> String;
+        get_class() -> u32;
+        is_powered() -> bool;
+        is_discoverable() -> bool;
+        is_pairable() -> bool;
+        get_pairable_timeout() -> u32;
+        get_discoverable_timeout() -> u32;
+        is_discovering() -> bool;
+        get_uuids() -> Vec<String>;
+        get_vendor_id_source() -> String;
+        get_vendor_id() -> u32;
+        get_product_id() -> u32;
+        get_device_id() -> u32;
+        get_modalias() -> (String, u32, u32, u32);
+        // Accepts the agent `DeviceBackend::pair_with_agent` is called with.
+        register_agent(agent: Arc<dyn PairingAgent>) -> ();
+        unregister_agent() -> ();
+    }
+}
+
+// The discovery session of blurz holds a D-Bus connection, which can not be shared between threads.
+pub trait SessionBackend: Any + Debug {
+    not_supported! {
+        start_discovery() -> ();
+        stop_discovery() -> ();
+    }
+}
+
+pub trait DeviceBackend: Any + Debug + Send + Sync {
+    fn get_id(&self) -> String;
+
+    // The backend of the service with the id, one of the ids of `get_gatt_services`,
+    // or of the includes of a service of the device.
+    fn create_service(self: Arc<Self>, id: String) -> Arc<dyn ServiceBackend>;
+
+    not_supported! {
+        get_address() -> String;
+        get_name() -> String;
+        get_icon() -> String;
+        get_class() -> u32;
+        get_appearance() -> u16;
+        get_uuids() -> Vec<String>;
+        is_paired() -> bool;
+        is_connected() -> bool;
+        is_trusted() -> bool;
+        is_blocked() -> bool;
+        get_alias() -> String;
+        is_legacy_pairing() -> bool;
+        get_vendor_id_source() -> String;
+        get_vendor_id() -> u32;
+        get_product_id() -> u32;
+        get_device_id() -> u32;
+        get_modalias() -> (String, u32, u32, u32);
+        get_rssi() -> i16;
+        get_tx_power() -> i16;
+        get_manufacturer_data() -> HashMap<u16, Vec<u8>>;
+        get_service_data() -> HashMap<String, Vec<u8>>;
+        get_gatt_services() -> Vec<String>;
+        connect() -> ();
+        disconnect() -> ();
+        connect_profile(uuid: String) -> ();
+        disconnect_profile(uuid: String) -> ();
+        pair() -> ();
+        cancel_pairing() -> ();
+        // The ATT_MTU of the connection.
+        get_mtu() -> u16;
+        // Exchanges the ATT_MTU, and returns the new one.
+        request_mtu(mtu: u16) -> u16;
+    }
+
+    // Pairs with the device, asking the agent registered on the adapter, if there is one. The
+    // agent is asked about `device`, the device of this backend.
+    fn pair_with_agent(&self, device: &BluetoothDevice, agent: Option<Arc<dyn PairingAgent>>)
+                       -> Result<(), BluetoothError> {
+        let _ = (device, agent);
+        self.pair()
+    }
+}
+
+pub trait ServiceBackend: Any + Debug + Send + Sync {
+    fn get_id(&self) -> String;
+
+    // The backend of the characteristic with the id, one of the ids of `get_gatt_characteristics`.
+    fn create_characteristic(self: Arc<Self>, id: String) -> Arc<dyn CharacteristicBackend>;
+
+    not_supported! {
+        get_uuid() -> String;
+        is_primary() -> bool;
+        get_includes() -> Vec<String>;
+        get_gatt_characteristics() -> Vec<String>;
+    }
+}
+
+pub trait CharacteristicBackend: Any + Debug + Send + Sync {
+    fn get_id(&self) -> String;
+
+    // The backend of the descriptor with the id, one of the ids of `get_gatt_descriptors`.
+    fn create_descriptor(self: Arc<Self>, id: String) -> Arc<dyn DescriptorBackend>;
+
+    not_supported! {
+        get_uuid() -> String;
+        get_value() -> Vec<u8>;
+        is_notifying() -> bool;
+        get_flags() -> Vec<String>;
+        get_gatt_descriptors() -> Vec<String>;
+        read_value() -> Vec<u8>;
+        write_value(values: Vec<u8>) -> ();
+        write_value_with_type(values: Vec<u8>, write_type: WriteType) -> ();
+        start_notify() -> ();
+        stop_notify() -> ();
+    }
+}
+
+pub trait DescriptorBackend: Any + Debug + Send + Sync {
+    fn get_id(&self) -> String;
+
+    not_supported! {
+        get_uuid() -> String;
+        get_value() -> Vec<u8>;
+        get_flags() -> Vec<String>;
+        read_value() -> Vec<u8>;
+        write_value(values: Vec<u8>) -> ();
+    }
+}
+
+// Implements the traits for the types of a backend library, which share the names of the functions.
+// The children are created from the `Arc` of their parent, like blurdroid and blurmock need.
+macro_rules! implement_backend(
+    ($adapter: ty, $session: ty, $device: ty, $service: ty, $characteristic: ty, $descriptor: ty,
+     |$adapter_backend: ident| $create_session: expr,
+     |$create_device_parent: ident, $device_id: ident| $create_device: expr,
+     |$create_service_parent: ident, $service_id: ident| $create_service: expr,
+     |$create_characteristic_parent: ident, $characteristic_id: ident| $create_characteristic: expr,
+     |$create_descriptor_parent: ident, $descriptor_id: ident| $create_descriptor: expr,
+     adapter { $($adapter_item: item)* },
+     device { $($device_item: item)* }) => {
+        impl AdapterBackend for $adapter {
+            fn get_id(&self) -> String {
+                <$adapter>::get_id(self)
+            }
+
+            fn create_device(self: Arc<Self>, $device_id: String) -> Arc<dyn DeviceBackend> {
+                let $create_device_parent = self;
+                $create_device
+            }
+
+            fn create_discovery_session(self: Arc<Self>) -> Result<Arc<dyn SessionBackend>, BluetoothError> {
+                let $adapter_backend = self;
+                Ok(Arc::new($create_session.map_err(BluetoothError::from)?))
+            }
+
+            forward! {
+                $adapter,
+                get_device_list() -> Vec<String>;
+                get_address() -> String;
+                get_name() -> String;
+                get_alias() -> String;
+                get_class() -> u32;
+                is_powered() -> bool;
+                is_discoverable() -> bool;
+                is_pairable() -> bool;
+                get_pairable_timeout() -> u32;
+                get_discoverable_timeout() -> u32;
+                is_discovering() -> bool;
+                get_uuids() -> Vec<String>;
+                get_vendor_id_source() -> String;
+                get_vendor_id() -> u32;
+                get_product_id() -> u32;
+                get_device_id() -> u32;
+                get_modalias() -> (String, u32, u32, u32);
+            }
+
+            $($adapter_item)*
+        }
+
+        impl SessionBackend for $session {
+            forward! {
+                $session,
+                start_discovery() -> ();
+                stop_discovery() -> ();
+            }
+        }
+
+        impl DeviceBackend for $device {
+            fn get_id(&self) -> String {
+                <$device>::get_id(self)
+            }
+
+            fn create_service(self: Arc<Self>, $service_id: String) -> Arc<dyn ServiceBackend> {
+                let $create_service_parent = self;
+                $create_service
+            }
+
+            forward! {
+                $device,
+                get_address() -> String;
+                get_name() -> String;
+                get_icon() -> String;
+                get_class() -> u32;
+                get_appearance() -> u16;
+                get_uuids() -> Vec<String>;
+                is_paired() -> bool;
+                is_connected() -> bool;
+                is_trusted() -> bool;
+                is_blocked() -> bool;
+                get_alias() -> String;
+                is_legacy_pairing() -> bool;
+                get_vendor_id_source() -> String;
+                get_vendor_id() -> u32;
+                get_product_id() -> u32;
+                get_device_id() -> u32;
+                get_modalias() -> (String, u32, u32, u32);
+                get_rssi() -> i16;
+                get_tx_power() -> i16;
+                get_manufacturer_data() -> HashMap<u16, Vec<u8>>;
+                get_service_data() -> HashMap<String, Vec<u8>>;
+                get_gatt_services() -> Vec<String>;
+                connect() -> ();
+                disconnect() -> ();
+                connect_profile(uuid: String) -> ();
+                disconnect_profile(uuid: String) -> ();
+                pair() -> ();
+                cancel_pairing() -> ();
+            }
+
+            $($device_item)*
+        }
+
+        impl ServiceBackend for $service {
+            fn get_id(&self) -> String {
+                <$service>::get_id(self)
+            }
+
+            fn create_characteristic(self: Arc<Self>, $characteristic_id: String) -> Arc<dyn CharacteristicBackend> {
+                let $create_characteristic_parent = self;
+                $create_characteristic
+            }
+
+            forward! {
+                $service,
+                get_uuid() -> String;
+                is_primary() -> bool;
+                get_includes() -> Vec<String>;
+                get_gatt_characteristics() -> Vec<String>;
+            }
+        }
+
+        impl CharacteristicBackend for $characteristic {
+            fn get_id(&self) -> String {
+                <$characteristic>::get_id(self)
+            }
+
+            fn create_descriptor(self: Arc<Self>, $descriptor_id: String) -> Arc<dyn DescriptorBackend> {
+                let $create_descriptor_parent = self;
+                $create_descriptor
+            }
+
+            forward! {
+                $characteristic,
+                get_uuid() -> String;
+                get_value() -> Vec<u8>;
+                is_notifying() -> bool;
+                get_flags() -> Vec<String>;
+                get_gatt_descriptors() -> Vec<String>;
+                read_value() -> Vec<u8>;
+                write_value(values: Vec<u8>) -> ();
+                start_notify() -> ();
+                stop_notify() -> ();
+            }
+
+            // None of the libraries takes the write type, they write the way the properties of
+            // the characteristic allow.
+            fn write_value_with_type(&self, values: Vec<u8>, _write_type: WriteType) -> Result<(), BluetoothError> {
+                CharacteristicBackend::write_value(self, values)
+            }
+        }
+
+        impl DescriptorBackend for $descriptor {
+            fn get_id(&self) -> String {
+                <$descriptor>::get_id(self)
+            }
+
+            forward! {
+                $descriptor,
+                get_uuid() -> String;
+                get_value() -> Vec<u8>;
+                get_flags() -> Vec<String>;
+                read_value() -> Vec<u8>;
+                write_value(values: Vec<u8>) -> ();
+            }
+        }
+    };
+);
+
+#[cfg(all(target_os = "linux", feature = "bluetooth"))]
+implement_backend!(BluetoothAdapterBluez, BluetoothDiscoverySessionBluez, BluetoothDeviceBluez,
+                   BluetoothGATTServiceBluez, BluetoothGATTCharacteristicBluez, BluetoothGATTDescriptorBluez,
+                   |adapter| BluetoothDiscoverySessionBluez::create_session(adapter.get_id()),
+                   |_adapter, id| Arc::new(BluetoothDeviceBluez::new(id)),
+                   |_device, id| Arc::new(BluetoothGATTServiceBluez::new(id)),
+                   |_service, id| Arc::new(BluetoothGATTCharacteristicBluez::new(id)),
+                   |_characteristic, id| Arc::new(BluetoothGATTDescriptorBluez::new(id)),
+                   adapter {},
+                   device {});
+
+#[cfg(all(target_os = "android", feature = "bluetooth"))]
+implement_backend!(BluetoothAdapterAndroid, BluetoothDiscoverySessionAndroid, BluetoothDeviceAndroid,
+                   BluetoothGATTServiceAndroid, BluetoothGATTCharacteristicAndroid, BluetoothGATTDescriptorAndroid,
+                   |adapter| BluetoothDiscoverySessionAndroid::create_session(adapter),
+                   |adapter, id| Arc::new(BluetoothDeviceAndroid::new(adapter, id)),
+                   |device, id| Arc::new(BluetoothGATTServiceAndroid::new(device, id)),
+                   |service, id| Arc::new(BluetoothGATTCharacteristicAndroid::new(service, id)),
+                   |characteristic, id| Arc::new(BluetoothGATTDescriptorAndroid::new(characteristic, id)),
+                   adapter {},
+                   device {});
+
+#[cfg(not(any(all(target_os = "linux", feature = "bluetooth"), all(target_os = "android", feature = "bluetooth"))))]
+implement_backend!(BluetoothAdapterEmpty, BluetoothDiscoverySessionEmpty, BluetoothDeviceEmpty,
+                   BluetoothGATTServiceEmpty, BluetoothGATTCharacteristicEmpty, BluetoothGATTDescriptorEmpty,
+                   |adapter| BluetoothDiscoverySessionEmpty::create_session(adapter),
+                   |_adapter, id| Arc::new(BluetoothDeviceEmpty::new(id)),
+                   |_device, id| Arc::new(BluetoothGATTServiceEmpty::new(id)),
+                   |_service, id| Arc::new(BluetoothGATTCharacteristicEmpty::new(id)),
+                   |_characteristic, id| Arc::new(BluetoothGATTDescriptorEmpty::new(id)),
+                   adapter {},
+                   device {});
+
+#[cfg(feature = "bluetooth-test")]
+implement_backend!(FakeBluetoothAdapter, FakeBluetoothDiscoverySession, FakeBluetoothDevice,
+                   FakeBluetoothGATTService, FakeBluetoothGATTCharacteristic, FakeBluetoothGATTDescriptor,
+                   |adapter| FakeBluetoothDiscoverySession::create_session(adapter),
+                   |adapter, id| FakeBluetoothDevice::new_empty(adapter, id),
+                   |device, id| FakeBluetoothGATTService::new_empty(device, id),
+                   |service, id| FakeBluetoothGATTCharacteristic::new_empty(service, id),
+                   |characteristic, id| FakeBluetoothGATTDescriptor::new_empty(characteristic, id),
+                   adapter {
+                       fn register_agent(&self, _agent: Arc<dyn PairingAgent>) -> Result<(), BluetoothError> {
+                           Ok(())
+                       }
+
+                       fn unregister_agent(&self) -> Result<(), BluetoothError> {
+                           Ok(())
+                       }
+                   },
+                   device {
+                       fn get_mtu(&self) -> Result<u16, BluetoothError> {
+                           mock::get(self).map(|mock_device| mock_device.mtu)
+                       }
+
+                       fn request_mtu(&self, mtu: u16) -> Result<u16, BluetoothError> {
+                           let mock_device = mock::update(self, |mock_device| {
+                               mock_device.mtu = mtu.min(mock_device.server_mtu);
+                           })?;
+                           Ok(mock_device.mtu)
+                       }
+
+                       fn pair_with_agent(&self, device: &BluetoothDevice, agent: Option<Arc<dyn PairingAgent>>)
+                                          -> Result<(), BluetoothError> {
+                           mock::pair(self, device, agent)
+                       }
+                   });
+
+pub type BackendFactory = dyn Fn() -> Result<Arc<dyn AdapterBackend>, BluetoothError> + Send + Sync;
+
+// The factories of the backends registered by name.
+static REGISTERED_BACKENDS: Mutex<BTreeMap<String, Arc<BackendFactory>>> = Mutex::new(BTreeMap::new());
+
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    match mutex.lock() {
+        Ok(guard) => guard,
+        Err(poisoned) => poisoned.into_inner(),
+    }
+}
+
+// Makes the backend created by the factory available to `BluetoothAdapter::init_registered_backend`.
+// Fails with `InvalidState`, if a backend is registered with the name already.
+pub fn register_backend<F>(name: &str, factory: F) -> Result<(), BluetoothError>
+    where F: Fn() -> Result<Arc<dyn AdapterBackend>, BluetoothError> + Send + Sync + 'static
+{
+    let mut backends = lock(&REGISTERED_BACKENDS);
+    if backends.contains_key(name) {
+        return Err(BluetoothError::InvalidState);
+    }
+    backends.insert(name.to_owned(), Arc::new(factory));
+    Ok(())
+}
+
+pub fn unregister_backend(name: &str) -> Result<(), BluetoothError> {
+    match lock(&REGISTERED_BACKENDS).remove(name) {
+        Some(_) => Ok(()),
+        None => Err(BluetoothError::NotFound),
+    }
+}
+
+pub fn get_registered_backends() -> Vec<String> {
+    lock(&REGISTERED_BACKENDS).keys().cloned().collect()
+}
+
+// The factory is called without the lock held, so that it can register backends itself.
+pub(crate) fn create_registered_backend(name: &str) -> Result<Arc<dyn AdapterBackend>, BluetoothError> {
+    let factory = lock(&REGISTERED_BACKENDS).get(name).cloned().ok_or(BluetoothError::NotFound)?;
+    factory()
+}
@@ -0,0 +1,78 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+// The GATT blocklist of Web Bluetooth.
+// https://github.com/WebBluetoothCG/registries/blob/master/gatt_blocklist.txt
+
+use error::BluetoothError;
+use std::collections::HashMap;
+use uuid::BluetoothUuid;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BlocklistEntry {
+    // The attribute is not reported at all.
+    Exclude,
+    ExcludeReads,
+    ExcludeWrites,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Blocklist {
+    entries: HashMap<BluetoothUuid, BlocklistEntry>,
+}
+
+impl Blocklist {
+    pub fn new() -> Blocklist {
+        Blocklist::default()
+    }
+
+    // Every line is empty, a comment starting with '#', or a UUID in the 128-bit form,
+    // optionally followed by "exclude", "exclude-reads" or "exclude-writes".
+    pub fn parse(text: &str) -> Result<Blocklist, BluetoothError> {
+        let mut blocklist = Blocklist::new();
+        for (index, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let invalid = || BluetoothError::InvalidArgument(format!("gatt_blocklist.txt line {}: {}", index + 1, line));
+            let mut tokens = line.split_whitespace();
+            let uuid = match tokens.next() {
+                Some(uuid) if uuid.len() == 36 => BluetoothUuid::parse(uuid).map_err(|_| invalid())?,
+                _ => return Err(invalid()),
+            };
+            let entry = match tokens.next() {
+                None | Some("exclude") => BlocklistEntry::Exclude,
+                Some("exclude-reads") => BlocklistEntry::ExcludeReads,
+                Some("exclude-writes") => BlocklistEntry::ExcludeWrites,
+                Some(_) => return Err(invalid()),
+            };
+            if tokens.next().is_some() {
+                return Err(invalid());
+            }
+            blocklist.insert(uuid, entry);
+        }
+        Ok(blocklist)
+    }
+
+    pub fn insert(&mut self, uuid: BluetoothUuid, entry: BlocklistEntry) {
+        self.entries.insert(uuid, entry);
+    }
+
+    pub fn get(&self, uuid: &BluetoothUuid) -> Option<BlocklistEntry> {
+        self.entries.get(uuid).cloned()
+    }
+
+    pub fn is_excluded(&self, uuid: &BluetoothUuid) -> bool {
+        self.get(uuid) == Some(BlocklistEntry::Exclude)
+    }
+
+    pub fn is_excluded_reads(&self, uuid: &BluetoothUuid) -> bool {
+        matches!(self.get(uuid), Some(BlocklistEntry::Exclude) | Some(BlocklistEntry::ExcludeReads))
+    }
+
+    pub fn is_excluded_writes(&self, uuid: &BluetoothUuid) -> bool {
+        matches!(self.get(uuid), Some(BlocklistEntry::Exclude) | Some(BlocklistEntry::ExcludeWrites))
+    }
+}
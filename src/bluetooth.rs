@@ -4,935 +4,1873 @@
 
 #[cfg(all(target_os = "linux", feature = "bluetooth"))]
 use blurz::bluetooth_adapter::BluetoothAdapter as BluetoothAdapterBluez;
+#[cfg(all(target_os = "linux", feature = "bluetooth"))]
+use dbus::{self, BusType, Connection, Message, MessageItem};
 #[cfg(all(target_os = "android", feature = "bluetooth"))]
 use blurdroid::bluetooth_adapter::Adapter as BluetoothAdapterAndroid;
 #[cfg(not(any(all(target_os = "linux", feature = "bluetooth"), all(target_os = "android", feature = "bluetooth"))))]
 use empty::BluetoothAdapter as BluetoothAdapterEmpty;
+
+use backend::{self, AdapterBackend, CharacteristicBackend, DescriptorBackend, DeviceBackend, ServiceBackend, SessionBackend};
 #[cfg(feature = "bluetooth-test")]
 use blurmock::fake_adapter::FakeBluetoothAdapter;
-#[cfg(all(target_os = "linux", feature = "bluetooth"))]
-use blurz::bluetooth_device::BluetoothDevice as BluetoothDeviceBluez;
-#[cfg(all(target_os = "android", feature = "bluetooth"))]
-use blurdroid::bluetooth_device::Device as BluetoothDeviceAndroid;
-#[cfg(not(any(all(target_os = "linux", feature = "bluetooth"), all(target_os = "android", feature = "bluetooth"))))]
-use empty::BluetoothDevice as BluetoothDeviceEmpty;
-#[cfg(feature = "bluetooth-test")]
-use blurmock::fake_device::FakeBluetoothDevice;
-#[cfg(all(target_os = "linux", feature = "bluetooth"))]
-use blurz::bluetooth_gatt_characteristic::BluetoothGATTCharacteristic as BluetoothGATTCharacteristicBluez;
-#[cfg(all(target_os = "android", feature = "bluetooth"))]
-use blurdroid::bluetooth_gatt_characteristic::Characteristic as BluetoothGATTCharacteristicAndroid;
-#[cfg(not(any(all(target_os = "linux", feature = "bluetooth"), all(target_os = "android", feature = "bluetooth"))))]
-use empty::BluetoothGATTCharacteristic as BluetoothGATTCharacteristicEmpty;
 #[cfg(feature = "bluetooth-test")]
 use blurmock::fake_characteristic::FakeBluetoothGATTCharacteristic;
-#[cfg(all(target_os = "linux", feature = "bluetooth"))]
-use blurz::bluetooth_gatt_descriptor::BluetoothGATTDescriptor as BluetoothGATTDescriptorBluez;
-#[cfg(all(target_os = "android", feature = "bluetooth"))]
-use blurdroid::bluetooth_gatt_descriptor::Descriptor as BluetoothGATTDescriptorAndroid;
-#[cfg(not(any(all(target_os = "linux", feature = "bluetooth"), all(target_os = "android", feature = "bluetooth"))))]
-use empty::BluetoothGATTDescriptor as BluetoothGATTDescriptorEmpty;
 #[cfg(feature = "bluetooth-test")]
 use blurmock::fake_descriptor::FakeBluetoothGATTDescriptor;
-#[cfg(all(target_os = "linux", feature = "bluetooth"))]
-use blurz::bluetooth_gatt_service::BluetoothGATTService as BluetoothGATTServiceBluez;
-#[cfg(all(target_os = "android", feature = "bluetooth"))]
-use blurdroid::bluetooth_gatt_service::Service as BluetoothGATTServiceAndroid;
-#[cfg(not(any(all(target_os = "linux", feature = "bluetooth"), all(target_os = "android", feature = "bluetooth"))))]
-use empty::BluetoothGATTService as BluetoothGATTServiceEmpty;
 #[cfg(feature = "bluetooth-test")]
-use blurmock::fake_service::FakeBluetoothGATTService;
-#[cfg(all(target_os = "linux", feature = "bluetooth"))]
-use blurz::bluetooth_discovery_session::BluetoothDiscoverySession as BluetoothDiscoverySessionBluez;
-#[cfg(all(target_os = "android", feature = "bluetooth"))]
-use blurdroid::bluetooth_discovery_session::DiscoverySession as BluetoothDiscoverySessionAndroid;
-#[cfg(not(any(all(target_os = "linux", feature = "bluetooth"), all(target_os = "android", feature = "bluetooth"))))]
-use empty::BluetoothDiscoverySession as BluetoothDiscoverySessionEmpty;
+use blurmock::fake_device::FakeBluetoothDevice;
 #[cfg(feature = "bluetooth-test")]
-use blurmock::fake_discovery_session::FakeBluetoothDiscoverySession;
-
-use std::sync::Arc;
-use std::error::Error;
+use blurmock::fake_service::FakeBluetoothGATTService;
 
+use blocklist::Blocklist;
+use discovery_filter::DiscoveryFilter;
+use error::{ATT_INVALID_ATTRIBUTE_VALUE_LENGTH, ATT_INVALID_OFFSET};
+use events::EventSubscribers;
+#[cfg(feature = "bluetooth-test")]
+use mock::{self, MockDevice};
+use operation_queue::OperationQueue;
+use pairing::PairingAgent;
 #[cfg(feature = "bluetooth-test")]
-const NOT_SUPPORTED_ON_REAL_ERROR: &'static str = "Error! Test functions are not supported on real devices!";
+use pairing::IoCapability;
 #[cfg(feature = "bluetooth-test")]
-const NOT_SUPPORTED_ON_MOCK_ERROR: &'static str = "Error! The first parameter must be a mock structure!";
+use preset;
+#[cfg(any(feature = "bluetooth-test", all(target_os = "linux", feature = "bluetooth")))]
+use std::any::Any;
+use std::collections::{BTreeMap, HashMap, HashSet};
+#[cfg(all(target_os = "linux", feature = "bluetooth"))]
+use std::error::Error;
+use std::fmt;
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::Duration;
+
+pub use address::{BluetoothAddress, BluetoothAddressType};
+pub use error::BluetoothError;
+pub use events::{BluetoothEvent, DeviceProperty};
+pub use properties::{CharacteristicProperties, DescriptorPermissions};
+pub use uuid::BluetoothUuid;
+
 
 #[derive(Clone, Debug)]
-pub enum BluetoothAdapter {
-    #[cfg(all(target_os = "linux", feature = "bluetooth"))]
-    Bluez(Arc<BluetoothAdapterBluez>),
-    #[cfg(all(target_os = "android", feature = "bluetooth"))]
-    Android(Arc<BluetoothAdapterAndroid>),
-    #[cfg(not(any(all(target_os = "linux", feature = "bluetooth"), all(target_os = "android", feature = "bluetooth"))))]
-    Empty(Arc<BluetoothAdapterEmpty>),
-    #[cfg(feature = "bluetooth-test")]
-    Mock(Arc<FakeBluetoothAdapter>),
+pub struct BluetoothAdapter {
+    backend: Arc<dyn AdapterBackend>,
+    pub(crate) context: Arc<AdapterContext>,
 }
 
+// State shared by an adapter and every device, service, characteristic and descriptor
+// reached through it.
+#[derive(Debug, Default)]
+pub(crate) struct AdapterContext {
+    device_index: Mutex<DeviceIndex>,
+    subscribers: EventSubscribers,
+    // The state seen by the last `poll_events` call, `None` until the first one.
+    snapshot: Mutex<Option<AdapterSnapshot>>,
+    // Characteristics with notifications started, by their id.
+    notifying: Mutex<BTreeMap<String, NotifyingCharacteristic>>,
+    // How long the calls made through the async API, and the connection attempts of a
+    // `BluetoothConnection`, wait before calling a mock backend.
+    mock_delay: Mutex<Duration>,
+    blocklist: Mutex<Option<Arc<Blocklist>>>,
+    // The GATT operation queues of the devices, by their id.
+    operation_queues: Mutex<HashMap<String, Arc<OperationQueue>>>,
+    agent: Mutex<Option<RegisteredAgent>>,
+}
+
+// The agent does not need to implement Debug.
+#[derive(Clone)]
+struct RegisteredAgent(Arc<dyn PairingAgent>);
+
 #[derive(Debug)]
-pub enum BluetoothDiscoverySession {
-    #[cfg(all(target_os = "linux", feature = "bluetooth"))]
-    Bluez(Arc<BluetoothDiscoverySessionBluez>),
-    #[cfg(all(target_os = "android", feature = "bluetooth"))]
-    Android(Arc<BluetoothDiscoverySessionAndroid>),
-    #[cfg(not(any(all(target_os = "linux", feature = "bluetooth"), all(target_os = "android", feature = "bluetooth"))))]
-    Empty(Arc<BluetoothDiscoverySessionEmpty>),
-    #[cfg(feature = "bluetooth-test")]
-    Mock(Arc<FakeBluetoothDiscoverySession>),
+struct NotifyingCharacteristic {
+    backend: Arc<dyn CharacteristicBackend>,
+    // The id of the device, whose removal stops the notifications.
+    device_id: String,
+    // The last value seen, or notified.
+    value: Option<Vec<u8>>,
+    // The senders of the receivers handed out by `BluetoothGATTCharacteristic::get_notifications`.
+    receivers: Vec<Sender<Vec<u8>>>,
+}
+
+// Devices of an adapter, keyed by their id and by their address. Devices whose
+// address could not be read are only indexed by their id.
+#[derive(Debug, Default)]
+struct DeviceIndex {
+    devices: HashMap<String, (Arc<dyn DeviceBackend>, Option<BluetoothAddress>)>,
+    addresses: HashMap<BluetoothAddress, String>,
+}
+
+#[derive(Clone, Debug, Default)]
+struct AdapterSnapshot {
+    powered: Option<bool>,
+    devices: BTreeMap<String, DeviceSnapshot>,
+}
+
+// The device properties reported by `BluetoothEvent::PropertyChanged`,
+// `None` if the backend could not read them.
+#[derive(Clone, Debug, Default)]
+struct DeviceSnapshot {
+    name: Option<String>,
+    rssi: Option<i16>,
+    tx_power: Option<i16>,
+    connected: Option<bool>,
+    paired: Option<bool>,
+    uuids: Option<Vec<BluetoothUuid>>,
 }
 
+#[derive(Debug)]
+pub struct BluetoothDiscoverySession {
+    backend: Arc<dyn SessionBackend>,
+    adapter: BluetoothAdapter,
+    filter: DiscoveryFilter,
+    // Whether the platform discovers only the devices matching the filter, but for the name.
+    platform_filter: bool,
+    // The devices returned by `get_new_devices`, with the RSSI they had then.
+    reported: Mutex<HashMap<String, Option<i16>>>,
+}
+
+
 #[derive(Clone, Debug)]
-pub enum BluetoothDevice {
-    #[cfg(all(target_os = "linux", feature = "bluetooth"))]
-    Bluez(Arc<BluetoothDeviceBluez>),
-    #[cfg(all(target_os = "android", feature = "bluetooth"))]
-    Android(Arc<BluetoothDeviceAndroid>),
-    #[cfg(not(any(all(target_os = "linux", feature = "bluetooth"), all(target_os = "android", feature = "bluetooth"))))]
-    Empty(Arc<BluetoothDeviceEmpty>),
-    #[cfg(feature = "bluetooth-test")]
-    Mock(Arc<FakeBluetoothDevice>),
+pub struct BluetoothDevice {
+    backend: Arc<dyn DeviceBackend>,
+    pub(crate) context: Arc<AdapterContext>,
 }
 
+
 #[derive(Clone, Debug)]
-pub enum BluetoothGATTService {
-    #[cfg(all(target_os = "linux", feature = "bluetooth"))]
-    Bluez(Arc<BluetoothGATTServiceBluez>),
-    #[cfg(all(target_os = "android", feature = "bluetooth"))]
-    Android(Arc<BluetoothGATTServiceAndroid>),
-    #[cfg(not(any(all(target_os = "linux", feature = "bluetooth"), all(target_os = "android", feature = "bluetooth"))))]
-    Empty(Arc<BluetoothGATTServiceEmpty>),
-    #[cfg(feature = "bluetooth-test")]
-    Mock(Arc<FakeBluetoothGATTService>),
+pub struct BluetoothGATTService {
+    backend: Arc<dyn ServiceBackend>,
+    pub(crate) context: Arc<AdapterContext>,
+    // The backend of the device, which has the ATT_MTU.
+    device: Arc<dyn DeviceBackend>,
+    // The operation queue of the device.
+    queue: Arc<OperationQueue>,
 }
 
+
 #[derive(Clone, Debug)]
-pub enum BluetoothGATTCharacteristic {
-    #[cfg(all(target_os = "linux", feature = "bluetooth"))]
-    Bluez(Arc<BluetoothGATTCharacteristicBluez>),
-    #[cfg(all(target_os = "android", feature = "bluetooth"))]
-    Android(Arc<BluetoothGATTCharacteristicAndroid>),
-    #[cfg(not(any(all(target_os = "linux", feature = "bluetooth"), all(target_os = "android", feature = "bluetooth"))))]
-    Empty(Arc<BluetoothGATTCharacteristicEmpty>),
-    #[cfg(feature = "bluetooth-test")]
-    Mock(Arc<FakeBluetoothGATTCharacteristic>),
+pub struct BluetoothGATTCharacteristic {
+    backend: Arc<dyn CharacteristicBackend>,
+    pub(crate) context: Arc<AdapterContext>,
+    // The backend of the device, which has the ATT_MTU.
+    device: Arc<dyn DeviceBackend>,
+    // The operation queue of the device.
+    queue: Arc<OperationQueue>,
 }
 
+
 #[derive(Clone, Debug)]
-pub enum BluetoothGATTDescriptor {
-    #[cfg(all(target_os = "linux", feature = "bluetooth"))]
-    Bluez(Arc<BluetoothGATTDescriptorBluez>),
-    #[cfg(all(target_os = "android", feature = "bluetooth"))]
-    Android(Arc<BluetoothGATTDescriptorAndroid>),
-    #[cfg(not(any(all(target_os = "linux", feature = "bluetooth"), all(target_os = "android", feature = "bluetooth"))))]
-    Empty(Arc<BluetoothGATTDescriptorEmpty>),
-    #[cfg(feature = "bluetooth-test")]
-    Mock(Arc<FakeBluetoothGATTDescriptor>),
+pub struct BluetoothGATTDescriptor {
+    backend: Arc<dyn DescriptorBackend>,
+    pub(crate) context: Arc<AdapterContext>,
+    // The backend of the device, which has the ATT_MTU.
+    device: Arc<dyn DeviceBackend>,
+    // The operation queue of the device.
+    queue: Arc<OperationQueue>,
 }
 
-macro_rules! get_inner_and_call(
-    ($enum_value: expr, $enum_type: ident, $function_name: ident) => {
-        match $enum_value {
-            #[cfg(all(target_os = "linux", feature = "bluetooth"))]
-            &$enum_type::Bluez(ref bluez) => bluez.$function_name(),
-            #[cfg(all(target_os = "android", feature = "bluetooth"))]
-            &$enum_type::Android(ref android) => android.$function_name(),
-            #[cfg(not(any(all(target_os = "linux", feature = "bluetooth"), all(target_os = "android", feature = "bluetooth"))))]
-            &$enum_type::Empty(ref empty) => empty.$function_name(),
-            #[cfg(feature = "bluetooth-test")]
-            &$enum_type::Mock(ref fake) => fake.$function_name(),
-        }
-    };
 
-    ($enum_value: expr, $enum_type: ident, $function_name: ident, $value: expr) => {
-        match $enum_value {
-            #[cfg(all(target_os = "linux", feature = "bluetooth"))]
-            &$enum_type::Bluez(ref bluez) => bluez.$function_name($value),
-            #[cfg(all(target_os = "android", feature = "bluetooth"))]
-            &$enum_type::Android(ref android) => android.$function_name($value),
-            #[cfg(not(any(all(target_os = "linux", feature = "bluetooth"), all(target_os = "android", feature = "bluetooth"))))]
-            &$enum_type::Empty(ref empty) => empty.$function_name($value),
-            #[cfg(feature = "bluetooth-test")]
-            &$enum_type::Mock(ref fake) => fake.$function_name($value),
+// The ATT_MTU until an other one is exchanged, and the largest attribute value.
+// https://www.bluetooth.com/specifications/bluetooth-core-specification
+// Vol 3, Part G, 5.2.1 and Part F, 3.2.9.
+pub(crate) const ATT_DEFAULT_MTU: u16 = 23;
+const ATT_MAX_VALUE_LENGTH: usize = 512;
+
+#[cfg(all(target_os = "linux", feature = "bluetooth"))]
+const BLUEZ_SERVICE: &str = "org.bluez";
+#[cfg(all(target_os = "linux", feature = "bluetooth"))]
+const BLUEZ_ADAPTER_INTERFACE: &str = "org.bluez.Adapter1";
+// How long a D-Bus call waits for BlueZ, in milliseconds, like the calls of blurz.
+#[cfg(all(target_os = "linux", feature = "bluetooth"))]
+const DBUS_TIMEOUT: i32 = 1000;
+
+// The GATT procedure used to write a characteristic value.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum WriteType {
+    // Write Characteristic Value, acknowledged by the remote device.
+    WithResponse,
+    // Write Without Response, which is not acknowledged.
+    WithoutResponse,
+}
+
+impl WriteType {
+    // The characteristic property which allows the procedure.
+    fn get_property(&self) -> CharacteristicProperties {
+        match *self {
+            WriteType::WithResponse => CharacteristicProperties::WRITE,
+            WriteType::WithoutResponse => CharacteristicProperties::WRITE_WITHOUT_RESPONSE,
         }
+    }
+}
+
+// The mock backend of a `backend` field, if it is one.
+#[cfg(feature = "bluetooth-test")]
+macro_rules! get_fake(
+    ($backend: expr, $fake_type: ident) => {
+        (&*$backend as &dyn Any).downcast_ref::<$fake_type>()
     };
 );
 
+macro_rules! is_mock(
+    ($backend: expr, $fake_type: ident) => {{
+        #[cfg(feature = "bluetooth-test")]
+        let is_mock = get_fake!($backend, $fake_type).is_some();
+        #[cfg(not(feature = "bluetooth-test"))]
+        let is_mock = false;
+        is_mock
+    }};
+);
+
 #[cfg(feature = "bluetooth-test")]
 macro_rules! get_inner_and_call_test_func {
-    ($enum_value: expr, $enum_type: ident, $function_name: ident, $value: expr) => {
-        match $enum_value {
-            &$enum_type::Mock(ref fake) => fake.$function_name($value),
-            _ => Err(Box::from(NOT_SUPPORTED_ON_REAL_ERROR)),
+    ($backend: expr, $fake_type: ident, $function_name: ident, $value: expr) => {
+        match get_fake!($backend, $fake_type) {
+            Some(fake) => fake.$function_name($value).map_err(BluetoothError::from),
+            None => Err(BluetoothError::NotSupported),
         }
     };
 
-    ($enum_value: expr, $enum_type: ident, $function_name: ident) => {
-        match $enum_value {
-            &$enum_type::Mock(ref fake) => fake.$function_name(),
-            _ => Err(Box::from(NOT_SUPPORTED_ON_REAL_ERROR)),
+    ($backend: expr, $fake_type: ident, $function_name: ident) => {
+        match get_fake!($backend, $fake_type) {
+            Some(fake) => fake.$function_name().map_err(BluetoothError::from),
+            None => Err(BluetoothError::NotSupported),
         }
     };
 }
 
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    match mutex.lock() {
+        Ok(guard) => guard,
+        Err(poisoned) => poisoned.into_inner(),
+    }
+}
+
+// The part of the value a Read Blob Request at the offset returns. Without a payload limit, the
+// backend reads the long value itself, and the whole rest of the value is returned.
+fn read_blob(mut value: Vec<u8>, offset: u16, max_payload: Option<usize>) -> Result<Vec<u8>, BluetoothError> {
+    let offset = offset as usize;
+    if offset > value.len() {
+        return Err(BluetoothError::GattError(ATT_INVALID_OFFSET));
+    }
+    let mut part = value.split_off(offset);
+    if let Some(max_payload) = max_payload {
+        part.truncate(max_payload);
+    }
+    Ok(part)
+}
+
+// The object paths of the BlueZ adapters, from a single GetManagedObjects call, in the order of
+// their index, e.g. "/org/bluez/hci0", then "/org/bluez/hci1".
+#[cfg(all(target_os = "linux", feature = "bluetooth"))]
+fn get_bluez_adapter_paths() -> Result<Vec<String>, BluetoothError> {
+    let dbus_error = |error: dbus::Error| BluetoothError::from(Box::new(error) as Box<dyn Error>);
+    let malformed = |_| BluetoothError::Backend(String::from("malformed GetManagedObjects reply"));
+    let connection = Connection::get_private(BusType::System).map_err(dbus_error)?;
+    let request = Message::new_method_call(BLUEZ_SERVICE, "/", "org.freedesktop.DBus.ObjectManager", "GetManagedObjects")
+        .map_err(BluetoothError::Backend)?;
+    let reply = connection.send_with_reply_and_block(request, DBUS_TIMEOUT).map_err(dbus_error)?;
+    let items = reply.get_items();
+    let objects: &[MessageItem] = items.first().ok_or(()).and_then(MessageItem::inner).map_err(malformed)?;
+    let mut paths = vec![];
+    for object in objects {
+        let (path, interfaces): (&MessageItem, &MessageItem) = object.inner().map_err(malformed)?;
+        let interfaces: &[MessageItem] = interfaces.inner().map_err(malformed)?;
+        let is_adapter = interfaces.iter().any(|interface| {
+            let name = interface.inner::<(&MessageItem, &MessageItem)>().and_then(|(name, _)| name.inner::<&str>());
+            name == Ok(BLUEZ_ADAPTER_INTERFACE)
+        });
+        if is_adapter {
+            paths.push(path.inner::<&str>().map_err(malformed)?.to_owned());
+        }
+    }
+    paths.sort_by(|a, b| (a.len(), a).cmp(&(b.len(), b)));
+    Ok(paths)
+}
+
+// A BlueZ discovery session, which sets the discovery filter before it starts. BlueZ keeps a
+// filter for each D-Bus client, so the session calls SetDiscoveryFilter and StartDiscovery on
+// the same connection, which blurz does not expose.
+#[cfg(all(target_os = "linux", feature = "bluetooth"))]
+#[derive(Debug)]
+struct BluezDiscoverySession {
+    adapter: String,
+    connection: Connection,
+}
+
+#[cfg(all(target_os = "linux", feature = "bluetooth"))]
+impl BluezDiscoverySession {
+    fn create_session(adapter: String, filter: &DiscoveryFilter) -> Result<BluezDiscoverySession, BluetoothError> {
+        let connection = Connection::get_private(BusType::System)
+            .map_err(|error| BluetoothError::from(Box::new(error) as Box<dyn Error>))?;
+        let session = BluezDiscoverySession {
+            adapter,
+            connection,
+        };
+        session.call_method("SetDiscoveryFilter", Some(filter.to_bluez_filter()))?;
+        Ok(session)
+    }
+
+    fn call_method(&self, method: &str, param: Option<MessageItem>) -> Result<(), BluetoothError> {
+        let mut request = Message::new_method_call(BLUEZ_SERVICE, &self.adapter, BLUEZ_ADAPTER_INTERFACE, method)
+            .map_err(BluetoothError::Backend)?;
+        if let Some(param) = param {
+            request.append_items(&[param]);
+        }
+        self.connection.send_with_reply_and_block(request, DBUS_TIMEOUT)
+            .map_err(|error| BluetoothError::from(Box::new(error) as Box<dyn Error>))?;
+        Ok(())
+    }
+}
+
+#[cfg(all(target_os = "linux", feature = "bluetooth"))]
+impl SessionBackend for BluezDiscoverySession {
+    fn start_discovery(&self) -> Result<(), BluetoothError> {
+        self.call_method("StartDiscovery", None)
+    }
+
+    fn stop_discovery(&self) -> Result<(), BluetoothError> {
+        self.call_method("StopDiscovery", None)
+    }
+}
+
+fn find_adapter<F>(adapters: Vec<BluetoothAdapter>, predicate: F) -> Result<BluetoothAdapter, BluetoothError>
+    where F: Fn(&BluetoothAdapter) -> bool
+{
+    adapters.into_iter().find(predicate).ok_or(BluetoothError::NotFound)
+}
+
+// The value, if it is known and differs from the previous one.
+fn changed<T: Clone + PartialEq>(previous: &Option<T>, current: &Option<T>) -> Option<T> {
+    match *current {
+        Some(ref value) if previous.as_ref() != Some(value) => Some(value.clone()),
+        _ => None,
+    }
+}
+
+impl AdapterContext {
+    pub(crate) fn get_mock_delay(&self) -> Duration {
+        *lock(&self.mock_delay)
+    }
+
+    fn get_operation_queue(&self, device_id: String) -> Arc<OperationQueue> {
+        lock(&self.operation_queues).entry(device_id).or_default().clone()
+    }
+
+    // Forgets the notifying characteristics of a removed device. The operation queue is kept, as
+    // the services, characteristics and descriptors taken from the device still hold it, and
+    // have to share it with the ones of a device added again with the same id.
+    fn remove_device_state(&self, device_id: &str) {
+        lock(&self.notifying).retain(|_, characteristic| characteristic.device_id != device_id);
+    }
+
+    fn get_agent(&self) -> Option<Arc<dyn PairingAgent>> {
+        lock(&self.agent).as_ref().map(|agent| agent.0.clone())
+    }
+
+    fn get_blocklist(&self) -> Option<Arc<Blocklist>> {
+        lock(&self.blocklist).clone()
+    }
+
+    // Drops the attributes excluded by the blocklist, and the ones whose UUID can not be read,
+    // as those can not be checked.
+    fn remove_blocklisted<T, F>(&self, attributes: Vec<T>, get_uuid: F) -> Vec<T>
+        where F: Fn(&T) -> Result<BluetoothUuid, BluetoothError>
+    {
+        match self.get_blocklist() {
+            Some(blocklist) => attributes.into_iter()
+                                         .filter(|attribute| get_uuid(attribute).is_ok_and(|uuid| !blocklist.is_excluded(&uuid)))
+                                         .collect(),
+            None => attributes,
+        }
+    }
+
+    fn check_read<F>(&self, get_uuid: F) -> Result<(), BluetoothError>
+        where F: Fn() -> Result<BluetoothUuid, BluetoothError>
+    {
+        match self.get_blocklist() {
+            Some(ref blocklist) if blocklist.is_excluded_reads(&get_uuid()?) => Err(BluetoothError::Blocklisted),
+            _ => Ok(()),
+        }
+    }
+
+    fn check_write<F>(&self, get_uuid: F) -> Result<(), BluetoothError>
+        where F: Fn() -> Result<BluetoothUuid, BluetoothError>
+    {
+        match self.get_blocklist() {
+            Some(ref blocklist) if blocklist.is_excluded_writes(&get_uuid()?) => Err(BluetoothError::Blocklisted),
+            _ => Ok(()),
+        }
+    }
+
+    // Reads the value of every notifying characteristic, and reports the ones that changed.
+    fn notification_events(&self, events: &mut Vec<BluetoothEvent>) {
+        for (id, characteristic) in lock(&self.notifying).iter_mut() {
+            let value = characteristic.backend.get_value().ok();
+            if let Some(value) = changed(&characteristic.value, &value) {
+                characteristic.notify(value.clone());
+                events.push(BluetoothEvent::CharacteristicValueChanged(id.clone(), value));
+            }
+        }
+    }
+}
+
+impl fmt::Debug for RegisteredAgent {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "RegisteredAgent({:?})", self.0.get_io_capability())
+    }
+}
+
+impl NotifyingCharacteristic {
+    fn notify(&mut self, value: Vec<u8>) {
+        self.receivers.retain(|sender| sender.send(value.clone()).is_ok());
+        self.value = Some(value);
+    }
+}
+
+impl DeviceIndex {
+    // Indexes the device by its id, and by its address, if it can be read.
+    fn insert(&mut self, device: &BluetoothDevice) {
+        let id = device.get_id();
+        self.remove(&id);
+        let address = device.get_address().ok();
+        if let Some(address) = address {
+            self.addresses.insert(address, id.clone());
+        }
+        self.devices.insert(id, (device.backend.clone(), address));
+    }
+
+    fn remove(&mut self, id: &str) {
+        if let Some((_, Some(address))) = self.devices.remove(id) {
+            if self.addresses.get(&address).is_some_and(|indexed_id| indexed_id == id) {
+                self.addresses.remove(&address);
+            }
+        }
+    }
+}
+
+impl AdapterSnapshot {
+    fn events(&self, current: &AdapterSnapshot, events: &mut Vec<BluetoothEvent>) {
+        if let Some(powered) = changed(&self.powered, &current.powered) {
+            events.push(BluetoothEvent::AdapterPoweredChanged(powered));
+        }
+        for id in self.devices.keys().filter(|id| !current.devices.contains_key(*id)) {
+            events.push(BluetoothEvent::DeviceRemoved(id.clone()));
+        }
+        for (id, device) in &current.devices {
+            match self.devices.get(id) {
+                Some(previous) => previous.events(id, device, events),
+                None => events.push(BluetoothEvent::DeviceAdded(id.clone())),
+            }
+        }
+    }
+}
+
+impl DeviceSnapshot {
+    fn take(device: &BluetoothDevice) -> DeviceSnapshot {
+        DeviceSnapshot {
+            name: device.get_name().ok(),
+            rssi: device.get_rssi().ok(),
+            tx_power: device.get_tx_power().ok(),
+            connected: device.is_connected().ok(),
+            paired: device.is_paired().ok(),
+            uuids: device.get_uuids().ok(),
+        }
+    }
+
+    fn events(&self, id: &str, current: &DeviceSnapshot, events: &mut Vec<BluetoothEvent>) {
+        let properties = vec![
+            changed(&self.name, &current.name).map(DeviceProperty::Name),
+            changed(&self.rssi, &current.rssi).map(DeviceProperty::Rssi),
+            changed(&self.tx_power, &current.tx_power).map(DeviceProperty::TxPower),
+            changed(&self.connected, &current.connected).map(DeviceProperty::Connected),
+            changed(&self.paired, &current.paired).map(DeviceProperty::Paired),
+            changed(&self.uuids, &current.uuids).map(DeviceProperty::Uuids),
+        ];
+        for property in properties.into_iter().flatten() {
+            events.push(BluetoothEvent::PropertyChanged(id.to_owned(), property));
+        }
+    }
+}
+
 impl BluetoothAdapter {
     #[cfg(all(target_os = "linux", feature = "bluetooth"))]
-    pub fn init() -> Result<BluetoothAdapter, Box<Error>> {
-        let bluez_adapter = try!(BluetoothAdapterBluez::init());
-        Ok(BluetoothAdapter::Bluez(Arc::new(bluez_adapter)))
+    pub fn init() -> Result<BluetoothAdapter, BluetoothError> {
+        let bluez_adapter = BluetoothAdapterBluez::init()?;
+        Ok(BluetoothAdapter::new(Arc::new(bluez_adapter)))
     }
 
     #[cfg(all(target_os = "android", feature = "bluetooth"))]
-    pub fn init() -> Result<BluetoothAdapter, Box<Error>> {
-        let blurdroid_adapter = try!(BluetoothAdapterAndroid::get_adapter());
-        Ok(BluetoothAdapter::Android(Arc::new(blurdroid_adapter)))
+    pub fn init() -> Result<BluetoothAdapter, BluetoothError> {
+        let blurdroid_adapter = BluetoothAdapterAndroid::get_adapter()?;
+        Ok(BluetoothAdapter::new(Arc::new(blurdroid_adapter)))
     }
 
     #[cfg(not(any(all(target_os = "linux", feature = "bluetooth"), all(target_os = "android", feature = "bluetooth"))))]
-    pub fn init() -> Result<BluetoothAdapter, Box<Error>> {
-        let adapter = try!(BluetoothAdapterEmpty::init());
-        Ok(BluetoothAdapter::Empty(Arc::new(adapter)))
+    pub fn init() -> Result<BluetoothAdapter, BluetoothError> {
+        let adapter = BluetoothAdapterEmpty::init()?;
+        Ok(BluetoothAdapter::new(Arc::new(adapter)))
     }
 
     #[cfg(feature = "bluetooth-test")]
-    pub fn init_mock() -> Result<BluetoothAdapter, Box<Error>> {
-        Ok(BluetoothAdapter::Mock(FakeBluetoothAdapter::new_empty()))
+    pub fn init_mock() -> Result<BluetoothAdapter, BluetoothError> {
+        Ok(BluetoothAdapter::new(FakeBluetoothAdapter::new_empty()))
+    }
+
+    // A mock adapter with the devices of one of the named fake adapters of the Web Bluetooth
+    // tests, e.g. "HeartRateAdapter", see `preset::PRESETS`.
+    #[cfg(feature = "bluetooth-test")]
+    pub fn init_preset(name: &str) -> Result<BluetoothAdapter, BluetoothError> {
+        preset::create_preset(name)
+    }
+
+    // An adapter of a backend implemented outside of this crate.
+    pub fn init_with_backend(backend: Arc<dyn AdapterBackend>) -> BluetoothAdapter {
+        BluetoothAdapter::new(backend)
+    }
+
+    // An adapter of the backend registered with the name, see `backend::register_backend`.
+    pub fn init_registered_backend(name: &str) -> Result<BluetoothAdapter, BluetoothError> {
+        Ok(BluetoothAdapter::new(backend::create_registered_backend(name)?))
+    }
+
+    // blurz does not expose the object paths of the adapters, so they are read from BlueZ, and
+    // `create_adapter` is called with each of them.
+    #[cfg(all(target_os = "linux", feature = "bluetooth"))]
+    pub fn list() -> Result<Vec<BluetoothAdapter>, BluetoothError> {
+        let mut adapters = vec![];
+        for path in get_bluez_adapter_paths()? {
+            adapters.push(BluetoothAdapter::new(Arc::new(BluetoothAdapterBluez::create_adapter(path)?)));
+        }
+        Ok(adapters)
+    }
+
+    // Android and the other platforms have a single adapter.
+    #[cfg(not(all(target_os = "linux", feature = "bluetooth")))]
+    pub fn list() -> Result<Vec<BluetoothAdapter>, BluetoothError> {
+        Ok(vec![BluetoothAdapter::init()?])
+    }
+
+    // The id is the D-Bus object path of the adapter with BlueZ, e.g. "/org/bluez/hci1".
+    pub fn init_with_id(id: &str) -> Result<BluetoothAdapter, BluetoothError> {
+        find_adapter(BluetoothAdapter::list()?, |adapter| adapter.get_id() == id)
+    }
+
+    pub fn init_with_address(address: BluetoothAddress) -> Result<BluetoothAdapter, BluetoothError> {
+        find_adapter(BluetoothAdapter::list()?, |adapter| adapter.get_address().as_ref() == Ok(&address))
     }
 
     pub fn get_id(&self) -> String {
-        get_inner_and_call!(self, BluetoothAdapter, get_id)
+        self.backend.get_id()
+    }
+
+    // Whether the test functions can be called on the adapter, and on its devices.
+    pub fn is_mock(&self) -> bool {
+        is_mock!(self.backend, FakeBluetoothAdapter)
     }
 
     #[cfg(feature = "bluetooth-test")]
     pub fn set_id(&self, id: String) {
-        match self {
-            &BluetoothAdapter::Mock(ref fake_adapter) => fake_adapter.set_id(id),
-            _ => (),
+        if let Some(fake_adapter) = get_fake!(self.backend, FakeBluetoothAdapter) {
+            fake_adapter.set_id(id);
         }
     }
 
-    pub fn get_devices(&self) -> Result<Vec<BluetoothDevice>, Box<Error>> {
-        let device_list = try!(get_inner_and_call!(self, BluetoothAdapter, get_device_list));
-        Ok(device_list.into_iter().map(|device| BluetoothDevice::create_device(self.clone(), device)).collect())
+    fn new(backend: Arc<dyn AdapterBackend>) -> BluetoothAdapter {
+        BluetoothAdapter {
+            backend,
+            context: Arc::new(AdapterContext::default()),
+        }
     }
 
-    pub fn get_device(&self, address: String) -> Result<Option<BluetoothDevice>, Box<Error>> {
-        let devices = try!(self.get_devices());
-        for device in devices {
-            if try!(device.get_address()) == address {
-                return Ok(Some(device));
-            }
+    fn wrap_device(&self, backend: Arc<dyn DeviceBackend>) -> BluetoothDevice {
+        BluetoothDevice {
+            backend,
+            context: self.context.clone(),
+        }
+    }
+
+    pub fn get_devices(&self) -> Result<Vec<BluetoothDevice>, BluetoothError> {
+        let device_list = self.backend.get_device_list()?;
+        let mut index = lock(&self.context.device_index);
+        let current: HashSet<&String> = device_list.iter().collect();
+        let removed: Vec<String> = index.devices.keys().filter(|id| !current.contains(id)).cloned().collect();
+        for id in &removed {
+            index.remove(id);
+        }
+        let mut devices = Vec::with_capacity(device_list.len());
+        for id in device_list {
+            let device = match index.devices.get(&id) {
+                Some((backend, Some(_))) => {
+                    devices.push(self.wrap_device(backend.clone()));
+                    continue;
+                },
+                Some((backend, None)) => self.wrap_device(backend.clone()),
+                None => BluetoothDevice::create_device(self.clone(), id),
+            };
+            index.insert(&device);
+            devices.push(device);
+        }
+        drop(index);
+        for id in &removed {
+            self.context.remove_device_state(id);
+        }
+        Ok(devices)
+    }
+
+    // Looks the device up in the index first, and only asks the backend for the device list
+    // if the address is not known yet, or does not belong to the indexed device anymore.
+    pub fn get_device(&self, address: BluetoothAddress) -> Result<Option<BluetoothDevice>, BluetoothError> {
+        if let Some(device) = self.get_indexed_device(&address) {
+            return Ok(Some(device));
+        }
+        self.get_devices()?;
+        Ok(self.get_indexed_device(&address))
+    }
+
+    pub fn get_device_by_id(&self, id: &str) -> Result<Option<BluetoothDevice>, BluetoothError> {
+        if let Some(device) = self.get_indexed_device_by_id(id) {
+            return Ok(Some(device));
+        }
+        self.get_devices()?;
+        Ok(self.get_indexed_device_by_id(id))
+    }
+
+    fn get_indexed_device_by_id(&self, id: &str) -> Option<BluetoothDevice> {
+        let index = lock(&self.context.device_index);
+        index.devices.get(id).map(|(backend, _)| self.wrap_device(backend.clone()))
+    }
+
+    fn get_indexed_device(&self, address: &BluetoothAddress) -> Option<BluetoothDevice> {
+        let mut index = lock(&self.context.device_index);
+        let device = match index.addresses.get(address) {
+            Some(id) => self.wrap_device(index.devices[id].0.clone()),
+            None => return None,
+        };
+        match device.get_address() {
+            Ok(ref current) if current == address => Some(device),
+            _ => {
+                index.insert(&device);
+                None
+            },
+        }
+    }
+
+    // Every receiver gets the events found by the `poll_events` calls made after it subscribed.
+    pub fn subscribe(&self) -> Receiver<BluetoothEvent> {
+        let mut snapshot = lock(&self.context.snapshot);
+        if snapshot.is_none() {
+            *snapshot = self.take_snapshot().ok();
+        }
+        self.context.subscribers.subscribe()
+    }
+
+    // None of the backends report changes by themselves, so the adapter, its devices and the
+    // notifying characteristics are compared with their state at the previous call (or at the
+    // first `subscribe` call), and an event is sent to the subscribers for every difference.
+    // The mock backend fires events in the same way: change it with the test functions,
+    // then call `poll_events`.
+    pub fn poll_events(&self) -> Result<(), BluetoothError> {
+        let mut snapshot = lock(&self.context.snapshot);
+        let current = self.take_snapshot()?;
+        let mut events = vec![];
+        if let Some(previous) = snapshot.take() {
+            previous.events(&current, &mut events);
+        }
+        *snapshot = Some(current);
+        self.context.notification_events(&mut events);
+        for event in events {
+            self.context.subscribers.emit(event);
+        }
+        Ok(())
+    }
+
+    fn take_snapshot(&self) -> Result<AdapterSnapshot, BluetoothError> {
+        let mut devices = BTreeMap::new();
+        for device in self.get_devices()? {
+            devices.insert(device.get_id(), DeviceSnapshot::take(&device));
+        }
+        Ok(AdapterSnapshot {
+            powered: self.is_powered().ok(),
+            devices,
+        })
+    }
+
+    #[cfg(feature = "bluetooth-test")]
+    pub fn remove_device(&self, id: String) -> Result<(), BluetoothError> {
+        let fake = get_fake!(self.backend, FakeBluetoothAdapter).ok_or(BluetoothError::NotSupported)?;
+        fake.remove_device(id.clone())?;
+        lock(&self.context.device_index).remove(&id);
+        self.context.remove_device_state(&id);
+        mock::remove(fake, &id);
+        Ok(())
+    }
+
+    // The backend asks the registered agent when pairing, if it implements
+    // `AdapterBackend::register_agent`. BlueZ and Android pair through the agent of the system,
+    // which neither blurz nor blurdroid can replace, so only the mock backend does.
+    pub fn register_agent(&self, agent: Arc<dyn PairingAgent>) -> Result<(), BluetoothError> {
+        let mut registered_agent = lock(&self.context.agent);
+        if registered_agent.is_some() {
+            return Err(BluetoothError::InvalidState);
         }
-        Ok(None)
+        self.backend.register_agent(agent.clone())?;
+        *registered_agent = Some(RegisteredAgent(agent));
+        Ok(())
     }
 
-    pub fn get_address(&self) -> Result<String, Box<Error>> {
-        get_inner_and_call!(self, BluetoothAdapter, get_address)
+    pub fn unregister_agent(&self) -> Result<(), BluetoothError> {
+        match lock(&self.context.agent).take() {
+            Some(_) => self.backend.unregister_agent(),
+            None => Err(BluetoothError::NotFound),
+        }
+    }
+
+    pub fn get_agent(&self) -> Option<Arc<dyn PairingAgent>> {
+        self.context.get_agent()
+    }
+
+    // With a blocklist, the excluded services, characteristics and descriptors are not returned,
+    // and reading or writing the excluded ones fails with `BluetoothError::Blocklisted`.
+    pub fn set_blocklist(&self, blocklist: Option<Blocklist>) {
+        *lock(&self.context.blocklist) = blocklist.map(Arc::new);
+    }
+
+    pub fn get_blocklist(&self) -> Option<Blocklist> {
+        self.context.get_blocklist().map(|blocklist| (*blocklist).clone())
     }
 
     #[cfg(feature = "bluetooth-test")]
-    pub fn set_address(&self, address: String) -> Result<(), Box<Error>> {
-        match self {
-            &BluetoothAdapter::Mock(ref fake_adapter) => fake_adapter.set_address(address),
-            _ => Err(Box::from(NOT_SUPPORTED_ON_REAL_ERROR)),
+    pub fn set_mock_delay(&self, delay: Duration) -> Result<(), BluetoothError> {
+        if !is_mock!(self.backend, FakeBluetoothAdapter) {
+            return Err(BluetoothError::NotSupported);
         }
+        *lock(&self.context.mock_delay) = delay;
+        Ok(())
     }
 
-    pub fn get_name(&self) -> Result<String, Box<Error>> {
-        get_inner_and_call!(self, BluetoothAdapter, get_name)
+    pub fn get_address(&self) -> Result<BluetoothAddress, BluetoothError> {
+        let address = self.backend.get_address()?;
+        BluetoothAddress::parse(&address)
     }
 
     #[cfg(feature = "bluetooth-test")]
-    pub fn set_name(&self, name: String) -> Result<(), Box<Error>> {
-        get_inner_and_call_test_func!(self, BluetoothAdapter, set_name, name)
+    pub fn set_address(&self, address: BluetoothAddress) -> Result<(), BluetoothError> {
+        get_inner_and_call_test_func!(self.backend, FakeBluetoothAdapter, set_address, address.to_string())
     }
 
-    pub fn get_alias(&self) -> Result<String, Box<Error>> {
-        get_inner_and_call!(self, BluetoothAdapter, get_alias)
+    pub fn get_name(&self) -> Result<String, BluetoothError> {
+        self.backend.get_name()
     }
 
     #[cfg(feature = "bluetooth-test")]
-    pub fn set_alias(&self, alias: String) -> Result<(), Box<Error>> {
-        get_inner_and_call_test_func!(self, BluetoothAdapter, set_alias, alias)
+    pub fn set_name(&self, name: String) -> Result<(), BluetoothError> {
+        get_inner_and_call_test_func!(self.backend, FakeBluetoothAdapter, set_name, name)
     }
 
-    pub fn get_class(&self) -> Result<u32, Box<Error>> {
-        get_inner_and_call!(self, BluetoothAdapter, get_class)
+    pub fn get_alias(&self) -> Result<String, BluetoothError> {
+        self.backend.get_alias()
     }
 
     #[cfg(feature = "bluetooth-test")]
-    pub fn set_class(&self, class: u32) -> Result<(), Box<Error>> {
-        get_inner_and_call_test_func!(self, BluetoothAdapter, set_class, class)
+    pub fn set_alias(&self, alias: String) -> Result<(), BluetoothError> {
+        get_inner_and_call_test_func!(self.backend, FakeBluetoothAdapter, set_alias, alias)
     }
 
-    pub fn is_powered(&self) -> Result<bool, Box<Error>> {
-        get_inner_and_call!(self, BluetoothAdapter, is_powered)
+    pub fn get_class(&self) -> Result<u32, BluetoothError> {
+        self.backend.get_class()
     }
 
     #[cfg(feature = "bluetooth-test")]
-    pub fn set_powered(&self, powered: bool) -> Result<(), Box<Error>> {
-        get_inner_and_call_test_func!(self, BluetoothAdapter, set_powered, powered)
+    pub fn set_class(&self, class: u32) -> Result<(), BluetoothError> {
+        get_inner_and_call_test_func!(self.backend, FakeBluetoothAdapter, set_class, class)
+    }
+
+    pub fn is_powered(&self) -> Result<bool, BluetoothError> {
+        self.backend.is_powered()
+    }
+
+    #[cfg(feature = "bluetooth-test")]
+    pub fn set_powered(&self, powered: bool) -> Result<(), BluetoothError> {
+        get_inner_and_call_test_func!(self.backend, FakeBluetoothAdapter, set_powered, powered)
     }
 
     #[cfg(feature = "bluetooth-test")]
-    pub fn is_present(&self) -> Result<bool, Box<Error>> {
-        get_inner_and_call_test_func!(self, BluetoothAdapter, is_present)
+    pub fn is_present(&self) -> Result<bool, BluetoothError> {
+        get_inner_and_call_test_func!(self.backend, FakeBluetoothAdapter, is_present)
     }
 
     #[cfg(feature = "bluetooth-test")]
-    pub fn set_present(&self, present: bool) -> Result<(), Box<Error>> {
-        get_inner_and_call_test_func!(self, BluetoothAdapter, set_present, present)
+    pub fn set_present(&self, present: bool) -> Result<(), BluetoothError> {
+        get_inner_and_call_test_func!(self.backend, FakeBluetoothAdapter, set_present, present)
     }
 
-    pub fn is_discoverable(&self) -> Result<bool, Box<Error>> {
-        get_inner_and_call!(self, BluetoothAdapter, is_discoverable)
+    pub fn is_discoverable(&self) -> Result<bool, BluetoothError> {
+        self.backend.is_discoverable()
     }
 
     #[cfg(feature = "bluetooth-test")]
-    pub fn set_discoverable(&self, discoverable: bool) -> Result<(), Box<Error>> {
-        get_inner_and_call_test_func!(self, BluetoothAdapter, set_discoverable, discoverable)
+    pub fn set_discoverable(&self, discoverable: bool) -> Result<(), BluetoothError> {
+        get_inner_and_call_test_func!(self.backend, FakeBluetoothAdapter, set_discoverable, discoverable)
     }
 
-    pub fn is_pairable(&self) -> Result<bool, Box<Error>> {
-        get_inner_and_call!(self, BluetoothAdapter, is_pairable)
+    pub fn is_pairable(&self) -> Result<bool, BluetoothError> {
+        self.backend.is_pairable()
     }
 
     #[cfg(feature = "bluetooth-test")]
-    pub fn set_pairable(&self, pairable: bool) -> Result<(), Box<Error>> {
-        get_inner_and_call_test_func!(self, BluetoothAdapter, set_pairable, pairable)
+    pub fn set_pairable(&self, pairable: bool) -> Result<(), BluetoothError> {
+        get_inner_and_call_test_func!(self.backend, FakeBluetoothAdapter, set_pairable, pairable)
     }
 
-    pub fn get_pairable_timeout(&self) -> Result<u32, Box<Error>> {
-        get_inner_and_call!(self, BluetoothAdapter, get_pairable_timeout)
+    pub fn get_pairable_timeout(&self) -> Result<u32, BluetoothError> {
+        self.backend.get_pairable_timeout()
     }
 
     #[cfg(feature = "bluetooth-test")]
-    pub fn set_pairable_timeout(&self, timeout: u32) -> Result<(), Box<Error>> {
-        get_inner_and_call_test_func!(self, BluetoothAdapter, set_pairable_timeout, timeout)
+    pub fn set_pairable_timeout(&self, timeout: u32) -> Result<(), BluetoothError> {
+        get_inner_and_call_test_func!(self.backend, FakeBluetoothAdapter, set_pairable_timeout, timeout)
     }
 
-    pub fn get_discoverable_timeout(&self) -> Result<u32, Box<Error>> {
-        get_inner_and_call!(self, BluetoothAdapter, get_discoverable_timeout)
+    pub fn get_discoverable_timeout(&self) -> Result<u32, BluetoothError> {
+        self.backend.get_discoverable_timeout()
     }
 
     #[cfg(feature = "bluetooth-test")]
-    pub fn set_discoverable_timeout(&self, timeout: u32) -> Result<(), Box<Error>> {
-        get_inner_and_call_test_func!(self, BluetoothAdapter, set_discoverable_timeout, timeout)
+    pub fn set_discoverable_timeout(&self, timeout: u32) -> Result<(), BluetoothError> {
+        get_inner_and_call_test_func!(self.backend, FakeBluetoothAdapter, set_discoverable_timeout, timeout)
     }
 
-    pub fn is_discovering(&self) -> Result<bool, Box<Error>> {
-        get_inner_and_call!(self, BluetoothAdapter, is_discovering)
+    pub fn is_discovering(&self) -> Result<bool, BluetoothError> {
+        self.backend.is_discovering()
     }
 
     #[cfg(feature = "bluetooth-test")]
-    pub fn set_discovering(&self, discovering: bool) -> Result<(), Box<Error>> {
-        get_inner_and_call_test_func!(self, BluetoothAdapter, set_discovering, discovering)
+    pub fn set_discovering(&self, discovering: bool) -> Result<(), BluetoothError> {
+        get_inner_and_call_test_func!(self.backend, FakeBluetoothAdapter, set_discovering, discovering)
     }
 
     #[cfg(feature = "bluetooth-test")]
-    pub fn set_can_start_discovery(&self, can_start_discovery: bool) -> Result<(), Box<Error>> {
-        get_inner_and_call_test_func!(self, BluetoothAdapter, set_can_start_discovery, can_start_discovery)
+    pub fn set_can_start_discovery(&self, can_start_discovery: bool) -> Result<(), BluetoothError> {
+        get_inner_and_call_test_func!(self.backend, FakeBluetoothAdapter, set_can_start_discovery, can_start_discovery)
     }
 
     #[cfg(feature = "bluetooth-test")]
-    pub fn set_can_stop_discovery(&self, can_stop_discovery: bool) -> Result<(), Box<Error>> {
-        get_inner_and_call_test_func!(self, BluetoothAdapter, set_can_stop_discovery, can_stop_discovery)
+    pub fn set_can_stop_discovery(&self, can_stop_discovery: bool) -> Result<(), BluetoothError> {
+        get_inner_and_call_test_func!(self.backend, FakeBluetoothAdapter, set_can_stop_discovery, can_stop_discovery)
+    }
+
+    pub fn create_discovery_session(&self) -> Result<BluetoothDiscoverySession, BluetoothError> {
+        BluetoothDiscoverySession::create_session(self.clone(), DiscoveryFilter::default())
     }
 
-    pub fn create_discovery_session(&self) -> Result<BluetoothDiscoverySession, Box<Error>> {
-        BluetoothDiscoverySession::create_session(self.clone())
+    pub fn create_discovery_session_with_filter(&self,
+                                                filter: DiscoveryFilter)
+                                                -> Result<BluetoothDiscoverySession, BluetoothError> {
+        BluetoothDiscoverySession::create_session(self.clone(), filter)
     }
 
-    pub fn get_uuids(&self) -> Result<Vec<String>, Box<Error>> {
-        get_inner_and_call!(self, BluetoothAdapter, get_uuids)
+    pub fn get_uuids(&self) -> Result<Vec<BluetoothUuid>, BluetoothError> {
+        let uuids = self.backend.get_uuids()?;
+        uuids.iter().map(|uuid| BluetoothUuid::parse(uuid)).collect()
     }
 
     #[cfg(feature = "bluetooth-test")]
-    pub fn set_uuids(&self, uuids: Vec<String>) -> Result<(), Box<Error>> {
-        get_inner_and_call_test_func!(self, BluetoothAdapter, set_uuids, uuids)
+    pub fn set_uuids(&self, uuids: Vec<BluetoothUuid>) -> Result<(), BluetoothError> {
+        let uuids = uuids.iter().map(BluetoothUuid::to_string).collect();
+        get_inner_and_call_test_func!(self.backend, FakeBluetoothAdapter, set_uuids, uuids)
     }
 
-    pub fn get_vendor_id_source(&self) -> Result<String, Box<Error>> {
-        get_inner_and_call!(self, BluetoothAdapter, get_vendor_id_source)
+    pub fn get_vendor_id_source(&self) -> Result<String, BluetoothError> {
+        self.backend.get_vendor_id_source()
     }
 
-    pub fn get_vendor_id(&self) -> Result<u32, Box<Error>> {
-        get_inner_and_call!(self, BluetoothAdapter, get_vendor_id)
+    pub fn get_vendor_id(&self) -> Result<u32, BluetoothError> {
+        self.backend.get_vendor_id()
     }
 
-    pub fn get_product_id(&self) -> Result<u32, Box<Error>> {
-        get_inner_and_call!(self, BluetoothAdapter, get_product_id)
+    pub fn get_product_id(&self) -> Result<u32, BluetoothError> {
+        self.backend.get_product_id()
     }
 
-    pub fn get_device_id(&self) -> Result<u32, Box<Error>> {
-        get_inner_and_call!(self, BluetoothAdapter, get_device_id)
+    pub fn get_device_id(&self) -> Result<u32, BluetoothError> {
+        self.backend.get_device_id()
     }
 
-    pub fn get_modalias(&self) -> Result<(String, u32, u32, u32), Box<Error>> {
-        get_inner_and_call!(self, BluetoothAdapter, get_modalias)
+    pub fn get_modalias(&self) -> Result<(String, u32, u32, u32), BluetoothError> {
+        self.backend.get_modalias()
     }
 
     #[cfg(feature = "bluetooth-test")]
-    pub fn set_modalias(&self, modalias: String) -> Result<(), Box<Error>> {
-        get_inner_and_call_test_func!(self, BluetoothAdapter, set_modalias, modalias)
+    pub fn set_modalias(&self, modalias: String) -> Result<(), BluetoothError> {
+        get_inner_and_call_test_func!(self.backend, FakeBluetoothAdapter, set_modalias, modalias)
     }
 
     #[cfg(feature = "bluetooth-test")]
-    pub fn get_ad_datas(&self) -> Result<Vec<String>, Box<Error>> {
-        get_inner_and_call_test_func!(self, BluetoothAdapter, get_ad_datas)
+    pub fn get_ad_datas(&self) -> Result<Vec<String>, BluetoothError> {
+        get_inner_and_call_test_func!(self.backend, FakeBluetoothAdapter, get_ad_datas)
     }
 
     #[cfg(feature = "bluetooth-test")]
-    pub fn set_ad_datas(&self, ad_datas: Vec<String>) -> Result<(), Box<Error>> {
-        get_inner_and_call_test_func!(self, BluetoothAdapter, set_ad_datas, ad_datas)
+    pub fn set_ad_datas(&self, ad_datas: Vec<String>) -> Result<(), BluetoothError> {
+        get_inner_and_call_test_func!(self.backend, FakeBluetoothAdapter, set_ad_datas, ad_datas)
     }
 }
 
 impl BluetoothDiscoverySession {
-    fn create_session(adapter: BluetoothAdapter) -> Result<BluetoothDiscoverySession, Box<Error>> {
-        match adapter {
-            #[cfg(all(target_os = "linux", feature = "bluetooth"))]
-            BluetoothAdapter::Bluez(bluez_adapter) => {
-                let bluez_session = try!(BluetoothDiscoverySessionBluez::create_session(bluez_adapter.get_id()));
-                Ok(BluetoothDiscoverySession::Bluez(Arc::new(bluez_session)))
-            },
-            #[cfg(all(target_os = "android", feature = "bluetooth"))]
-            BluetoothAdapter::Android(android_adapter) => {
-                let blurdroid_session = try!(BluetoothDiscoverySessionAndroid::create_session(android_adapter));
-                Ok(BluetoothDiscoverySession::Android(Arc::new(blurdroid_session)))
-            },
-            #[cfg(not(any(all(target_os = "linux", feature = "bluetooth"), all(target_os = "android", feature = "bluetooth"))))]
-            BluetoothAdapter::Empty(adapter) => {
-                let empty_session = try!(BluetoothDiscoverySessionEmpty::create_session(adapter));
-                Ok(BluetoothDiscoverySession::Empty(Arc::new(empty_session)))
-            },
-            #[cfg(feature = "bluetooth-test")]
-            BluetoothAdapter::Mock(fake_adapter) => {
-                let test_session = try!(FakeBluetoothDiscoverySession::create_session(fake_adapter));
-                Ok(BluetoothDiscoverySession::Mock(Arc::new(test_session)))
-            },
+    fn create_session(adapter: BluetoothAdapter,
+                      filter: DiscoveryFilter)
+                      -> Result<BluetoothDiscoverySession, BluetoothError> {
+        filter.validate()?;
+        #[cfg(all(target_os = "linux", feature = "bluetooth"))]
+        {
+            if (&*adapter.backend as &dyn Any).downcast_ref::<BluetoothAdapterBluez>().is_some() {
+                // Like the blurz session, it is not `Send`, and neither is the `BluetoothDiscoverySession`.
+                #[allow(clippy::arc_with_non_send_sync)]
+                let backend = Arc::new(BluezDiscoverySession::create_session(adapter.get_id(), &filter)?);
+                return Ok(BluetoothDiscoverySession {
+                    backend,
+                    adapter,
+                    filter,
+                    platform_filter: true,
+                    reported: Mutex::new(HashMap::new()),
+                });
+            }
         }
+        let backend = adapter.backend.clone().create_discovery_session()?;
+        Ok(BluetoothDiscoverySession {
+            backend,
+            adapter,
+            filter,
+            platform_filter: false,
+            reported: Mutex::new(HashMap::new()),
+        })
+    }
+
+    pub fn start_discovery(&self) -> Result<(), BluetoothError> {
+        self.backend.start_discovery()
+    }
+
+    pub fn stop_discovery(&self) -> Result<(), BluetoothError> {
+        self.backend.stop_discovery()
+    }
+
+    pub fn get_filter(&self) -> &DiscoveryFilter {
+        &self.filter
     }
 
-    pub fn start_discovery(&self) -> Result<(), Box<Error>> {
-        get_inner_and_call!(self, BluetoothDiscoverySession, start_discovery)
+    // BlueZ is given the filter when the session is created. The mock and Android backends take
+    // no filter, so it is applied to the devices of the adapter instead.
+    pub fn get_discovered_devices(&self) -> Result<Vec<BluetoothDevice>, BluetoothError> {
+        let devices = self.adapter.get_devices()?;
+        Ok(devices.into_iter().filter(|device| {
+            if self.platform_filter {
+                self.filter.matches_name(device)
+            } else {
+                self.filter.matches(device)
+            }
+        }).collect())
     }
 
-    pub fn stop_discovery(&self) -> Result<(), Box<Error>> {
-        get_inner_and_call!(self, BluetoothDiscoverySession, stop_discovery)
+    // The matching devices not returned by a previous call. With `duplicate_data` set in the filter,
+    // a device is returned again when its RSSI changed.
+    pub fn get_new_devices(&self) -> Result<Vec<BluetoothDevice>, BluetoothError> {
+        let devices = self.get_discovered_devices()?;
+        let mut reported = lock(&self.reported);
+        let mut new_devices = vec![];
+        for device in devices {
+            let rssi = device.get_rssi().ok();
+            let is_new = match reported.get(&device.get_id()) {
+                None => true,
+                Some(reported_rssi) => self.filter.duplicate_data && *reported_rssi != rssi,
+            };
+            if is_new {
+                reported.insert(device.get_id(), rssi);
+                new_devices.push(device);
+            }
+        }
+        Ok(new_devices)
     }
 }
 
 impl BluetoothDevice {
 
     fn create_device(adapter: BluetoothAdapter, device: String) -> BluetoothDevice {
-        match adapter {
-            #[cfg(all(target_os = "linux", feature = "bluetooth"))]
-            BluetoothAdapter::Bluez(_bluez_adapter) => {
-                BluetoothDevice::Bluez(Arc::new(BluetoothDeviceBluez::new(device)))
-            },
-            #[cfg(all(target_os = "android", feature = "bluetooth"))]
-            BluetoothAdapter::Android(android_adapter) => {
-                BluetoothDevice::Android(Arc::new(BluetoothDeviceAndroid::new(android_adapter, device)))
-            },
-            #[cfg(not(any(all(target_os = "linux", feature = "bluetooth"), all(target_os = "android", feature = "bluetooth"))))]
-            BluetoothAdapter::Empty(_adapter) => {
-                BluetoothDevice::Empty(Arc::new(BluetoothDeviceEmpty::new(device)))
-            },
-            #[cfg(feature = "bluetooth-test")]
-            BluetoothAdapter::Mock(fake_adapter) => {
-                BluetoothDevice::Mock(FakeBluetoothDevice::new_empty(fake_adapter, device))
-            },
+        let backend = adapter.backend.create_device(device);
+        BluetoothDevice {
+            backend,
+            context: adapter.context,
         }
     }
 
     #[cfg(feature = "bluetooth-test")]
-    pub fn create_mock_device(adapter: BluetoothAdapter, device: String) -> Result<BluetoothDevice, Box<Error>> {
-        match adapter {
-            BluetoothAdapter::Mock(fake_adapter) => {
-                Ok(BluetoothDevice::Mock(FakeBluetoothDevice::new_empty(fake_adapter, device)))
-            },
-            _ => {
-                Err(Box::from(NOT_SUPPORTED_ON_MOCK_ERROR))
-            },
+    pub fn create_mock_device(adapter: BluetoothAdapter, device: String) -> Result<BluetoothDevice, BluetoothError> {
+        if !is_mock!(adapter.backend, FakeBluetoothAdapter) {
+            return Err(BluetoothError::NotSupported);
         }
+        let backend = adapter.backend.create_device(device);
+        Ok(BluetoothDevice {
+            backend,
+            context: adapter.context,
+        })
     }
 
     pub fn get_id(&self) -> String {
-        get_inner_and_call!(self, BluetoothDevice, get_id)
+        self.backend.get_id()
     }
 
     #[cfg(feature = "bluetooth-test")]
     pub fn set_id(&self, id: String) {
-        match self {
-            &BluetoothDevice::Mock(ref fake_adapter) => fake_adapter.set_id(id),
-            _ => (),
+        if let Some(fake_adapter) = get_fake!(self.backend, FakeBluetoothDevice) {
+            fake_adapter.set_id(id);
         }
     }
 
-    pub fn get_address(&self) -> Result<String, Box<Error>> {
-        get_inner_and_call!(self, BluetoothDevice, get_address)
+    pub fn get_address(&self) -> Result<BluetoothAddress, BluetoothError> {
+        let address = self.backend.get_address()?;
+        BluetoothAddress::parse(&address)
+    }
+
+    #[cfg(feature = "bluetooth-test")]
+    pub fn set_address(&self, address: BluetoothAddress) -> Result<(), BluetoothError> {
+        get_inner_and_call_test_func!(self.backend, FakeBluetoothDevice, set_address, address.to_string())
+    }
+
+    pub fn get_name(&self) -> Result<String, BluetoothError> {
+        self.backend.get_name()
+    }
+
+    #[cfg(feature = "bluetooth-test")]
+    pub fn set_name(&self, name: Option<String>) -> Result<(), BluetoothError> {
+        get_inner_and_call_test_func!(self.backend, FakeBluetoothDevice, set_name, name)
+    }
+
+    pub fn get_icon(&self) -> Result<String, BluetoothError> {
+        self.backend.get_icon()
+    }
+
+    #[cfg(feature = "bluetooth-test")]
+    pub fn set_icon(&self, icon: String) -> Result<(), BluetoothError> {
+        get_inner_and_call_test_func!(self.backend, FakeBluetoothDevice, set_icon, icon)
+    }
+
+    pub fn get_class(&self) -> Result<u32, BluetoothError> {
+        self.backend.get_class()
     }
 
     #[cfg(feature = "bluetooth-test")]
-    pub fn set_address(&self, address: String) -> Result<(), Box<Error>> {
-        get_inner_and_call_test_func!(self, BluetoothDevice, set_address, address)
+    pub fn set_class(&self, class: u32) -> Result<(), BluetoothError> {
+        get_inner_and_call_test_func!(self.backend, FakeBluetoothDevice, set_class, class)
     }
 
-    pub fn get_name(&self) -> Result<String, Box<Error>> {
-        get_inner_and_call!(self, BluetoothDevice, get_name)
+    pub fn get_appearance(&self) -> Result<u16, BluetoothError> {
+        self.backend.get_appearance()
     }
 
     #[cfg(feature = "bluetooth-test")]
-    pub fn set_name(&self, name: Option<String>) -> Result<(), Box<Error>> {
-        get_inner_and_call_test_func!(self, BluetoothDevice, set_name, name)
+    pub fn set_appearance(&self, appearance: u16) -> Result<(), BluetoothError> {
+        get_inner_and_call_test_func!(self.backend, FakeBluetoothDevice, set_appearance, Some(appearance))
     }
 
-    pub fn get_icon(&self) -> Result<String, Box<Error>> {
-        get_inner_and_call!(self, BluetoothDevice, get_icon)
+    pub fn get_uuids(&self) -> Result<Vec<BluetoothUuid>, BluetoothError> {
+        let uuids = self.backend.get_uuids()?;
+        uuids.iter().map(|uuid| BluetoothUuid::parse(uuid)).collect()
     }
 
     #[cfg(feature = "bluetooth-test")]
-    pub fn set_icon(&self, icon: String) -> Result<(), Box<Error>> {
-        get_inner_and_call_test_func!(self, BluetoothDevice, set_icon, icon)
+    pub fn set_uuids(&self, uuids: Vec<BluetoothUuid>) -> Result<(), BluetoothError> {
+        let uuids = uuids.iter().map(BluetoothUuid::to_string).collect();
+        get_inner_and_call_test_func!(self.backend, FakeBluetoothDevice, set_uuids, uuids)
     }
 
-    pub fn get_class(&self) -> Result<u32, Box<Error>> {
-        get_inner_and_call!(self, BluetoothDevice, get_class)
+    pub fn is_paired(&self) -> Result<bool, BluetoothError> {
+        self.backend.is_paired()
     }
 
     #[cfg(feature = "bluetooth-test")]
-    pub fn set_class(&self, class: u32) -> Result<(), Box<Error>> {
-        get_inner_and_call_test_func!(self, BluetoothDevice, set_class, class)
+    pub fn set_paired(&self, paired: bool) -> Result<(), BluetoothError> {
+        get_inner_and_call_test_func!(self.backend, FakeBluetoothDevice, set_paired, paired)
     }
 
-    pub fn get_appearance(&self) -> Result<u16, Box<Error>> {
-        get_inner_and_call!(self, BluetoothDevice, get_appearance)
+    pub fn is_connected(&self) -> Result<bool, BluetoothError> {
+        self.backend.is_connected()
     }
 
     #[cfg(feature = "bluetooth-test")]
-    pub fn set_appearance(&self, appearance: u16) -> Result<(), Box<Error>> {
-        get_inner_and_call_test_func!(self, BluetoothDevice, set_appearance, Some(appearance))
+    pub fn set_connected(&self, connected: bool) -> Result<(), BluetoothError> {
+        get_inner_and_call_test_func!(self.backend, FakeBluetoothDevice, set_connected, connected)?;
+        if !connected {
+            self.reset_mtu();
+        }
+        Ok(())
     }
 
-    pub fn get_uuids(&self) -> Result<Vec<String>, Box<Error>> {
-        get_inner_and_call!(self, BluetoothDevice, get_uuids)
+    #[cfg(feature = "bluetooth-test")]
+    pub fn is_connectable(&self) -> Result<bool, BluetoothError> {
+        get_inner_and_call_test_func!(self.backend, FakeBluetoothDevice, is_connectable)
     }
 
     #[cfg(feature = "bluetooth-test")]
-    pub fn set_uuids(&self, uuids: Vec<String>) -> Result<(), Box<Error>> {
-        get_inner_and_call_test_func!(self, BluetoothDevice, set_uuids, uuids)
+    pub fn set_connectable(&self, connectable: bool) -> Result<(), BluetoothError> {
+        get_inner_and_call_test_func!(self.backend, FakeBluetoothDevice, set_connectable, connectable)
     }
 
-    pub fn is_paired(&self) -> Result<bool, Box<Error>> {
-        get_inner_and_call!(self, BluetoothDevice, is_paired)
+    pub fn is_trusted(&self) -> Result<bool, BluetoothError> {
+        self.backend.is_trusted()
     }
 
     #[cfg(feature = "bluetooth-test")]
-    pub fn set_paired(&self, paired: bool) -> Result<(), Box<Error>> {
-        get_inner_and_call_test_func!(self, BluetoothDevice, set_paired, paired)
+    pub fn set_trusted(&self, trusted: bool) -> Result<(), BluetoothError> {
+        get_inner_and_call_test_func!(self.backend, FakeBluetoothDevice, set_trusted, trusted)
     }
 
-    pub fn is_connected(&self) -> Result<bool, Box<Error>> {
-        get_inner_and_call!(self, BluetoothDevice, is_connected)
+    pub fn is_blocked(&self) -> Result<bool, BluetoothError> {
+        self.backend.is_blocked()
     }
 
     #[cfg(feature = "bluetooth-test")]
-    pub fn set_connected(&self, connected: bool) -> Result<(), Box<Error>> {
-        get_inner_and_call_test_func!(self, BluetoothDevice, set_connected, connected)
+    pub fn set_blocked(&self, blocked: bool) -> Result<(), BluetoothError> {
+        get_inner_and_call_test_func!(self.backend, FakeBluetoothDevice, set_blocked, blocked)
+    }
+
+    pub fn get_alias(&self) -> Result<String, BluetoothError> {
+        self.backend.get_alias()
     }
 
     #[cfg(feature = "bluetooth-test")]
-    pub fn is_connectable(&self) -> Result<bool, Box<Error>> {
-        get_inner_and_call_test_func!(self, BluetoothDevice, is_connectable)
+    pub fn set_alias(&self, alias: String) -> Result<(), BluetoothError> {
+        get_inner_and_call_test_func!(self.backend, FakeBluetoothDevice, set_alias, alias)
+    }
+
+    pub fn is_legacy_pairing(&self) -> Result<bool, BluetoothError> {
+        self.backend.is_legacy_pairing()
+    }
+
+    #[cfg(feature = "bluetooth-test")]
+    pub fn set_legacy_pairing(&self, legacy_pairing: bool) -> Result<(), BluetoothError> {
+        get_inner_and_call_test_func!(self.backend, FakeBluetoothDevice, set_legacy_pairing, legacy_pairing)
+    }
+
+    pub fn get_vendor_id_source(&self) -> Result<String, BluetoothError> {
+        self.backend.get_vendor_id_source()
+    }
+
+    pub fn get_vendor_id(&self) -> Result<u32, BluetoothError> {
+        self.backend.get_vendor_id()
+    }
+
+    pub fn get_product_id(&self) -> Result<u32, BluetoothError> {
+        self.backend.get_product_id()
+    }
+
+    pub fn get_device_id(&self) -> Result<u32, BluetoothError> {
+        self.backend.get_device_id()
+    }
+
+    pub fn get_modalias(&self) -> Result<(String, u32, u32, u32), BluetoothError> {
+        self.backend.get_modalias()
     }
 
     #[cfg(feature = "bluetooth-test")]
-    pub fn set_connectable(&self, connectable: bool) -> Result<(), Box<Error>> {
-        get_inner_and_call_test_func!(self, BluetoothDevice, set_connectable, connectable)
+    pub fn set_modalias(&self, modalias: String) -> Result<(), BluetoothError> {
+        get_inner_and_call_test_func!(self.backend, FakeBluetoothDevice, set_modalias, modalias)
     }
 
-    pub fn is_trusted(&self) -> Result<bool, Box<Error>> {
-        get_inner_and_call!(self, BluetoothDevice, is_trusted)
+    pub fn get_rssi(&self) -> Result<i16, BluetoothError> {
+        self.backend.get_rssi()
     }
 
     #[cfg(feature = "bluetooth-test")]
-    pub fn set_trusted(&self, trusted: bool) -> Result<(), Box<Error>> {
-        get_inner_and_call_test_func!(self, BluetoothDevice, set_trusted, trusted)
+    pub fn set_rssi(&self, rssi: i16) -> Result<(), BluetoothError> {
+        get_inner_and_call_test_func!(self.backend, FakeBluetoothDevice, set_rssi, Some(rssi))
     }
 
-    pub fn is_blocked(&self) -> Result<bool, Box<Error>> {
-        get_inner_and_call!(self, BluetoothDevice, is_blocked)
+    pub fn get_tx_power(&self) -> Result<i16, BluetoothError> {
+        self.backend.get_tx_power()
     }
 
     #[cfg(feature = "bluetooth-test")]
-    pub fn set_blocked(&self, blocked: bool) -> Result<(), Box<Error>> {
-        get_inner_and_call_test_func!(self, BluetoothDevice, set_blocked, blocked)
+    pub fn set_tx_power(&self, tx_power: i16) -> Result<(), BluetoothError> {
+        get_inner_and_call_test_func!(self.backend, FakeBluetoothDevice, set_tx_power, Some(tx_power))
     }
 
-    pub fn get_alias(&self) -> Result<String, Box<Error>> {
-        get_inner_and_call!(self, BluetoothDevice, get_alias)
+    // The manufacturer specific data of the last advertisement, by company identifier.
+    pub fn get_manufacturer_data(&self) -> Result<HashMap<u16, Vec<u8>>, BluetoothError> {
+        self.backend.get_manufacturer_data()
     }
 
     #[cfg(feature = "bluetooth-test")]
-    pub fn set_alias(&self, alias: String) -> Result<(), Box<Error>> {
-        get_inner_and_call_test_func!(self, BluetoothDevice, set_alias, alias)
+    pub fn set_manufacturer_data(&self, manufacturer_data: HashMap<u16, Vec<u8>>) -> Result<(), BluetoothError> {
+        get_inner_and_call_test_func!(self.backend, FakeBluetoothDevice, set_manufacturer_data, Some(manufacturer_data))
     }
 
-    pub fn is_legacy_pairing(&self) -> Result<bool, Box<Error>> {
-        get_inner_and_call!(self, BluetoothDevice, is_legacy_pairing)
+    // The service data of the last advertisement, by service UUID.
+    pub fn get_service_data(&self) -> Result<HashMap<BluetoothUuid, Vec<u8>>, BluetoothError> {
+        let service_data = self.backend.get_service_data()?;
+        service_data.into_iter().map(|(uuid, data)| Ok((BluetoothUuid::parse(&uuid)?, data))).collect()
     }
 
     #[cfg(feature = "bluetooth-test")]
-    pub fn set_legacy_pairing(&self, legacy_pairing: bool) -> Result<(), Box<Error>> {
-        get_inner_and_call_test_func!(self, BluetoothDevice, set_legacy_pairing, legacy_pairing)
+    pub fn set_service_data(&self, service_data: HashMap<BluetoothUuid, Vec<u8>>) -> Result<(), BluetoothError> {
+        let service_data = service_data.into_iter().map(|(uuid, data)| (uuid.to_string(), data)).collect();
+        get_inner_and_call_test_func!(self.backend, FakeBluetoothDevice, set_service_data, Some(service_data))
+    }
+
+    pub fn get_gatt_services(&self) -> Result<Vec<BluetoothGATTService>, BluetoothError> {
+        let services = self.backend.get_gatt_services()?;
+        let services = services.into_iter().map(|service| BluetoothGATTService::create_service(self.clone(), service));
+        Ok(self.context.remove_blocklisted(services.collect(), BluetoothGATTService::get_uuid))
+    }
+
+    pub fn connect(&self) -> Result<(), BluetoothError> {
+        self.backend.connect()?;
+        self.reset_mtu();
+        Ok(())
+    }
+
+    pub fn disconnect(&self) -> Result<(), BluetoothError> {
+        self.backend.disconnect()?;
+        self.reset_mtu();
+        Ok(())
     }
 
-    pub fn get_vendor_id_source(&self) -> Result<String, Box<Error>> {
-        get_inner_and_call!(self, BluetoothDevice, get_vendor_id_source)
+    // The ATT_MTU is exchanged again on every connection.
+    fn reset_mtu(&self) {
+        #[cfg(feature = "bluetooth-test")]
+        {
+            if let Some(fake) = get_fake!(self.backend, FakeBluetoothDevice) {
+                let _ = mock::reset_mtu(fake);
+            }
+        }
     }
 
-    pub fn get_vendor_id(&self) -> Result<u32, Box<Error>> {
-        get_inner_and_call!(self, BluetoothDevice, get_vendor_id)
+    // BlueZ and Android exchange the ATT_MTU by themselves, when connecting, but neither blurz
+    // nor blurdroid report it, so only the mock backend, and the backends implementing
+    // `DeviceBackend::get_mtu`, support it.
+    pub fn get_mtu(&self) -> Result<u16, BluetoothError> {
+        if !self.is_connected()? {
+            return Err(BluetoothError::NotConnected);
+        }
+        self.backend.get_mtu()
     }
 
-    pub fn get_product_id(&self) -> Result<u32, Box<Error>> {
-        get_inner_and_call!(self, BluetoothDevice, get_product_id)
+    // Exchanges the ATT_MTU, and returns the new one: the lower of the requested one and the
+    // one the remote device accepts.
+    pub fn request_mtu(&self, mtu: u16) -> Result<u16, BluetoothError> {
+        if mtu < ATT_DEFAULT_MTU {
+            return Err(BluetoothError::InvalidArgument(format!("ATT_MTU {} is lower than {}", mtu, ATT_DEFAULT_MTU)));
+        }
+        if !self.is_connected()? {
+            return Err(BluetoothError::NotConnected);
+        }
+        self.backend.request_mtu(mtu)
     }
 
-    pub fn get_device_id(&self) -> Result<u32, Box<Error>> {
-        get_inner_and_call!(self, BluetoothDevice, get_device_id)
+    #[cfg(feature = "bluetooth-test")]
+    pub fn set_mtu(&self, mtu: u16) -> Result<(), BluetoothError> {
+        self.set_mock_mtu(mtu, |mock_device| mock_device.mtu = mtu)
     }
 
-    pub fn get_modalias(&self) -> Result<(String, u32, u32, u32), Box<Error>> {
-        get_inner_and_call!(self, BluetoothDevice, get_modalias)
+    // The largest ATT_MTU `request_mtu` can exchange on the mock device, 517 by default.
+    #[cfg(feature = "bluetooth-test")]
+    pub fn set_server_mtu(&self, mtu: u16) -> Result<(), BluetoothError> {
+        self.set_mock_mtu(mtu, |mock_device| mock_device.server_mtu = mtu)
     }
 
     #[cfg(feature = "bluetooth-test")]
-    pub fn set_modalias(&self, modalias: String) -> Result<(), Box<Error>> {
-        get_inner_and_call_test_func!(self, BluetoothDevice, set_modalias, modalias)
+    fn set_mock_mtu<F: FnOnce(&mut MockDevice)>(&self, mtu: u16, update: F) -> Result<(), BluetoothError> {
+        if mtu < ATT_DEFAULT_MTU {
+            return Err(BluetoothError::InvalidArgument(format!("ATT_MTU {} is lower than {}", mtu, ATT_DEFAULT_MTU)));
+        }
+        self.update_mock_device(update)
+    }
+
+    pub fn connect_profile(&self, uuid: BluetoothUuid) -> Result<(), BluetoothError> {
+        self.backend.connect_profile(uuid.to_string())
+    }
+
+    pub fn disconnect_profile(&self, uuid: BluetoothUuid) -> Result<(), BluetoothError> {
+        self.backend.disconnect_profile(uuid.to_string())
+    }
+
+    fn get_operation_queue(&self) -> Arc<OperationQueue> {
+        self.context.get_operation_queue(self.get_id())
+    }
+
+    // The `read_value`, `write_value`, `start_notify` and `stop_notify` calls on the
+    // characteristics and descriptors of the device run one at a time, in the order they
+    // were made. This cancels the ones waiting for their turn, which fail with
+    // `BluetoothError::Cancelled`, and returns their number.
+    pub fn cancel_pending_operations(&self) -> usize {
+        self.get_operation_queue().cancel_pending()
     }
 
-    pub fn get_rssi(&self) -> Result<i16, Box<Error>> {
-        get_inner_and_call!(self, BluetoothDevice, get_rssi)
+    pub fn get_pending_operations(&self) -> usize {
+        self.get_operation_queue().get_pending()
     }
 
+    // How long every GATT operation on the mock device takes. The mock device rejects an
+    // operation with `BluetoothError::InvalidState`, while an other one is running.
     #[cfg(feature = "bluetooth-test")]
-    pub fn set_rssi(&self, rssi: i16) -> Result<(), Box<Error>> {
-        get_inner_and_call_test_func!(self, BluetoothDevice, set_rssi, Some(rssi))
+    pub fn set_mock_operation_duration(&self, duration: Duration) -> Result<(), BluetoothError> {
+        if !is_mock!(self.backend, FakeBluetoothDevice) {
+            return Err(BluetoothError::NotSupported);
+        }
+        self.get_operation_queue().set_mock_duration(duration);
+        Ok(())
     }
 
-    pub fn get_tx_power(&self) -> Result<i16, Box<Error>> {
-        get_inner_and_call!(self, BluetoothDevice, get_tx_power)
+    // Without the queue, the GATT operations on the device can overlap.
+    #[cfg(feature = "bluetooth-test")]
+    pub fn set_operation_queue_enabled(&self, enabled: bool) -> Result<(), BluetoothError> {
+        if !is_mock!(self.backend, FakeBluetoothDevice) {
+            return Err(BluetoothError::NotSupported);
+        }
+        self.get_operation_queue().set_enabled(enabled);
+        Ok(())
     }
 
+    // Asks the agent registered on the adapter, if the backend supports one.
+    pub fn pair(&self) -> Result<(), BluetoothError> {
+        self.backend.pair_with_agent(self, self.context.get_agent())
+    }
+
+    // A connection of the mock device to a local service, which the registered agent has to
+    // authorize, unless the device is trusted.
     #[cfg(feature = "bluetooth-test")]
-    pub fn set_tx_power(&self, tx_power: i16) -> Result<(), Box<Error>> {
-        get_inner_and_call_test_func!(self, BluetoothDevice, set_tx_power, Some(tx_power))
+    pub fn request_service_authorization(&self, uuid: BluetoothUuid) -> Result<(), BluetoothError> {
+        if self.is_trusted()? {
+            return Ok(());
+        }
+        match self.context.get_agent() {
+            Some(agent) => agent.authorize_service(self, uuid),
+            None => Err(BluetoothError::PermissionDenied),
+        }
     }
 
-    pub fn get_gatt_services(&self) -> Result<Vec<BluetoothGATTService>, Box<Error>> {
-        let services = try!(get_inner_and_call!(self, BluetoothDevice, get_gatt_services));
-        Ok(services.into_iter().map(|service| BluetoothGATTService::create_service(self.clone(), service)).collect())
+    #[cfg(feature = "bluetooth-test")]
+    pub fn set_io_capability(&self, io_capability: IoCapability) -> Result<(), BluetoothError> {
+        self.update_mock_device(|mock_device| mock_device.io_capability = io_capability)
     }
 
-    pub fn connect(&self) -> Result<(), Box<Error>> {
-        get_inner_and_call!(self, BluetoothDevice, connect)
+    #[cfg(feature = "bluetooth-test")]
+    pub fn set_pairing_passkey(&self, passkey: u32) -> Result<(), BluetoothError> {
+        if passkey > 999999 {
+            return Err(BluetoothError::InvalidArgument(format!("passkey {}", passkey)));
+        }
+        self.update_mock_device(|mock_device| mock_device.passkey = passkey)
     }
 
-    pub fn disconnect(&self) -> Result<(), Box<Error>> {
-        get_inner_and_call!(self, BluetoothDevice, disconnect)
+    #[cfg(feature = "bluetooth-test")]
+    pub fn set_pairing_pin_code(&self, pin_code: String) -> Result<(), BluetoothError> {
+        self.update_mock_device(|mock_device| mock_device.pin_code = pin_code)
     }
 
-    pub fn connect_profile(&self, uuid: String) -> Result<(), Box<Error>> {
-        get_inner_and_call!(self, BluetoothDevice, connect_profile, uuid)
+    #[cfg(feature = "bluetooth-test")]
+    pub fn set_pairing_rejected(&self, rejected: bool) -> Result<(), BluetoothError> {
+        self.update_mock_device(|mock_device| mock_device.rejected = rejected)
     }
 
-    pub fn disconnect_profile(&self, uuid: String) -> Result<(), Box<Error>> {
-        get_inner_and_call!(self, BluetoothDevice, disconnect_profile, uuid)
+    #[cfg(feature = "bluetooth-test")]
+    fn update_mock_device<F: FnOnce(&mut MockDevice)>(&self, update: F) -> Result<(), BluetoothError> {
+        match get_fake!(self.backend, FakeBluetoothDevice) {
+            Some(fake) => mock::update(fake, update).map(|_| ()),
+            None => Err(BluetoothError::NotSupported),
+        }
     }
 
-    pub fn pair(&self) -> Result<(), Box<Error>> {
-        get_inner_and_call!(self, BluetoothDevice, pair)
+    pub fn cancel_pairing(&self) -> Result<(), BluetoothError> {
+        self.backend.cancel_pairing()
     }
 
-    pub fn cancel_pairing(&self) -> Result<(), Box<Error>> {
-        get_inner_and_call!(self, BluetoothDevice, cancel_pairing)
+    // The services of a mock device, whether it is connected or not, and blocklisted or not.
+    #[cfg(feature = "bluetooth-test")]
+    pub(crate) fn get_mock_services(&self) -> Result<Vec<BluetoothGATTService>, BluetoothError> {
+        let fake = get_fake!(self.backend, FakeBluetoothDevice).ok_or(BluetoothError::NotSupported)?;
+        let services = mock::get(fake)?.services.into_iter().map(|backend| {
+            BluetoothGATTService {
+                backend,
+                queue: self.get_operation_queue(),
+                device: self.backend.clone(),
+                context: self.context.clone(),
+            }
+        });
+        Ok(services.collect())
     }
 }
 
 impl BluetoothGATTService {
     fn create_service(device: BluetoothDevice, service: String) -> BluetoothGATTService {
-        match device {
-            #[cfg(all(target_os = "linux", feature = "bluetooth"))]
-            BluetoothDevice::Bluez(_bluez_device) => {
-                BluetoothGATTService::Bluez(Arc::new(BluetoothGATTServiceBluez::new(service)))
-            },
-            #[cfg(all(target_os = "android", feature = "bluetooth"))]
-            BluetoothDevice::Android(android_device) => {
-                BluetoothGATTService::Android(Arc::new(BluetoothGATTServiceAndroid::new(android_device, service)))
-            },
-            #[cfg(not(any(all(target_os = "linux", feature = "bluetooth"), all(target_os = "android", feature = "bluetooth"))))]
-            BluetoothDevice::Empty(_device) => {
-                BluetoothGATTService::Empty(Arc::new(BluetoothGATTServiceEmpty::new(service)))
-            },
-            #[cfg(feature = "bluetooth-test")]
-            BluetoothDevice::Mock(fake_device) => {
-                BluetoothGATTService::Mock(FakeBluetoothGATTService::new_empty(fake_device, service))
-            },
+        let queue = device.get_operation_queue();
+        let backend = device.backend.clone().create_service(service);
+        BluetoothGATTService {
+            backend,
+            queue,
+            device: device.backend,
+            context: device.context,
         }
     }
 
     #[cfg(feature = "bluetooth-test")]
-    pub fn create_mock_service(device: BluetoothDevice, service: String) -> Result<BluetoothGATTService, Box<Error>> {
-        match device {
-            BluetoothDevice::Mock(fake_device) => {
-                Ok(BluetoothGATTService::Mock(FakeBluetoothGATTService::new_empty(fake_device, service)))
-            },
-            _ => {
-                Err(Box::from(NOT_SUPPORTED_ON_MOCK_ERROR))
-            },
+    pub fn create_mock_service(device: BluetoothDevice, service: String) -> Result<BluetoothGATTService, BluetoothError> {
+        let queue = device.get_operation_queue();
+        if !is_mock!(device.backend, FakeBluetoothDevice) {
+            return Err(BluetoothError::NotSupported);
+        }
+        let backend = device.backend.clone().create_service(service);
+        if let (Some(fake_device), Some(fake_service)) = (get_fake!(device.backend, FakeBluetoothDevice),
+                                                          get_fake!(backend, FakeBluetoothGATTService)) {
+            mock::add_service(fake_device, Arc::new(fake_service.clone()))?;
         }
+        Ok(BluetoothGATTService {
+            backend,
+            queue,
+            device: device.backend,
+            context: device.context,
+        })
     }
 
     pub fn get_id(&self) -> String {
-        get_inner_and_call!(self, BluetoothGATTService, get_id)
+        self.backend.get_id()
     }
 
     #[cfg(feature = "bluetooth-test")]
     pub fn set_id(&self, id: String) {
-        match self {
-            &BluetoothGATTService::Mock(ref fake_service) => fake_service.set_id(id),
-            _ => (),
+        if let Some(fake_service) = get_fake!(self.backend, FakeBluetoothGATTService) {
+            fake_service.set_id(id);
         }
     }
 
-    pub fn get_uuid(&self) -> Result<String, Box<Error>> {
-        get_inner_and_call!(self, BluetoothGATTService, get_uuid)
+    pub fn get_uuid(&self) -> Result<BluetoothUuid, BluetoothError> {
+        let uuid = self.backend.get_uuid()?;
+        BluetoothUuid::parse(&uuid)
     }
 
     #[cfg(feature = "bluetooth-test")]
-    pub fn set_uuid(&self, uuid: String) -> Result<(), Box<Error>> {
-        get_inner_and_call_test_func!(self, BluetoothGATTService, set_uuid, uuid)
+    pub fn set_uuid(&self, uuid: BluetoothUuid) -> Result<(), BluetoothError> {
+        get_inner_and_call_test_func!(self.backend, FakeBluetoothGATTService, set_uuid, uuid.to_string())
     }
 
-    pub fn is_primary(&self) -> Result<bool, Box<Error>> {
-        get_inner_and_call!(self, BluetoothGATTService, is_primary)
+    pub fn is_primary(&self) -> Result<bool, BluetoothError> {
+        self.backend.is_primary()
     }
 
     #[cfg(feature = "bluetooth-test")]
-    pub fn set_primary(&self, primary: bool) -> Result<(), Box<Error>> {
-        get_inner_and_call_test_func!(self, BluetoothGATTService, set_is_primary, primary)
+    pub fn set_primary(&self, primary: bool) -> Result<(), BluetoothError> {
+        get_inner_and_call_test_func!(self.backend, FakeBluetoothGATTService, set_is_primary, primary)
+    }
+
+    pub fn get_includes(&self, device: BluetoothDevice) -> Result<Vec<BluetoothGATTService>, BluetoothError> {
+        let services = self.backend.get_includes()?;
+        let services = services.into_iter().map(|service| BluetoothGATTService::create_service(device.clone(), service));
+        Ok(self.context.remove_blocklisted(services.collect(), BluetoothGATTService::get_uuid))
     }
 
-    pub fn get_includes(&self, device: BluetoothDevice) -> Result<Vec<BluetoothGATTService>, Box<Error>> {
-        let services = try!(get_inner_and_call!(self, BluetoothGATTService, get_includes));
-        Ok(services.into_iter().map(|service| BluetoothGATTService::create_service(device.clone(), service)).collect())
+    pub fn get_gatt_characteristics(&self) -> Result<Vec<BluetoothGATTCharacteristic>, BluetoothError> {
+        let characteristics = self.get_all_gatt_characteristics()?;
+        Ok(self.context.remove_blocklisted(characteristics, BluetoothGATTCharacteristic::get_uuid))
     }
 
-    pub fn get_gatt_characteristics(&self) -> Result<Vec<BluetoothGATTCharacteristic>, Box<Error>> {
-        let characteristics = try!(get_inner_and_call!(self, BluetoothGATTService, get_gatt_characteristics));
-        Ok(characteristics.into_iter()
-                          .map(|characteristic|
-                              BluetoothGATTCharacteristic::create_characteristic(self.clone(), characteristic))
-                          .collect())
+    // With the blocklisted characteristics.
+    pub(crate) fn get_all_gatt_characteristics(&self) -> Result<Vec<BluetoothGATTCharacteristic>, BluetoothError> {
+        let characteristics = self.backend.get_gatt_characteristics()?;
+        let characteristics = characteristics.into_iter()
+                                             .map(|characteristic|
+                                                 BluetoothGATTCharacteristic::create_characteristic(self.clone(),
+                                                                                                    characteristic));
+        Ok(characteristics.collect())
     }
 }
 
 impl BluetoothGATTCharacteristic {
     fn create_characteristic(service: BluetoothGATTService, characteristic: String) -> BluetoothGATTCharacteristic {
-        match service {
-            #[cfg(all(target_os = "linux", feature = "bluetooth"))]
-            BluetoothGATTService::Bluez(_bluez_service) => {
-                BluetoothGATTCharacteristic::Bluez(Arc::new(BluetoothGATTCharacteristicBluez::new(characteristic)))
-            },
-            #[cfg(all(target_os = "android", feature = "bluetooth"))]
-            BluetoothGATTService::Android(android_service) => {
-                BluetoothGATTCharacteristic::Android(
-                    Arc::new(BluetoothGATTCharacteristicAndroid::new(android_service, characteristic)))
-            },
-            #[cfg(not(any(all(target_os = "linux", feature = "bluetooth"), all(target_os = "android", feature = "bluetooth"))))]
-            BluetoothGATTService::Empty(_service) => {
-                BluetoothGATTCharacteristic::Empty(Arc::new(BluetoothGATTCharacteristicEmpty::new(characteristic)))
-            },
-            #[cfg(feature = "bluetooth-test")]
-            BluetoothGATTService::Mock(fake_service) => {
-                BluetoothGATTCharacteristic::Mock(
-                    FakeBluetoothGATTCharacteristic::new_empty(fake_service, characteristic))
-            },
+        let backend = service.backend.create_characteristic(characteristic);
+        BluetoothGATTCharacteristic {
+            backend,
+            context: service.context,
+            device: service.device,
+            queue: service.queue,
         }
     }
 
     #[cfg(feature = "bluetooth-test")]
     pub fn create_mock_characteristic(service: BluetoothGATTService,
                                       characteristic: String)
-                                      -> Result<BluetoothGATTCharacteristic, Box<Error>> {
-        match service {
-            BluetoothGATTService::Mock(fake_service) => {
-                Ok(BluetoothGATTCharacteristic::Mock(
-                    FakeBluetoothGATTCharacteristic::new_empty(fake_service, characteristic)))
-            },
-            _ => {
-                Err(Box::from(NOT_SUPPORTED_ON_MOCK_ERROR))
-            },
+                                      -> Result<BluetoothGATTCharacteristic, BluetoothError> {
+        if !is_mock!(service.backend, FakeBluetoothGATTService) {
+            return Err(BluetoothError::NotSupported);
         }
+        let backend = service.backend.create_characteristic(characteristic);
+        Ok(BluetoothGATTCharacteristic {
+            backend,
+            context: service.context,
+            device: service.device,
+            queue: service.queue,
+        })
     }
 
     pub fn get_id(&self) -> String {
-        get_inner_and_call!(self, BluetoothGATTCharacteristic, get_id)
+        self.backend.get_id()
     }
 
     #[cfg(feature = "bluetooth-test")]
     pub fn set_id(&self, id: String) {
-        match self {
-            &BluetoothGATTCharacteristic::Mock(ref fake_characteristic) => fake_characteristic.set_id(id),
-            _ => (),
+        if let Some(fake_characteristic) = get_fake!(self.backend, FakeBluetoothGATTCharacteristic) {
+            fake_characteristic.set_id(id);
         }
     }
 
-    pub fn get_uuid(&self) -> Result<String, Box<Error>> {
-        get_inner_and_call!(self, BluetoothGATTCharacteristic, get_uuid)
+    pub fn get_uuid(&self) -> Result<BluetoothUuid, BluetoothError> {
+        let uuid = self.backend.get_uuid()?;
+        BluetoothUuid::parse(&uuid)
     }
 
     #[cfg(feature = "bluetooth-test")]
-    pub fn set_uuid(&self, uuid: String) -> Result<(), Box<Error>> {
-        get_inner_and_call_test_func!(self, BluetoothGATTCharacteristic, set_uuid, uuid)
+    pub fn set_uuid(&self, uuid: BluetoothUuid) -> Result<(), BluetoothError> {
+        get_inner_and_call_test_func!(self.backend, FakeBluetoothGATTCharacteristic, set_uuid, uuid.to_string())
     }
 
-    pub fn get_value(&self) -> Result<Vec<u8>, Box<Error>> {
-        get_inner_and_call!(self, BluetoothGATTCharacteristic, get_value)
+    pub fn get_value(&self) -> Result<Vec<u8>, BluetoothError> {
+        self.backend.get_value()
     }
 
     #[cfg(feature = "bluetooth-test")]
-    pub fn set_value(&self, value: Vec<u8>) -> Result<(), Box<Error>> {
-        get_inner_and_call_test_func!(self, BluetoothGATTCharacteristic, set_value, Some(value))
+    pub fn set_value(&self, value: Vec<u8>) -> Result<(), BluetoothError> {
+        get_inner_and_call_test_func!(self.backend, FakeBluetoothGATTCharacteristic, set_value, Some(value))
     }
 
-    pub fn is_notifying(&self) -> Result<bool, Box<Error>> {
-        get_inner_and_call!(self, BluetoothGATTCharacteristic, is_notifying)
+    pub fn is_notifying(&self) -> Result<bool, BluetoothError> {
+        self.backend.is_notifying()
     }
 
     #[cfg(feature = "bluetooth-test")]
-    pub fn set_notifying(&self, notifying: bool) -> Result<(), Box<Error>> {
-        get_inner_and_call_test_func!(self, BluetoothGATTCharacteristic, set_notifying, notifying)
+    pub fn set_notifying(&self, notifying: bool) -> Result<(), BluetoothError> {
+        get_inner_and_call_test_func!(self.backend, FakeBluetoothGATTCharacteristic, set_notifying, notifying)
+    }
+
+    pub fn get_flags(&self) -> Result<Vec<String>, BluetoothError> {
+        self.backend.get_flags()
     }
 
-    pub fn get_flags(&self) -> Result<Vec<String>, Box<Error>> {
-        get_inner_and_call!(self, BluetoothGATTCharacteristic, get_flags)
+    pub fn get_properties(&self) -> Result<CharacteristicProperties, BluetoothError> {
+        Ok(CharacteristicProperties::from_flags(&self.get_flags()?))
     }
 
     #[cfg(feature = "bluetooth-test")]
-    pub fn set_flags(&self, flags: Vec<String>) -> Result<(), Box<Error>> {
-        get_inner_and_call_test_func!(self, BluetoothGATTCharacteristic, set_flags, flags)
+    pub fn set_flags(&self, flags: Vec<String>) -> Result<(), BluetoothError> {
+        get_inner_and_call_test_func!(self.backend, FakeBluetoothGATTCharacteristic, set_flags, flags)
+    }
+
+    pub fn get_gatt_descriptors(&self) -> Result<Vec<BluetoothGATTDescriptor>, BluetoothError> {
+        let descriptors = self.get_all_gatt_descriptors()?;
+        Ok(self.context.remove_blocklisted(descriptors, BluetoothGATTDescriptor::get_uuid))
+    }
+
+    // With the blocklisted descriptors.
+    pub(crate) fn get_all_gatt_descriptors(&self) -> Result<Vec<BluetoothGATTDescriptor>, BluetoothError> {
+        let descriptors = self.backend.get_gatt_descriptors()?;
+        let descriptors = descriptors.into_iter()
+                                     .map(|descriptor| BluetoothGATTDescriptor::create_descriptor(self.clone(), descriptor));
+        Ok(descriptors.collect())
+    }
+
+    pub fn read_value(&self) -> Result<Vec<u8>, BluetoothError> {
+        self.context.check_read(|| self.get_uuid())?;
+        self.run_operation(|| self.backend.read_value())
+    }
+
+    pub fn write_value(&self, values: Vec<u8>) -> Result<(), BluetoothError> {
+        self.context.check_write(|| self.get_uuid())?;
+        self.run_operation(|| self.backend.write_value(values))
+    }
+
+    // Fails with `NotSupported` if the properties of the characteristic do not allow the write type.
+    // On BlueZ, Android and the mock backend the write type is only validated: neither blurz nor
+    // blurdroid take it, so BlueZ writes with response, unless only "write-without-response" is
+    // set, and Android picks the write type from the properties the same way. The mock
+    // characteristic stores the value for both write types.
+    pub fn write_value_with_type(&self, values: Vec<u8>, write_type: WriteType) -> Result<(), BluetoothError> {
+        if !self.get_properties()?.contains(write_type.get_property()) {
+            return Err(BluetoothError::NotSupported);
+        }
+        self.context.check_write(|| self.get_uuid())?;
+        self.run_operation(|| self.backend.write_value_with_type(values, write_type))
+    }
+
+    // The mock backend models the ATT transfer, where a Read Blob Response carries at most
+    // ATT_MTU - 1 bytes. The other backends read the whole value and return it from the offset.
+    pub fn read_value_at(&self, offset: u16) -> Result<Vec<u8>, BluetoothError> {
+        let value = self.read_value()?;
+        read_blob(value, offset, self.get_max_read_payload())
+    }
+
+    // Writes a value longer than ATT_MTU - 3 bytes with Prepare Write Requests and an
+    // Execute Write Request.
+    pub fn write_long_value(&self, values: Vec<u8>) -> Result<(), BluetoothError> {
+        let mut reliable_write = BluetoothReliableWrite::new(self.clone())?;
+        reliable_write.prepare_write(0, values)?;
+        // The new value replaces the whole current one.
+        reliable_write.apply(vec![])
     }
 
-    pub fn get_gatt_descriptors(&self) -> Result<Vec<BluetoothGATTDescriptor>, Box<Error>> {
-        let descriptors = try!(get_inner_and_call!(self, BluetoothGATTCharacteristic, get_gatt_descriptors));
-        Ok(descriptors.into_iter()
-                      .map(|descriptor| BluetoothGATTDescriptor::create_descriptor(self.clone(), descriptor))
-                      .collect())
+    // Requires the "reliable-write" property.
+    pub fn begin_reliable_write(&self) -> Result<BluetoothReliableWrite, BluetoothError> {
+        if !self.get_properties()?.contains(CharacteristicProperties::RELIABLE_WRITE) {
+            return Err(BluetoothError::NotSupported);
+        }
+        BluetoothReliableWrite::new(self.clone())
+    }
+
+    fn run_operation<T, F>(&self, operation: F) -> Result<T, BluetoothError>
+        where F: FnOnce() -> Result<T, BluetoothError>
+    {
+        self.queue.run(|| {
+            #[cfg(feature = "bluetooth-test")]
+            {
+                if is_mock!(self.backend, FakeBluetoothGATTCharacteristic) {
+                    return self.queue.run_mock(operation);
+                }
+            }
+            operation()
+        })
+    }
+
+    // The lowest possible ATT_MTU, if the backend does not report it.
+    fn get_mtu(&self) -> u16 {
+        self.device.get_mtu().unwrap_or(ATT_DEFAULT_MTU)
+    }
+
+    fn get_max_read_payload(&self) -> Option<usize> {
+        if is_mock!(self.backend, FakeBluetoothGATTCharacteristic) {
+            Some(self.get_mtu() as usize - 1)
+        } else {
+            None
+        }
     }
 
-    pub fn read_value(&self) -> Result<Vec<u8>, Box<Error>> {
-        get_inner_and_call!(self, BluetoothGATTCharacteristic, read_value)
+    pub fn start_notify(&self) -> Result<(), BluetoothError> {
+        self.run_operation(|| self.backend.start_notify())?;
+        let value = self.get_value().ok();
+        lock(&self.context.notifying).entry(self.get_id()).or_insert(NotifyingCharacteristic {
+            backend: self.backend.clone(),
+            device_id: self.device.get_id(),
+            value,
+            receivers: vec![],
+        });
+        Ok(())
     }
 
-    pub fn write_value(&self, values: Vec<u8>) -> Result<(), Box<Error>> {
-        get_inner_and_call!(self, BluetoothGATTCharacteristic, write_value, values)
+    // Yields the payload of every notification or indication, until `stop_notify` is called.
+    // The backends are read by `BluetoothAdapter::poll_events`, only the mock backend
+    // can deliver a notification by itself, see `push_notification`.
+    pub fn get_notifications(&self) -> Result<Receiver<Vec<u8>>, BluetoothError> {
+        let mut notifying = lock(&self.context.notifying);
+        let characteristic = notifying.get_mut(&self.get_id()).ok_or(BluetoothError::InvalidState)?;
+        let (sender, receiver) = mpsc::channel();
+        characteristic.receivers.push(sender);
+        Ok(receiver)
     }
 
-    pub fn start_notify(&self) -> Result<(), Box<Error>> {
-        get_inner_and_call!(self, BluetoothGATTCharacteristic, start_notify)
+    // Delivers the value as a notification, even if it equals the current value.
+    #[cfg(feature = "bluetooth-test")]
+    pub fn push_notification(&self, value: Vec<u8>) -> Result<(), BluetoothError> {
+        let id = self.get_id();
+        {
+            let mut notifying = lock(&self.context.notifying);
+            let characteristic = notifying.get_mut(&id).ok_or(BluetoothError::InvalidState)?;
+            self.set_value(value.clone())?;
+            characteristic.notify(value.clone());
+        }
+        self.context.subscribers.emit(BluetoothEvent::CharacteristicValueChanged(id, value));
+        Ok(())
     }
 
-    pub fn stop_notify(&self) -> Result<(), Box<Error>> {
-        get_inner_and_call!(self, BluetoothGATTCharacteristic, stop_notify)
+    pub fn stop_notify(&self) -> Result<(), BluetoothError> {
+        self.run_operation(|| self.backend.stop_notify())?;
+        lock(&self.context.notifying).remove(&self.get_id());
+        Ok(())
     }
 }
 
 impl BluetoothGATTDescriptor {
     fn create_descriptor(characteristic: BluetoothGATTCharacteristic, descriptor: String) -> BluetoothGATTDescriptor {
-        match characteristic {
-            #[cfg(all(target_os = "linux", feature = "bluetooth"))]
-            BluetoothGATTCharacteristic::Bluez(_bluez_characteristic) => {
-                BluetoothGATTDescriptor::Bluez(Arc::new(BluetoothGATTDescriptorBluez::new(descriptor)))
-            },
-            #[cfg(all(target_os = "android", feature = "bluetooth"))]
-            BluetoothGATTCharacteristic::Android(android_characteristic) => {
-                BluetoothGATTDescriptor::Android(
-                    Arc::new(BluetoothGATTDescriptorAndroid::new(android_characteristic, descriptor)))
-            },
-            #[cfg(not(any(all(target_os = "linux", feature = "bluetooth"), all(target_os = "android", feature = "bluetooth"))))]
-            BluetoothGATTCharacteristic::Empty(_characteristic) => {
-                BluetoothGATTDescriptor::Empty(Arc::new(BluetoothGATTDescriptorEmpty::new(descriptor)))
-            },
-            #[cfg(feature = "bluetooth-test")]
-            BluetoothGATTCharacteristic::Mock(fake_characteristic) => {
-                BluetoothGATTDescriptor::Mock(FakeBluetoothGATTDescriptor::new_empty(fake_characteristic, descriptor))
-            },
+        let backend = characteristic.backend.create_descriptor(descriptor);
+        BluetoothGATTDescriptor {
+            backend,
+            context: characteristic.context,
+            device: characteristic.device,
+            queue: characteristic.queue,
         }
     }
 
     #[cfg(feature = "bluetooth-test")]
     pub fn create_mock_descriptor(characteristic: BluetoothGATTCharacteristic,
                                   descriptor: String)
-                                  -> Result<BluetoothGATTDescriptor, Box<Error>> {
-        match characteristic {
-            BluetoothGATTCharacteristic::Mock(fake_characteristic) => {
-                Ok(BluetoothGATTDescriptor::Mock(
-                    FakeBluetoothGATTDescriptor::new_empty(fake_characteristic, descriptor)))
-            },
-            _ => {
-                Err(Box::from(NOT_SUPPORTED_ON_MOCK_ERROR))
-            },
+                                  -> Result<BluetoothGATTDescriptor, BluetoothError> {
+        if !is_mock!(characteristic.backend, FakeBluetoothGATTCharacteristic) {
+            return Err(BluetoothError::NotSupported);
         }
+        let backend = characteristic.backend.create_descriptor(descriptor);
+        Ok(BluetoothGATTDescriptor {
+            backend,
+            context: characteristic.context,
+            device: characteristic.device,
+            queue: characteristic.queue,
+        })
     }
 
     pub fn get_id(&self) -> String {
-        get_inner_and_call!(self, BluetoothGATTDescriptor, get_id)
+        self.backend.get_id()
     }
 
     #[cfg(feature = "bluetooth-test")]
     pub fn set_id(&self, id: String) {
-        match self {
-            &BluetoothGATTDescriptor::Mock(ref fake_descriptor) => fake_descriptor.set_id(id),
-            _ => (),
+        if let Some(fake_descriptor) = get_fake!(self.backend, FakeBluetoothGATTDescriptor) {
+            fake_descriptor.set_id(id);
         }
     }
 
-    pub fn get_uuid(&self) -> Result<String, Box<Error>> {
-        get_inner_and_call!(self, BluetoothGATTDescriptor, get_uuid)
+    pub fn get_uuid(&self) -> Result<BluetoothUuid, BluetoothError> {
+        let uuid = self.backend.get_uuid()?;
+        BluetoothUuid::parse(&uuid)
     }
 
     #[cfg(feature = "bluetooth-test")]
-    pub fn set_uuid(&self, uuid: String) -> Result<(), Box<Error>> {
-        get_inner_and_call_test_func!(self, BluetoothGATTDescriptor, set_uuid, uuid)
+    pub fn set_uuid(&self, uuid: BluetoothUuid) -> Result<(), BluetoothError> {
+        get_inner_and_call_test_func!(self.backend, FakeBluetoothGATTDescriptor, set_uuid, uuid.to_string())
     }
 
-    pub fn get_value(&self) -> Result<Vec<u8>, Box<Error>> {
-        get_inner_and_call!(self, BluetoothGATTDescriptor, get_value)
+    pub fn get_value(&self) -> Result<Vec<u8>, BluetoothError> {
+        self.backend.get_value()
     }
 
     #[cfg(feature = "bluetooth-test")]
-    pub fn set_value(&self, value: Vec<u8>) -> Result<(), Box<Error>> {
-        get_inner_and_call_test_func!(self, BluetoothGATTDescriptor, set_value, Some(value))
+    pub fn set_value(&self, value: Vec<u8>) -> Result<(), BluetoothError> {
+        get_inner_and_call_test_func!(self.backend, FakeBluetoothGATTDescriptor, set_value, Some(value))
     }
 
-    pub fn get_flags(&self) -> Result<Vec<String>, Box<Error>> {
-        get_inner_and_call!(self, BluetoothGATTDescriptor, get_flags)
+    pub fn get_flags(&self) -> Result<Vec<String>, BluetoothError> {
+        self.backend.get_flags()
+    }
+
+    pub fn get_permissions(&self) -> Result<DescriptorPermissions, BluetoothError> {
+        Ok(DescriptorPermissions::from_flags(&self.get_flags()?))
     }
 
     #[cfg(feature = "bluetooth-test")]
-    pub fn set_flags(&self, flags: Vec<String>) -> Result<(), Box<Error>> {
-        get_inner_and_call_test_func!(self, BluetoothGATTDescriptor, set_flags, flags)
+    pub fn set_flags(&self, flags: Vec<String>) -> Result<(), BluetoothError> {
+        get_inner_and_call_test_func!(self.backend, FakeBluetoothGATTDescriptor, set_flags, flags)
+    }
+
+    pub fn read_value(&self) -> Result<Vec<u8>, BluetoothError> {
+        self.context.check_read(|| self.get_uuid())?;
+        self.run_operation(|| self.backend.read_value())
+    }
+
+    pub fn write_value(&self, values: Vec<u8>) -> Result<(), BluetoothError> {
+        self.context.check_write(|| self.get_uuid())?;
+        self.run_operation(|| self.backend.write_value(values))
+    }
+
+    fn run_operation<T, F>(&self, operation: F) -> Result<T, BluetoothError>
+        where F: FnOnce() -> Result<T, BluetoothError>
+    {
+        self.queue.run(|| {
+            #[cfg(feature = "bluetooth-test")]
+            {
+                if is_mock!(self.backend, FakeBluetoothGATTDescriptor) {
+                    return self.queue.run_mock(operation);
+                }
+            }
+            operation()
+        })
+    }
+
+    pub fn read_value_at(&self, offset: u16) -> Result<Vec<u8>, BluetoothError> {
+        let value = self.read_value()?;
+        let max_payload = if is_mock!(self.backend, FakeBluetoothGATTDescriptor) {
+            Some(self.device.get_mtu().unwrap_or(ATT_DEFAULT_MTU) as usize - 1)
+        } else {
+            None
+        };
+        read_blob(value, offset, max_payload)
+    }
+}
+
+// The queue of Prepare Write Requests of a long or reliable write. The values are split into
+// parts of at most ATT_MTU - 5 bytes, and nothing is written until `execute`. Neither blurz,
+// blurdroid nor blurmock can send the requests one by one, so `execute` applies the queue
+// to the value, and writes the result as a whole.
+#[derive(Debug)]
+pub struct BluetoothReliableWrite {
+    characteristic: BluetoothGATTCharacteristic,
+    prepared_writes: Vec<(u16, Vec<u8>)>,
+}
+
+impl BluetoothReliableWrite {
+    fn new(characteristic: BluetoothGATTCharacteristic) -> Result<BluetoothReliableWrite, BluetoothError> {
+        characteristic.context.check_write(|| characteristic.get_uuid())?;
+        Ok(BluetoothReliableWrite {
+            characteristic,
+            prepared_writes: vec![],
+        })
+    }
+
+    pub fn get_characteristic(&self) -> &BluetoothGATTCharacteristic {
+        &self.characteristic
     }
 
-    pub fn read_value(&self) -> Result<Vec<u8>, Box<Error>> {
-        get_inner_and_call!(self, BluetoothGATTDescriptor, read_value)
+    // The (offset, part) pairs of the queued Prepare Write Requests.
+    pub fn get_prepared_writes(&self) -> &[(u16, Vec<u8>)] {
+        &self.prepared_writes
     }
 
-    pub fn write_value(&self, values: Vec<u8>) -> Result<(), Box<Error>> {
-        get_inner_and_call!(self, BluetoothGATTDescriptor, write_value, values)
+    pub fn prepare_write(&mut self, offset: u16, values: Vec<u8>) -> Result<(), BluetoothError> {
+        if offset as usize + values.len() > ATT_MAX_VALUE_LENGTH {
+            return Err(BluetoothError::GattError(ATT_INVALID_ATTRIBUTE_VALUE_LENGTH));
+        }
+        let max_payload = self.characteristic.get_mtu() as usize - 5;
+        if values.is_empty() {
+            self.prepared_writes.push((offset, values));
+            return Ok(());
+        }
+        for (index, part) in values.chunks(max_payload).enumerate() {
+            self.prepared_writes.push((offset + (index * max_payload) as u16, part.to_vec()));
+        }
+        Ok(())
     }
+
+    // Every part overwrites the bytes of the current value at its offset, in the order the parts
+    // were prepared, and extends the value if it runs past its end; the bytes no part covers
+    // keep their value. A characteristic without the "read" property, whose value can not be
+    // read, is written with the parts alone. Nothing is written, if an offset is past the end
+    // of the value.
+    pub fn execute(self) -> Result<(), BluetoothError> {
+        if self.prepared_writes.is_empty() {
+            return Ok(());
+        }
+        let characteristic = &self.characteristic;
+        // Reading the value for the write is not a read of the blocklist.
+        let value = match characteristic.run_operation(|| characteristic.backend.read_value()) {
+            Ok(value) => value,
+            Err(_) if !characteristic.get_properties()?.contains(CharacteristicProperties::READ) => vec![],
+            Err(error) => return Err(error),
+        };
+        self.apply(value)
+    }
+
+    fn apply(self, mut value: Vec<u8>) -> Result<(), BluetoothError> {
+        for (offset, part) in self.prepared_writes {
+            let offset = offset as usize;
+            if offset > value.len() {
+                return Err(BluetoothError::GattError(ATT_INVALID_OFFSET));
+            }
+            let overlap = part.len().min(value.len() - offset);
+            value[offset..offset + overlap].copy_from_slice(&part[..overlap]);
+            value.extend_from_slice(&part[overlap..]);
+        }
+        self.characteristic.write_value(value)
+    }
+
+    // Drops the queued writes, like an Execute Write Request with the cancel flag.
+    pub fn abort(self) {}
 }
@@ -0,0 +1,227 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+// A managed connection to a device, on top of `BluetoothDevice::connect` and `disconnect`.
+// The backends do not report link loss, so like `BluetoothAdapter::poll_events`,
+// `BluetoothConnection::poll` compares the connected property of the device with the
+// state of the connection.
+
+use bluetooth::BluetoothDevice;
+use error::BluetoothError;
+use events::EventSubscribers;
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError};
+use std::sync::{Mutex, MutexGuard};
+use std::thread;
+use std::time::Duration;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ConnectionState {
+    Disconnected,
+    Connecting,
+    Connected,
+    Disconnecting,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum ConnectionEvent {
+    StateChanged(ConnectionState),
+    // The number of the failed connection attempt, starting at 1, and its error.
+    AttemptFailed(u32, BluetoothError),
+    // The device disconnected, without `disconnect` being called.
+    LinkLost,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ConnectionOptions {
+    // How long a connection attempt may take.
+    pub timeout: Duration,
+    // How many times a failed connection attempt is repeated.
+    pub retries: u32,
+    // The wait before the first repeated attempt, doubled for every further one,
+    // up to `max_backoff`.
+    pub backoff: Duration,
+    pub max_backoff: Duration,
+    // Whether `poll` connects again, after the link was lost.
+    pub auto_reconnect: bool,
+}
+
+#[derive(Debug)]
+pub struct BluetoothConnection {
+    device: BluetoothDevice,
+    options: ConnectionOptions,
+    state: Mutex<ConnectionState>,
+    subscribers: EventSubscribers<ConnectionEvent>,
+}
+
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    match mutex.lock() {
+        Ok(guard) => guard,
+        Err(poisoned) => poisoned.into_inner(),
+    }
+}
+
+impl Default for ConnectionOptions {
+    fn default() -> ConnectionOptions {
+        ConnectionOptions {
+            timeout: Duration::from_secs(10),
+            retries: 0,
+            backoff: Duration::from_secs(1),
+            max_backoff: Duration::from_secs(30),
+            auto_reconnect: false,
+        }
+    }
+}
+
+impl ConnectionOptions {
+    pub fn new() -> ConnectionOptions {
+        ConnectionOptions::default()
+    }
+
+    // The wait after the failed attempt with the given number.
+    fn get_backoff(&self, attempt: u32) -> Duration {
+        let factor = 1u32 << (attempt - 1).min(16);
+        self.backoff.checked_mul(factor).map_or(self.max_backoff, |backoff| backoff.min(self.max_backoff))
+    }
+}
+
+impl BluetoothConnection {
+    // The connection starts in the state matching the connected property of the device.
+    pub fn new(device: BluetoothDevice, options: ConnectionOptions) -> BluetoothConnection {
+        let state = match device.is_connected() {
+            Ok(true) => ConnectionState::Connected,
+            _ => ConnectionState::Disconnected,
+        };
+        BluetoothConnection {
+            device,
+            options,
+            state: Mutex::new(state),
+            subscribers: EventSubscribers::default(),
+        }
+    }
+
+    pub fn get_device(&self) -> &BluetoothDevice {
+        &self.device
+    }
+
+    pub fn get_options(&self) -> &ConnectionOptions {
+        &self.options
+    }
+
+    pub fn get_state(&self) -> ConnectionState {
+        *lock(&self.state)
+    }
+
+    pub fn subscribe(&self) -> Receiver<ConnectionEvent> {
+        self.subscribers.subscribe()
+    }
+
+    // Tries to connect up to `retries + 1` times, and returns the error of the last attempt,
+    // if none of them succeeded.
+    pub fn connect(&self) -> Result<(), BluetoothError> {
+        match self.transition(&[ConnectionState::Disconnected], ConnectionState::Connecting) {
+            Ok(()) => (),
+            Err(ConnectionState::Connected) => return Ok(()),
+            Err(_) => return Err(BluetoothError::InvalidState),
+        }
+        let attempts = self.options.retries.saturating_add(1);
+        let mut attempt = 1;
+        loop {
+            let error = match self.connect_once() {
+                Ok(()) => {
+                    self.set_state(ConnectionState::Connected);
+                    return Ok(());
+                },
+                Err(error) => error,
+            };
+            self.subscribers.emit(ConnectionEvent::AttemptFailed(attempt, error.clone()));
+            if attempt >= attempts {
+                self.set_state(ConnectionState::Disconnected);
+                return Err(error);
+            }
+            thread::sleep(self.options.get_backoff(attempt));
+            attempt += 1;
+        }
+    }
+
+    // The blocking `BluetoothDevice::connect` call can not be interrupted, so after a timeout
+    // it still runs on its own thread. If it connects the device later, `poll` notices it.
+    fn connect_once(&self) -> Result<(), BluetoothError> {
+        let (sender, receiver) = mpsc::channel();
+        let device = self.device.clone();
+        let delay = self.device.context.get_mock_delay();
+        thread::spawn(move || {
+            if delay > Duration::from_secs(0) {
+                thread::sleep(delay);
+            }
+            let _ = sender.send(device.connect());
+        });
+        match receiver.recv_timeout(self.options.timeout) {
+            Ok(Ok(())) => Ok(()),
+            // E.g. an earlier attempt, which timed out, connected the device in the meantime.
+            Ok(Err(_)) if self.device.is_connected() == Ok(true) => Ok(()),
+            Ok(Err(error)) => Err(error),
+            Err(RecvTimeoutError::Timeout) => Err(BluetoothError::Timeout),
+            Err(RecvTimeoutError::Disconnected) =>
+                Err(BluetoothError::Backend(String::from("The backend call panicked."))),
+        }
+    }
+
+    pub fn disconnect(&self) -> Result<(), BluetoothError> {
+        match self.transition(&[ConnectionState::Connected], ConnectionState::Disconnecting) {
+            Ok(()) => (),
+            Err(ConnectionState::Disconnected) => return Ok(()),
+            Err(_) => return Err(BluetoothError::InvalidState),
+        }
+        match self.device.disconnect() {
+            Err(error) if self.device.is_connected() != Ok(false) => {
+                self.set_state(ConnectionState::Connected);
+                Err(error)
+            },
+            _ => {
+                self.set_state(ConnectionState::Disconnected);
+                Ok(())
+            },
+        }
+    }
+
+    // Updates the state from the connected property of the device. A lost link is reported
+    // with a `LinkLost` event, and connected again with `auto_reconnect`.
+    pub fn poll(&self) -> Result<(), BluetoothError> {
+        let connected = self.device.is_connected()?;
+        if connected {
+            let _ = self.transition(&[ConnectionState::Disconnected], ConnectionState::Connected);
+            return Ok(());
+        }
+        if self.transition(&[ConnectionState::Connected], ConnectionState::Disconnected).is_err() {
+            return Ok(());
+        }
+        self.subscribers.emit(ConnectionEvent::LinkLost);
+        if self.options.auto_reconnect {
+            return self.connect();
+        }
+        Ok(())
+    }
+
+    // Changes the state, if it is one of `from`, or returns the current state.
+    fn transition(&self, from: &[ConnectionState], to: ConnectionState) -> Result<(), ConnectionState> {
+        let mut state = lock(&self.state);
+        if !from.contains(&state) {
+            return Err(*state);
+        }
+        *state = to;
+        drop(state);
+        self.subscribers.emit(ConnectionEvent::StateChanged(to));
+        Ok(())
+    }
+
+    fn set_state(&self, to: ConnectionState) {
+        let mut state = lock(&self.state);
+        if *state == to {
+            return;
+        }
+        *state = to;
+        drop(state);
+        self.subscribers.emit(ConnectionEvent::StateChanged(to));
+    }
+}
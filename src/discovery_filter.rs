@@ -0,0 +1,118 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+// Modelled on the BlueZ org.bluez.Adapter1.SetDiscoveryFilter parameters.
+// https://git.kernel.org/pub/scm/bluetooth/bluez.git/tree/doc/adapter-api.txt
+
+use bluetooth::BluetoothDevice;
+#[cfg(all(target_os = "linux", feature = "bluetooth"))]
+use dbus::MessageItem;
+use error::BluetoothError;
+#[cfg(all(target_os = "linux", feature = "bluetooth"))]
+use std::borrow::Cow;
+use uuid::BluetoothUuid;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum DiscoveryTransport {
+    #[default]
+    Auto,
+    LowEnergy,
+    BrEdr,
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct DiscoveryFilter {
+    // A device matches if it advertises any of these services, or if the list is empty.
+    pub uuids: Vec<BluetoothUuid>,
+    pub name: Option<String>,
+    pub name_prefix: Option<String>,
+    // The minimum RSSI, in dBm.
+    pub rssi: Option<i16>,
+    // The maximum difference between the TX power and the RSSI, in dB.
+    pub pathloss: Option<u16>,
+    pub transport: DiscoveryTransport,
+    // Whether a device is reported again, when its RSSI changes.
+    pub duplicate_data: bool,
+}
+
+impl DiscoveryFilter {
+    pub fn new() -> DiscoveryFilter {
+        DiscoveryFilter::default()
+    }
+
+    // BlueZ refuses a filter with both an RSSI and a pathloss threshold.
+    pub fn validate(&self) -> Result<(), BluetoothError> {
+        if self.rssi.is_some() && self.pathloss.is_some() {
+            return Err(BluetoothError::InvalidArgument("rssi and pathloss are mutually exclusive".to_owned()));
+        }
+        Ok(())
+    }
+
+    // The SetDiscoveryFilter dictionary. It has no entry for the name and the name prefix.
+    #[cfg(all(target_os = "linux", feature = "bluetooth"))]
+    pub(crate) fn to_bluez_filter(&self) -> MessageItem {
+        let uuids = self.uuids.iter().map(|uuid| MessageItem::Str(uuid.to_string())).collect();
+        let mut entries = vec![(String::from("UUIDs"), MessageItem::Array(uuids, Cow::Borrowed("s")))];
+        if let Some(rssi) = self.rssi {
+            entries.push((String::from("RSSI"), MessageItem::Int16(rssi)));
+        }
+        if let Some(pathloss) = self.pathloss {
+            entries.push((String::from("Pathloss"), MessageItem::UInt16(pathloss)));
+        }
+        let transport = match self.transport {
+            DiscoveryTransport::Auto => "auto",
+            DiscoveryTransport::LowEnergy => "le",
+            DiscoveryTransport::BrEdr => "bredr",
+        };
+        entries.push((String::from("Transport"), MessageItem::from(transport)));
+        entries.push((String::from("DuplicateData"), MessageItem::Bool(self.duplicate_data)));
+        MessageItem::from_dict::<(), _>(entries.into_iter().map(Ok)).unwrap()
+    }
+
+    // Properties the backend can not read do not match any threshold.
+    pub fn matches(&self, device: &BluetoothDevice) -> bool {
+        if !self.uuids.is_empty() {
+            let uuids = device.get_uuids().unwrap_or_default();
+            if !self.uuids.iter().any(|uuid| uuids.contains(uuid)) {
+                return false;
+            }
+        }
+        if !self.matches_name(device) {
+            return false;
+        }
+        if let Some(threshold) = self.rssi {
+            if !device.get_rssi().is_ok_and(|rssi| rssi >= threshold) {
+                return false;
+            }
+        }
+        if let Some(pathloss) = self.pathloss {
+            match (device.get_tx_power(), device.get_rssi()) {
+                (Ok(tx_power), Ok(rssi)) if (tx_power as i32 - rssi as i32) <= pathloss as i32 => (),
+                _ => return false,
+            }
+        }
+        // Without the transport a device was found on, only BR/EDR devices are assumed to
+        // have a class of device.
+        let has_class = device.get_class().is_ok_and(|class| class != 0);
+        match self.transport {
+            DiscoveryTransport::Auto => true,
+            DiscoveryTransport::LowEnergy => !has_class,
+            DiscoveryTransport::BrEdr => has_class,
+        }
+    }
+
+    pub fn matches_name(&self, device: &BluetoothDevice) -> bool {
+        if self.name.is_none() && self.name_prefix.is_none() {
+            return true;
+        }
+        let name = match device.get_name() {
+            Ok(name) => name,
+            Err(_) => return false,
+        };
+        if self.name.as_ref().is_some_and(|expected| *expected != name) {
+            return false;
+        }
+        self.name_prefix.as_ref().is_none_or(|prefix| name.starts_with(prefix.as_str()))
+    }
+}
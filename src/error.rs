@@ -0,0 +1,120 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+use std::error::Error;
+use std::fmt;
+
+// https://www.bluetooth.com/specifications/bluetooth-core-specification
+// Vol 3, Part F, 3.4.1.1: Error Response ATT error codes.
+pub const ATT_INVALID_HANDLE: u8 = 0x01;
+pub const ATT_READ_NOT_PERMITTED: u8 = 0x02;
+pub const ATT_WRITE_NOT_PERMITTED: u8 = 0x03;
+pub const ATT_INSUFFICIENT_AUTHENTICATION: u8 = 0x05;
+pub const ATT_REQUEST_NOT_SUPPORTED: u8 = 0x06;
+pub const ATT_INVALID_OFFSET: u8 = 0x07;
+pub const ATT_INSUFFICIENT_AUTHORIZATION: u8 = 0x08;
+pub const ATT_ATTRIBUTE_NOT_FOUND: u8 = 0x0A;
+pub const ATT_ATTRIBUTE_NOT_LONG: u8 = 0x0B;
+pub const ATT_INVALID_ATTRIBUTE_VALUE_LENGTH: u8 = 0x0D;
+pub const ATT_UNLIKELY_ERROR: u8 = 0x0E;
+pub const ATT_INSUFFICIENT_ENCRYPTION: u8 = 0x0F;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum BluetoothError {
+    // The operation is not available on this platform or structure.
+    NotSupported,
+    // The requested adapter, device or attribute does not exist.
+    NotFound,
+    // The operation requires a connected device.
+    NotConnected,
+    // The object is not in a state where the operation can be performed.
+    InvalidState,
+    // The remote device answered with an ATT error code.
+    GattError(u8),
+    // The backend did not answer in time.
+    Timeout,
+    // The operation was refused by the platform or the remote device.
+    PermissionDenied,
+    // The attribute, or the operation on it, is excluded by the GATT blocklist.
+    Blocklisted,
+    // The operation was cancelled before it started.
+    Cancelled,
+    // A value passed to, or returned by, the backend could not be parsed.
+    InvalidArgument(String),
+    // Any other error reported by the underlying backend.
+    Backend(String),
+}
+
+impl fmt::Display for BluetoothError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            BluetoothError::NotSupported => write!(f, "Error! Not supported!"),
+            BluetoothError::NotFound => write!(f, "Error! Not found!"),
+            BluetoothError::NotConnected => write!(f, "Error! Not connected!"),
+            BluetoothError::InvalidState => write!(f, "Error! Invalid state!"),
+            BluetoothError::GattError(code) => write!(f, "Error! GATT error 0x{:02X}!", code),
+            BluetoothError::Timeout => write!(f, "Error! Timed out!"),
+            BluetoothError::PermissionDenied => write!(f, "Error! Permission denied!"),
+            BluetoothError::Blocklisted => write!(f, "Error! Blocklisted UUID!"),
+            BluetoothError::Cancelled => write!(f, "Error! Cancelled!"),
+            BluetoothError::InvalidArgument(ref value) => write!(f, "Error! Invalid argument: {}", value),
+            BluetoothError::Backend(ref message) => write!(f, "Error! {}", message),
+        }
+    }
+}
+
+impl Error for BluetoothError {}
+
+// blurz reports D-Bus errors, whose debug output contains the error name,
+// e.g. `D-Bus error: Not connected (org.bluez.Error.NotConnected)`.
+const BACKEND_ERROR_NAMES: &[(&str, BluetoothError)] = &[
+    ("org.bluez.Error.NotSupported", BluetoothError::NotSupported),
+    ("org.bluez.Error.DoesNotExist", BluetoothError::NotFound),
+    ("org.freedesktop.DBus.Error.UnknownObject", BluetoothError::NotFound),
+    ("org.bluez.Error.NotConnected", BluetoothError::NotConnected),
+    ("org.bluez.Error.NotReady", BluetoothError::InvalidState),
+    ("org.bluez.Error.InProgress", BluetoothError::InvalidState),
+    ("org.bluez.Error.AlreadyExists", BluetoothError::InvalidState),
+    ("org.bluez.Error.AlreadyConnected", BluetoothError::InvalidState),
+    ("org.bluez.Error.InvalidOffset", BluetoothError::GattError(ATT_INVALID_OFFSET)),
+    ("org.bluez.Error.InvalidValueLength", BluetoothError::GattError(ATT_INVALID_ATTRIBUTE_VALUE_LENGTH)),
+    ("org.bluez.Error.NotPermitted", BluetoothError::PermissionDenied),
+    ("org.bluez.Error.NotAuthorized", BluetoothError::PermissionDenied),
+    ("org.bluez.Error.AuthenticationFailed", BluetoothError::PermissionDenied),
+    ("org.bluez.Error.AuthenticationRejected", BluetoothError::PermissionDenied),
+    ("org.freedesktop.DBus.Error.AccessDenied", BluetoothError::PermissionDenied),
+    ("org.freedesktop.DBus.Error.NoReply", BluetoothError::Timeout),
+    ("org.freedesktop.DBus.Error.Timeout", BluetoothError::Timeout),
+];
+
+// Messages returned by blurz and blurmock. The errors of blurdroid are not mapped yet, so on
+// Android every backend error is reported as `Backend`, with the original message.
+const BACKEND_ERROR_MESSAGES: &[(&str, BluetoothError)] = &[
+    ("Bluetooth adapter not found", BluetoothError::NotFound),
+    ("Device not connected.", BluetoothError::NotConnected),
+    ("The device is not connected.", BluetoothError::NotConnected),
+    ("No device exists with the given id.", BluetoothError::NotFound),
+    ("No service exists with the given id.", BluetoothError::NotFound),
+    ("No characteristic exists with the given id.", BluetoothError::NotFound),
+    ("No descriptor exists with the given id.", BluetoothError::NotFound),
+    ("No device found.", BluetoothError::NotFound),
+];
+
+impl From<Box<dyn Error>> for BluetoothError {
+    fn from(error: Box<dyn Error>) -> BluetoothError {
+        let message = error.to_string();
+        let details = format!("{:?}", error);
+        for &(name, ref bluetooth_error) in BACKEND_ERROR_NAMES {
+            if details.contains(name) {
+                return bluetooth_error.clone();
+            }
+        }
+        for &(known_message, ref bluetooth_error) in BACKEND_ERROR_MESSAGES {
+            if message == known_message {
+                return bluetooth_error.clone();
+            }
+        }
+        BluetoothError::Backend(message)
+    }
+}
@@ -0,0 +1,63 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+use std::sync::Mutex;
+use std::sync::mpsc::{self, Receiver, Sender};
+use uuid::BluetoothUuid;
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum BluetoothEvent {
+    AdapterPoweredChanged(bool),
+    // The id of the device.
+    DeviceAdded(String),
+    DeviceRemoved(String),
+    PropertyChanged(String, DeviceProperty),
+    // The id of the characteristic and its new value.
+    CharacteristicValueChanged(String, Vec<u8>),
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum DeviceProperty {
+    Name(String),
+    Rssi(i16),
+    TxPower(i16),
+    Connected(bool),
+    Paired(bool),
+    Uuids(Vec<BluetoothUuid>),
+}
+
+// The senders of every receiver handed out by `BluetoothAdapter::subscribe`, or the
+// `subscribe` function of an other event source.
+#[derive(Debug)]
+pub(crate) struct EventSubscribers<T = BluetoothEvent> {
+    senders: Mutex<Vec<Sender<T>>>,
+}
+
+impl<T> Default for EventSubscribers<T> {
+    fn default() -> EventSubscribers<T> {
+        EventSubscribers {
+            senders: Mutex::new(vec![]),
+        }
+    }
+}
+
+impl<T: Clone> EventSubscribers<T> {
+    pub(crate) fn subscribe(&self) -> Receiver<T> {
+        let (sender, receiver) = mpsc::channel();
+        match self.senders.lock() {
+            Ok(mut senders) => senders.push(sender),
+            Err(poisoned) => poisoned.into_inner().push(sender),
+        }
+        receiver
+    }
+
+    // Sends the event to every subscriber, and forgets the ones whose receiver was dropped.
+    pub(crate) fn emit(&self, event: T) {
+        let mut senders = match self.senders.lock() {
+            Ok(guard) => guard,
+            Err(poisoned) => poisoned.into_inner(),
+        };
+        senders.retain(|sender| sender.send(event.clone()).is_ok());
+    }
+}
@@ -0,0 +1,264 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+// The device filters of the Web Bluetooth requestDevice function.
+// https://webbluetoothcg.github.io/web-bluetooth/#device-discovery
+
+use bluetooth::{BluetoothAdapter, BluetoothDevice};
+use error::BluetoothError;
+use std::collections::{BTreeSet, HashMap, HashSet};
+use uuid::BluetoothUuid;
+
+// https://webbluetoothcg.github.io/web-bluetooth/#dom-bluetoothlescanfilterinit-name
+const MAX_NAME_LENGTH: usize = 248;
+
+// BluetoothManufacturerDataFilterInit. A missing mask matches every bit of the prefix.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BluetoothManufacturerDataFilter {
+    pub company_identifier: u16,
+    pub data_prefix: Vec<u8>,
+    pub mask: Option<Vec<u8>>,
+}
+
+// BluetoothServiceDataFilterInit. A missing mask matches every bit of the prefix.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BluetoothServiceDataFilter {
+    pub service: BluetoothUuid,
+    pub data_prefix: Vec<u8>,
+    pub mask: Option<Vec<u8>>,
+}
+
+// BluetoothLEScanFilterInit.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct BluetoothLEScanFilter {
+    pub services: Option<Vec<BluetoothUuid>>,
+    pub name: Option<String>,
+    pub name_prefix: Option<String>,
+    pub manufacturer_data: Option<Vec<BluetoothManufacturerDataFilter>>,
+    pub service_data: Option<Vec<BluetoothServiceDataFilter>>,
+}
+
+// RequestDeviceOptions.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct RequestDeviceOptions {
+    pub filters: Option<Vec<BluetoothLEScanFilter>>,
+    pub exclusion_filters: Option<Vec<BluetoothLEScanFilter>>,
+    pub optional_services: Vec<BluetoothUuid>,
+    pub optional_manufacturer_data: Vec<u16>,
+    pub accept_all_devices: bool,
+}
+
+// The outcome of matching a device: whether it can be offered to the user, and what the page may
+// access on it once it is chosen.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct FilterResult {
+    pub matched: bool,
+    pub allowed_services: BTreeSet<BluetoothUuid>,
+    pub allowed_manufacturer_data: BTreeSet<u16>,
+}
+
+// The advertised properties of a device the filters are evaluated on. A property
+// the backend can not read is treated as missing.
+struct DeviceData {
+    name: Option<String>,
+    uuids: Vec<BluetoothUuid>,
+    manufacturer_data: HashMap<u16, Vec<u8>>,
+    service_data: HashMap<BluetoothUuid, Vec<u8>>,
+}
+
+fn type_error(message: &str) -> BluetoothError {
+    BluetoothError::InvalidArgument(message.to_owned())
+}
+
+// https://webbluetoothcg.github.io/web-bluetooth/#bluetoothdatafilterinit-canonicalizing
+fn validate_data_filter(data_prefix: &[u8], mask: &Option<Vec<u8>>) -> Result<(), BluetoothError> {
+    match *mask {
+        Some(ref mask) if mask.len() != data_prefix.len() =>
+            Err(type_error("'mask' size must be equal to 'dataPrefix' size.")),
+        _ => Ok(()),
+    }
+}
+
+// https://webbluetoothcg.github.io/web-bluetooth/#bluetoothdatafilterinit-matches
+fn data_matches(data: &[u8], data_prefix: &[u8], mask: &Option<Vec<u8>>) -> bool {
+    if data.len() < data_prefix.len() {
+        return false;
+    }
+    data_prefix.iter().enumerate().all(|(index, prefix_byte)| {
+        let mask_byte = mask.as_ref().map_or(0xFF, |mask| mask[index]);
+        data[index] & mask_byte == prefix_byte & mask_byte
+    })
+}
+
+impl BluetoothLEScanFilter {
+    // https://webbluetoothcg.github.io/web-bluetooth/#bluetoothlescanfilterinit-canonicalizing
+    pub fn validate(&self) -> Result<(), BluetoothError> {
+        if self.services.is_none() && self.name.is_none() && self.name_prefix.is_none() &&
+           self.manufacturer_data.is_none() && self.service_data.is_none() {
+            return Err(type_error("A filter must restrict the devices in some way."));
+        }
+        if self.services.as_ref().is_some_and(|services| services.is_empty()) {
+            return Err(type_error("'services', if present, must contain at least one service."));
+        }
+        if self.name.as_ref().is_some_and(|name| name.len() > MAX_NAME_LENGTH) {
+            return Err(type_error("A device name can't be longer than 248 bytes."));
+        }
+        if let Some(ref name_prefix) = self.name_prefix {
+            if name_prefix.is_empty() {
+                return Err(type_error("'namePrefix', if present, must be nonempty."));
+            }
+            if name_prefix.len() > MAX_NAME_LENGTH {
+                return Err(type_error("A device name can't be longer than 248 bytes."));
+            }
+        }
+        if let Some(ref manufacturer_data) = self.manufacturer_data {
+            if manufacturer_data.is_empty() {
+                return Err(type_error("'manufacturerData', if present, must be non-empty to filter devices."));
+            }
+            let mut company_identifiers = HashSet::new();
+            for filter in manufacturer_data {
+                if !company_identifiers.insert(filter.company_identifier) {
+                    return Err(type_error("'companyIdentifier' must be unique in 'manufacturerData'."));
+                }
+                validate_data_filter(&filter.data_prefix, &filter.mask)?;
+            }
+        }
+        if let Some(ref service_data) = self.service_data {
+            if service_data.is_empty() {
+                return Err(type_error("'serviceData', if present, must be non-empty to filter devices."));
+            }
+            let mut services = HashSet::new();
+            for filter in service_data {
+                if !services.insert(filter.service) {
+                    return Err(type_error("'service' must be unique in 'serviceData'."));
+                }
+                validate_data_filter(&filter.data_prefix, &filter.mask)?;
+            }
+        }
+        Ok(())
+    }
+
+    // https://webbluetoothcg.github.io/web-bluetooth/#matches-a-filter
+    fn matches(&self, device: &DeviceData) -> bool {
+        if let Some(ref name) = self.name {
+            if device.name.as_ref() != Some(name) {
+                return false;
+            }
+        }
+        if let Some(ref name_prefix) = self.name_prefix {
+            if !device.name.as_ref().is_some_and(|name| name.starts_with(name_prefix.as_str())) {
+                return false;
+            }
+        }
+        if let Some(ref services) = self.services {
+            if !services.iter().all(|service| device.uuids.contains(service)) {
+                return false;
+            }
+        }
+        if let Some(ref manufacturer_data) = self.manufacturer_data {
+            for filter in manufacturer_data {
+                match device.manufacturer_data.get(&filter.company_identifier) {
+                    Some(data) if data_matches(data, &filter.data_prefix, &filter.mask) => (),
+                    _ => return false,
+                }
+            }
+        }
+        if let Some(ref service_data) = self.service_data {
+            for filter in service_data {
+                match device.service_data.get(&filter.service) {
+                    Some(data) if data_matches(data, &filter.data_prefix, &filter.mask) => (),
+                    _ => return false,
+                }
+            }
+        }
+        true
+    }
+}
+
+impl DeviceData {
+    fn read(device: &BluetoothDevice) -> DeviceData {
+        DeviceData {
+            name: device.get_name().ok(),
+            uuids: device.get_uuids().unwrap_or_default(),
+            manufacturer_data: device.get_manufacturer_data().unwrap_or_default(),
+            service_data: device.get_service_data().unwrap_or_default(),
+        }
+    }
+}
+
+impl RequestDeviceOptions {
+    // https://webbluetoothcg.github.io/web-bluetooth/#dom-bluetooth-requestdevice
+    pub fn validate(&self) -> Result<(), BluetoothError> {
+        match self.filters {
+            Some(_) if self.accept_all_devices =>
+                return Err(type_error("Either 'filters' should be present or 'acceptAllDevices' should be true, \
+                                       but not both.")),
+            None if !self.accept_all_devices =>
+                return Err(type_error("Either 'filters' should be present or 'acceptAllDevices' should be true, \
+                                       but not both.")),
+            Some(ref filters) if filters.is_empty() =>
+                return Err(type_error("'filters' member, if present, must be nonempty to find any devices.")),
+            _ => (),
+        }
+        if let Some(ref exclusion_filters) = self.exclusion_filters {
+            if self.accept_all_devices {
+                return Err(type_error("'exclusionFilters' can't be used with 'acceptAllDevices'."));
+            }
+            if exclusion_filters.is_empty() {
+                return Err(type_error("'exclusionFilters' member, if present, must be nonempty."));
+            }
+        }
+        for filter in self.filters.iter().chain(self.exclusion_filters.iter()).flatten() {
+            filter.validate()?;
+        }
+        Ok(())
+    }
+
+    // The services of every filter and the optional services. With `accept_all_devices` only the
+    // optional services.
+    pub fn get_allowed_services(&self) -> BTreeSet<BluetoothUuid> {
+        let mut allowed_services: BTreeSet<BluetoothUuid> = self.optional_services.iter().cloned().collect();
+        for filter in self.filters.iter().flatten() {
+            allowed_services.extend(filter.services.iter().flatten().cloned());
+        }
+        allowed_services
+    }
+
+    pub fn get_allowed_manufacturer_data(&self) -> BTreeSet<u16> {
+        let mut allowed_manufacturer_data: BTreeSet<u16> = self.optional_manufacturer_data.iter().cloned().collect();
+        for filter in self.filters.iter().flatten() {
+            allowed_manufacturer_data.extend(filter.manufacturer_data.iter().flatten()
+                                                   .map(|filter| filter.company_identifier));
+        }
+        allowed_manufacturer_data
+    }
+
+    // A device matches, if it matches any of the filters, and none of the exclusion filters.
+    pub fn match_device(&self, device: &BluetoothDevice) -> Result<FilterResult, BluetoothError> {
+        self.validate()?;
+        let data = DeviceData::read(device);
+        let matched = match self.filters {
+            Some(ref filters) => {
+                filters.iter().any(|filter| filter.matches(&data)) &&
+                !self.exclusion_filters.iter().flatten().any(|filter| filter.matches(&data))
+            },
+            None => self.accept_all_devices,
+        };
+        Ok(FilterResult {
+            matched,
+            allowed_services: self.get_allowed_services(),
+            allowed_manufacturer_data: self.get_allowed_manufacturer_data(),
+        })
+    }
+
+    pub fn get_matching_devices(&self, adapter: &BluetoothAdapter) -> Result<Vec<BluetoothDevice>, BluetoothError> {
+        let mut devices = vec![];
+        for device in adapter.get_devices()? {
+            if self.match_device(&device)?.matched {
+                devices.push(device);
+            }
+        }
+        Ok(devices)
+    }
+}
@@ -0,0 +1,729 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+// Mock adapters described by a JSON document, e.g.
+// {"id": "hci0", "powered": true, "devices": [{"address": "09:09:09:09:09:09", "name": "Heart Rate",
+//  "services": [{"uuid": "180d", "characteristics": [{"uuid": "2a37", "flags": ["notify"], "value": [0, 60]}]}]}]}
+// `load_adapter` builds the tree with `BluetoothAdapter::init_mock` and the mock setters, and
+// `dump_adapter` writes a mock adapter back in the same format, blocklisted attributes included.
+// Children without an id get one made up from the id of their parent, like the object paths of BlueZ.
+
+use address::BluetoothAddress;
+use bluetooth::{BluetoothAdapter, BluetoothDevice, BluetoothGATTCharacteristic};
+use bluetooth::{BluetoothGATTDescriptor, BluetoothGATTService};
+use error::BluetoothError;
+use std::collections::{BTreeMap, HashMap};
+use std::convert::TryFrom;
+use std::hash::Hash;
+use uuid::BluetoothUuid;
+
+const ADAPTER_KEYS: &[&str] = &["id", "address", "name", "alias", "class", "powered", "present", "discoverable",
+                                "pairable", "pairable_timeout", "discoverable_timeout", "discovering", "uuids",
+                                "ad_datas", "devices"];
+const DEVICE_KEYS: &[&str] = &["id", "address", "name", "alias", "icon", "class", "appearance", "uuids", "paired",
+                               "connected", "connectable", "trusted", "blocked", "legacy_pairing", "rssi", "tx_power",
+                               "manufacturer_data", "service_data", "services"];
+const SERVICE_KEYS: &[&str] = &["id", "uuid", "primary", "characteristics"];
+const CHARACTERISTIC_KEYS: &[&str] = &["id", "uuid", "flags", "value", "notifying", "descriptors"];
+const DESCRIPTOR_KEYS: &[&str] = &["id", "uuid", "flags", "value"];
+
+#[derive(Clone, Debug, PartialEq)]
+enum Json {
+    Null,
+    Bool(bool),
+    // Fixtures have no use for fractions.
+    Integer(i64),
+    String(String),
+    Array(Vec<Json>),
+    // The fields in the order of the document.
+    Object(Vec<(String, Json)>),
+}
+
+fn invalid(message: String) -> BluetoothError {
+    BluetoothError::InvalidArgument(format!("fixture: {}", message))
+}
+
+struct Parser<'a> {
+    text: &'a str,
+    position: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn error(&self, message: &str) -> BluetoothError {
+        invalid(format!("{} at byte {}", message, self.position))
+    }
+
+    fn peek(&self) -> Option<u8> {
+        self.text.as_bytes().get(self.position).cloned()
+    }
+
+    fn skip_whitespace(&mut self) {
+        while matches!(self.peek(), Some(b' ') | Some(b'\t') | Some(b'\n') | Some(b'\r')) {
+            self.position += 1;
+        }
+    }
+
+    fn expect(&mut self, byte: u8) -> Result<(), BluetoothError> {
+        self.skip_whitespace();
+        if self.peek() != Some(byte) {
+            return Err(self.error(&format!("expected '{}'", byte as char)));
+        }
+        self.position += 1;
+        Ok(())
+    }
+
+    fn parse_value(&mut self) -> Result<Json, BluetoothError> {
+        self.skip_whitespace();
+        match self.peek() {
+            Some(b'{') => self.parse_object(),
+            Some(b'[') => self.parse_array(),
+            Some(b'"') => self.parse_string().map(Json::String),
+            Some(b'-') | Some(b'0'..=b'9') => self.parse_integer(),
+            Some(b't') => self.parse_literal("true", Json::Bool(true)),
+            Some(b'f') => self.parse_literal("false", Json::Bool(false)),
+            Some(b'n') => self.parse_literal("null", Json::Null),
+            _ => Err(self.error("expected a value")),
+        }
+    }
+
+    fn parse_literal(&mut self, literal: &str, value: Json) -> Result<Json, BluetoothError> {
+        if !self.text[self.position..].starts_with(literal) {
+            return Err(self.error("expected a value"));
+        }
+        self.position += literal.len();
+        Ok(value)
+    }
+
+    fn parse_integer(&mut self) -> Result<Json, BluetoothError> {
+        let start = self.position;
+        if self.peek() == Some(b'-') {
+            self.position += 1;
+        }
+        let digits = self.position;
+        while matches!(self.peek(), Some(b'0'..=b'9')) {
+            self.position += 1;
+        }
+        // JSON has no leading zeros.
+        if self.text[digits..self.position].starts_with('0') && self.position - digits > 1 {
+            return Err(self.error("expected an integer"));
+        }
+        if matches!(self.peek(), Some(b'.') | Some(b'e') | Some(b'E')) {
+            return Err(self.error("expected an integer"));
+        }
+        self.text[start..self.position].parse().map(Json::Integer).map_err(|_| self.error("expected an integer"))
+    }
+
+    fn parse_string(&mut self) -> Result<String, BluetoothError> {
+        self.expect(b'"')?;
+        let mut string = String::new();
+        loop {
+            // Only stops at ASCII bytes, so the slices end at character boundaries.
+            let start = self.position;
+            while let Some(byte) = self.peek() {
+                if byte == b'"' || byte == b'\\' || byte < 0x20 {
+                    break;
+                }
+                self.position += 1;
+            }
+            string.push_str(&self.text[start..self.position]);
+            match self.peek() {
+                Some(b'"') => {
+                    self.position += 1;
+                    return Ok(string);
+                },
+                Some(b'\\') => {
+                    self.position += 1;
+                    let character = self.parse_escape()?;
+                    string.push(character);
+                },
+                _ => return Err(self.error("unterminated string")),
+            }
+        }
+    }
+
+    fn parse_escape(&mut self) -> Result<char, BluetoothError> {
+        let byte = self.peek().ok_or_else(|| self.error("unterminated string"))?;
+        self.position += 1;
+        let character = match byte {
+            b'"' => '"',
+            b'\\' => '\\',
+            b'/' => '/',
+            b'b' => '\u{8}',
+            b'f' => '\u{c}',
+            b'n' => '\n',
+            b'r' => '\r',
+            b't' => '\t',
+            b'u' => {
+                let mut code = self.parse_hex_escape()?;
+                // A character outside of the Basic Multilingual Plane is escaped as a surrogate pair.
+                if (0xD800..0xDC00).contains(&code) && self.text[self.position..].starts_with("\\u") {
+                    self.position += 2;
+                    let low = self.parse_hex_escape()?;
+                    if !(0xDC00..0xE000).contains(&low) {
+                        return Err(self.error("invalid escape"));
+                    }
+                    code = 0x10000 + ((code - 0xD800) << 10) + (low - 0xDC00);
+                }
+                return std::char::from_u32(code).ok_or_else(|| self.error("invalid escape"));
+            },
+            _ => return Err(self.error("invalid escape")),
+        };
+        Ok(character)
+    }
+
+    fn parse_hex_escape(&mut self) -> Result<u32, BluetoothError> {
+        let code = self.text.get(self.position..self.position + 4)
+                            .filter(|digits| digits.bytes().all(|byte| byte.is_ascii_hexdigit()))
+                            .and_then(|digits| u32::from_str_radix(digits, 16).ok())
+                            .ok_or_else(|| self.error("invalid escape"))?;
+        self.position += 4;
+        Ok(code)
+    }
+
+    fn parse_array(&mut self) -> Result<Json, BluetoothError> {
+        self.expect(b'[')?;
+        let mut values = vec![];
+        self.skip_whitespace();
+        if self.peek() == Some(b']') {
+            self.position += 1;
+            return Ok(Json::Array(values));
+        }
+        loop {
+            values.push(self.parse_value()?);
+            self.skip_whitespace();
+            match self.peek() {
+                Some(b',') => self.position += 1,
+                Some(b']') => {
+                    self.position += 1;
+                    return Ok(Json::Array(values));
+                },
+                _ => return Err(self.error("expected ',' or ']'")),
+            }
+        }
+    }
+
+    fn parse_object(&mut self) -> Result<Json, BluetoothError> {
+        self.expect(b'{')?;
+        let mut fields: Vec<(String, Json)> = vec![];
+        self.skip_whitespace();
+        if self.peek() == Some(b'}') {
+            self.position += 1;
+            return Ok(Json::Object(fields));
+        }
+        loop {
+            self.skip_whitespace();
+            let key = self.parse_string()?;
+            if fields.iter().any(|(existing, _)| *existing == key) {
+                return Err(self.error(&format!("duplicate field '{}'", key)));
+            }
+            self.expect(b':')?;
+            let value = self.parse_value()?;
+            fields.push((key, value));
+            self.skip_whitespace();
+            match self.peek() {
+                Some(b',') => self.position += 1,
+                Some(b'}') => {
+                    self.position += 1;
+                    return Ok(Json::Object(fields));
+                },
+                _ => return Err(self.error("expected ',' or '}'")),
+            }
+        }
+    }
+}
+
+fn parse(text: &str) -> Result<Json, BluetoothError> {
+    let mut parser = Parser {
+        text,
+        position: 0,
+    };
+    let value = parser.parse_value()?;
+    parser.skip_whitespace();
+    if parser.position != text.len() {
+        return Err(parser.error("unexpected characters after the document"));
+    }
+    Ok(value)
+}
+
+fn is_scalar(value: &Json) -> bool {
+    !matches!(*value, Json::Array(_) | Json::Object(_))
+}
+
+fn write_string(string: &str, output: &mut String) {
+    output.push('"');
+    for character in string.chars() {
+        match character {
+            '"' => output.push_str("\\\""),
+            '\\' => output.push_str("\\\\"),
+            '\n' => output.push_str("\\n"),
+            '\r' => output.push_str("\\r"),
+            '\t' => output.push_str("\\t"),
+            character if (character as u32) < 0x20 => output.push_str(&format!("\\u{:04x}", character as u32)),
+            character => output.push(character),
+        }
+    }
+    output.push('"');
+}
+
+// Arrays of scalars, e.g. values and flags, stay on one line.
+fn write_json(value: &Json, indent: usize, output: &mut String) {
+    let inner_indent = " ".repeat(indent + 2);
+    match *value {
+        Json::Null => output.push_str("null"),
+        Json::Bool(value) => output.push_str(if value { "true" } else { "false" }),
+        Json::Integer(value) => output.push_str(&value.to_string()),
+        Json::String(ref value) => write_string(value, output),
+        Json::Array(ref values) if values.iter().all(is_scalar) => {
+            output.push('[');
+            for (index, value) in values.iter().enumerate() {
+                if index > 0 {
+                    output.push_str(", ");
+                }
+                write_json(value, indent, output);
+            }
+            output.push(']');
+        },
+        Json::Array(ref values) => {
+            output.push_str("[\n");
+            for (index, value) in values.iter().enumerate() {
+                output.push_str(&inner_indent);
+                write_json(value, indent + 2, output);
+                output.push_str(if index + 1 < values.len() { ",\n" } else { "\n" });
+            }
+            output.push_str(&" ".repeat(indent));
+            output.push(']');
+        },
+        Json::Object(ref fields) if fields.is_empty() => output.push_str("{}"),
+        Json::Object(ref fields) => {
+            output.push_str("{\n");
+            for (index, (key, value)) in fields.iter().enumerate() {
+                output.push_str(&inner_indent);
+                write_string(key, output);
+                output.push_str(": ");
+                write_json(value, indent + 2, output);
+                output.push_str(if index + 1 < fields.len() { ",\n" } else { "\n" });
+            }
+            output.push_str(&" ".repeat(indent));
+            output.push('}');
+        },
+    }
+}
+
+fn as_bool(value: &Json) -> Option<bool> {
+    match *value {
+        Json::Bool(value) => Some(value),
+        _ => None,
+    }
+}
+
+fn as_string(value: &Json) -> Option<String> {
+    match *value {
+        Json::String(ref value) => Some(value.clone()),
+        _ => None,
+    }
+}
+
+fn as_optional_string(value: &Json) -> Option<Option<String>> {
+    match *value {
+        Json::Null => Some(None),
+        _ => as_string(value).map(Some),
+    }
+}
+
+fn as_integer<T: TryFrom<i64>>(value: &Json) -> Option<T> {
+    match *value {
+        Json::Integer(value) => T::try_from(value).ok(),
+        _ => None,
+    }
+}
+
+fn as_array<T>(value: &Json, convert: fn(&Json) -> Option<T>) -> Option<Vec<T>> {
+    match *value {
+        Json::Array(ref values) => values.iter().map(convert).collect(),
+        _ => None,
+    }
+}
+
+fn as_bytes(value: &Json) -> Option<Vec<u8>> {
+    as_array(value, as_integer)
+}
+
+fn as_strings(value: &Json) -> Option<Vec<String>> {
+    as_array(value, as_string)
+}
+
+fn as_uuid(value: &Json) -> Option<BluetoothUuid> {
+    as_string(value).and_then(|uuid| BluetoothUuid::parse(&uuid).ok())
+}
+
+fn as_uuids(value: &Json) -> Option<Vec<BluetoothUuid>> {
+    as_array(value, as_uuid)
+}
+
+fn as_address(value: &Json) -> Option<BluetoothAddress> {
+    as_string(value).and_then(|address| BluetoothAddress::parse(&address).ok())
+}
+
+// An object of byte arrays, e.g. manufacturer data by company identifier.
+fn as_byte_map<K: Eq + Hash>(value: &Json, parse_key: fn(&str) -> Option<K>) -> Option<HashMap<K, Vec<u8>>> {
+    match *value {
+        Json::Object(ref fields) => fields.iter().map(|(key, data)| Some((parse_key(key)?, as_bytes(data)?))).collect(),
+        _ => None,
+    }
+}
+
+// Decimal, or hexadecimal with a "0x" prefix, as written by `dump_adapter`.
+fn parse_company_identifier(key: &str) -> Option<u16> {
+    if key.starts_with("0x") || key.starts_with("0X") {
+        u16::from_str_radix(&key[2..], 16).ok()
+    } else {
+        key.parse().ok()
+    }
+}
+
+// The fields of an object of the fixture, checked against the keys known at its level.
+struct Fields<'a> {
+    path: String,
+    fields: &'a [(String, Json)],
+}
+
+impl<'a> Fields<'a> {
+    fn new(value: &'a Json, path: String, keys: &[&str]) -> Result<Fields<'a>, BluetoothError> {
+        let fields = match *value {
+            Json::Object(ref fields) => fields,
+            _ => return Err(invalid(format!("{}: expected an object", path))),
+        };
+        if let Some((key, _)) = fields.iter().find(|(key, _)| !keys.contains(&key.as_str())) {
+            return Err(invalid(format!("{}: unknown field '{}'", path, key)));
+        }
+        Ok(Fields {
+            path,
+            fields,
+        })
+    }
+
+    fn get_as<T>(&self, key: &str, expected: &str, convert: fn(&Json) -> Option<T>) -> Result<Option<T>, BluetoothError> {
+        match self.fields.iter().find(|(field, _)| field == key) {
+            Some((_, value)) => {
+                let value = convert(value).ok_or_else(|| invalid(format!("{}.{}: expected {}", self.path, key, expected)))?;
+                Ok(Some(value))
+            },
+            None => Ok(None),
+        }
+    }
+
+    fn boolean(&self, key: &str) -> Result<Option<bool>, BluetoothError> {
+        self.get_as(key, "a boolean", as_bool)
+    }
+
+    fn string(&self, key: &str) -> Result<Option<String>, BluetoothError> {
+        self.get_as(key, "a string", as_string)
+    }
+
+    fn integer<T: TryFrom<i64>>(&self, key: &str) -> Result<Option<T>, BluetoothError> {
+        self.get_as(key, "an integer in range", as_integer)
+    }
+
+    fn bytes(&self, key: &str) -> Result<Option<Vec<u8>>, BluetoothError> {
+        self.get_as(key, "an array of bytes", as_bytes)
+    }
+
+    fn strings(&self, key: &str) -> Result<Option<Vec<String>>, BluetoothError> {
+        self.get_as(key, "an array of strings", as_strings)
+    }
+
+    fn uuid(&self, key: &str) -> Result<Option<BluetoothUuid>, BluetoothError> {
+        self.get_as(key, "a UUID", as_uuid)
+    }
+
+    fn uuids(&self, key: &str) -> Result<Option<Vec<BluetoothUuid>>, BluetoothError> {
+        self.get_as(key, "an array of UUIDs", as_uuids)
+    }
+
+    fn address(&self, key: &str) -> Result<Option<BluetoothAddress>, BluetoothError> {
+        self.get_as(key, "a Bluetooth address", as_address)
+    }
+
+    fn objects(&self, key: &str, keys: &[&str]) -> Result<Vec<Fields<'a>>, BluetoothError> {
+        let values = match self.fields.iter().find(|(field, _)| field == key) {
+            Some((_, Json::Array(values))) => values,
+            Some(_) => return Err(invalid(format!("{}.{}: expected an array of objects", self.path, key))),
+            None => return Ok(vec![]),
+        };
+        values.iter()
+              .enumerate()
+              .map(|(index, value)| Fields::new(value, format!("{}.{}[{}]", self.path, key, index), keys))
+              .collect()
+    }
+}
+
+// Calls the setter with the field, if the fixture has it.
+macro_rules! set_field {
+    ($fields:expr, $key:expr, $getter:ident, $target:expr, $setter:ident) => {
+        if let Some(value) = $fields.$getter($key)? {
+            $target.$setter(value)?;
+        }
+    };
+}
+
+pub fn load_adapter(json: &str) -> Result<BluetoothAdapter, BluetoothError> {
+    let document = parse(json)?;
+    let fields = Fields::new(&document, String::from("adapter"), ADAPTER_KEYS)?;
+    let adapter = BluetoothAdapter::init_mock()?;
+    if let Some(id) = fields.string("id")? {
+        adapter.set_id(id);
+    }
+    set_field!(fields, "address", address, adapter, set_address);
+    set_field!(fields, "name", string, adapter, set_name);
+    set_field!(fields, "alias", string, adapter, set_alias);
+    set_field!(fields, "class", integer, adapter, set_class);
+    set_field!(fields, "powered", boolean, adapter, set_powered);
+    set_field!(fields, "present", boolean, adapter, set_present);
+    set_field!(fields, "discoverable", boolean, adapter, set_discoverable);
+    set_field!(fields, "pairable", boolean, adapter, set_pairable);
+    set_field!(fields, "pairable_timeout", integer, adapter, set_pairable_timeout);
+    set_field!(fields, "discoverable_timeout", integer, adapter, set_discoverable_timeout);
+    set_field!(fields, "discovering", boolean, adapter, set_discovering);
+    set_field!(fields, "uuids", uuids, adapter, set_uuids);
+    set_field!(fields, "ad_datas", strings, adapter, set_ad_datas);
+    for (index, device) in fields.objects("devices", DEVICE_KEYS)?.iter().enumerate() {
+        load_device(&adapter, device, index)?;
+    }
+    Ok(adapter)
+}
+
+fn load_device(adapter: &BluetoothAdapter, fields: &Fields, index: usize) -> Result<(), BluetoothError> {
+    let id = fields.string("id")?.unwrap_or_else(|| format!("{}/dev{:04x}", adapter.get_id(), index));
+    let device = BluetoothDevice::create_mock_device(adapter.clone(), id)?;
+    set_field!(fields, "address", address, device, set_address);
+    // A null name is a device without a name.
+    if let Some(name) = fields.get_as("name", "a string or null", as_optional_string)? {
+        device.set_name(name)?;
+    }
+    set_field!(fields, "alias", string, device, set_alias);
+    set_field!(fields, "icon", string, device, set_icon);
+    set_field!(fields, "class", integer, device, set_class);
+    set_field!(fields, "appearance", integer, device, set_appearance);
+    set_field!(fields, "uuids", uuids, device, set_uuids);
+    set_field!(fields, "paired", boolean, device, set_paired);
+    set_field!(fields, "connected", boolean, device, set_connected);
+    set_field!(fields, "connectable", boolean, device, set_connectable);
+    set_field!(fields, "trusted", boolean, device, set_trusted);
+    set_field!(fields, "blocked", boolean, device, set_blocked);
+    set_field!(fields, "legacy_pairing", boolean, device, set_legacy_pairing);
+    set_field!(fields, "rssi", integer, device, set_rssi);
+    set_field!(fields, "tx_power", integer, device, set_tx_power);
+    let manufacturer_data = fields.get_as("manufacturer_data",
+                                          "an object of byte arrays by company identifier",
+                                          |value| as_byte_map(value, parse_company_identifier))?;
+    if let Some(manufacturer_data) = manufacturer_data {
+        device.set_manufacturer_data(manufacturer_data)?;
+    }
+    let service_data = fields.get_as("service_data",
+                                     "an object of byte arrays by UUID",
+                                     |value| as_byte_map(value, |uuid| BluetoothUuid::parse(uuid).ok()))?;
+    if let Some(service_data) = service_data {
+        device.set_service_data(service_data)?;
+    }
+    for (index, service) in fields.objects("services", SERVICE_KEYS)?.iter().enumerate() {
+        load_service(&device, service, index)?;
+    }
+    Ok(())
+}
+
+fn load_service(device: &BluetoothDevice, fields: &Fields, index: usize) -> Result<(), BluetoothError> {
+    let id = fields.string("id")?.unwrap_or_else(|| format!("{}/service{:04x}", device.get_id(), index));
+    let service = BluetoothGATTService::create_mock_service(device.clone(), id)?;
+    set_field!(fields, "uuid", uuid, service, set_uuid);
+    set_field!(fields, "primary", boolean, service, set_primary);
+    for (index, characteristic) in fields.objects("characteristics", CHARACTERISTIC_KEYS)?.iter().enumerate() {
+        load_characteristic(&service, characteristic, index)?;
+    }
+    Ok(())
+}
+
+fn load_characteristic(service: &BluetoothGATTService, fields: &Fields, index: usize) -> Result<(), BluetoothError> {
+    let id = fields.string("id")?.unwrap_or_else(|| format!("{}/char{:04x}", service.get_id(), index));
+    let characteristic = BluetoothGATTCharacteristic::create_mock_characteristic(service.clone(), id)?;
+    set_field!(fields, "uuid", uuid, characteristic, set_uuid);
+    set_field!(fields, "flags", strings, characteristic, set_flags);
+    set_field!(fields, "value", bytes, characteristic, set_value);
+    set_field!(fields, "notifying", boolean, characteristic, set_notifying);
+    for (index, descriptor) in fields.objects("descriptors", DESCRIPTOR_KEYS)?.iter().enumerate() {
+        load_descriptor(&characteristic, descriptor, index)?;
+    }
+    Ok(())
+}
+
+fn load_descriptor(characteristic: &BluetoothGATTCharacteristic,
+                   fields: &Fields,
+                   index: usize)
+                   -> Result<(), BluetoothError> {
+    let id = fields.string("id")?.unwrap_or_else(|| format!("{}/desc{:04x}", characteristic.get_id(), index));
+    let descriptor = BluetoothGATTDescriptor::create_mock_descriptor(characteristic.clone(), id)?;
+    set_field!(fields, "uuid", uuid, descriptor, set_uuid);
+    set_field!(fields, "flags", strings, descriptor, set_flags);
+    set_field!(fields, "value", bytes, descriptor, set_value);
+    Ok(())
+}
+
+impl From<bool> for Json {
+    fn from(value: bool) -> Json {
+        Json::Bool(value)
+    }
+}
+
+impl From<String> for Json {
+    fn from(value: String) -> Json {
+        Json::String(value)
+    }
+}
+
+impl From<u32> for Json {
+    fn from(value: u32) -> Json {
+        Json::Integer(value.into())
+    }
+}
+
+impl From<u16> for Json {
+    fn from(value: u16) -> Json {
+        Json::Integer(value.into())
+    }
+}
+
+impl From<i16> for Json {
+    fn from(value: i16) -> Json {
+        Json::Integer(value.into())
+    }
+}
+
+impl From<BluetoothAddress> for Json {
+    fn from(value: BluetoothAddress) -> Json {
+        Json::String(value.to_string())
+    }
+}
+
+// UUIDs derived from the Bluetooth base UUID are written as their 16-bit alias.
+impl From<BluetoothUuid> for Json {
+    fn from(value: BluetoothUuid) -> Json {
+        match value.to_u16() {
+            Some(alias) => Json::String(format!("{:04x}", alias)),
+            None => Json::String(value.to_string()),
+        }
+    }
+}
+
+impl<T: Into<Json>> From<Vec<T>> for Json {
+    fn from(values: Vec<T>) -> Json {
+        Json::Array(values.into_iter().map(Into::into).collect())
+    }
+}
+
+fn bytes_to_json(bytes: Vec<u8>) -> Json {
+    Json::Array(bytes.into_iter().map(|byte| Json::Integer(byte.into())).collect())
+}
+
+// Properties the mock does not have, e.g. the RSSI of a device it was never set on, are left out.
+fn insert<T: Into<Json>>(fields: &mut Vec<(String, Json)>, key: &str, value: Result<T, BluetoothError>) {
+    if let Ok(value) = value {
+        fields.push((key.to_owned(), value.into()));
+    }
+}
+
+pub fn dump_adapter(adapter: &BluetoothAdapter) -> Result<String, BluetoothError> {
+    if !adapter.is_mock() {
+        return Err(BluetoothError::NotSupported);
+    }
+    let mut fields = vec![(String::from("id"), Json::String(adapter.get_id()))];
+    insert(&mut fields, "address", adapter.get_address());
+    insert(&mut fields, "name", adapter.get_name());
+    insert(&mut fields, "alias", adapter.get_alias());
+    insert(&mut fields, "class", adapter.get_class());
+    insert(&mut fields, "powered", adapter.is_powered());
+    insert(&mut fields, "present", adapter.is_present());
+    insert(&mut fields, "discoverable", adapter.is_discoverable());
+    insert(&mut fields, "pairable", adapter.is_pairable());
+    insert(&mut fields, "pairable_timeout", adapter.get_pairable_timeout());
+    insert(&mut fields, "discoverable_timeout", adapter.get_discoverable_timeout());
+    insert(&mut fields, "discovering", adapter.is_discovering());
+    insert(&mut fields, "uuids", adapter.get_uuids());
+    insert(&mut fields, "ad_datas", adapter.get_ad_datas());
+    let devices = adapter.get_devices()?.iter().map(dump_device).collect::<Result<Vec<Json>, BluetoothError>>()?;
+    fields.push((String::from("devices"), Json::Array(devices)));
+    let mut output = String::new();
+    write_json(&Json::Object(fields), 0, &mut output);
+    output.push('\n');
+    Ok(output)
+}
+
+fn dump_device(device: &BluetoothDevice) -> Result<Json, BluetoothError> {
+    let mut fields = vec![(String::from("id"), Json::String(device.get_id()))];
+    insert(&mut fields, "address", device.get_address());
+    insert(&mut fields, "name", device.get_name());
+    insert(&mut fields, "alias", device.get_alias());
+    insert(&mut fields, "icon", device.get_icon());
+    insert(&mut fields, "class", device.get_class());
+    insert(&mut fields, "appearance", device.get_appearance());
+    insert(&mut fields, "uuids", device.get_uuids());
+    insert(&mut fields, "paired", device.is_paired());
+    insert(&mut fields, "connected", device.is_connected());
+    insert(&mut fields, "connectable", device.is_connectable());
+    insert(&mut fields, "trusted", device.is_trusted());
+    insert(&mut fields, "blocked", device.is_blocked());
+    insert(&mut fields, "legacy_pairing", device.is_legacy_pairing());
+    insert(&mut fields, "rssi", device.get_rssi());
+    insert(&mut fields, "tx_power", device.get_tx_power());
+    if let Ok(manufacturer_data) = device.get_manufacturer_data() {
+        let manufacturer_data: BTreeMap<u16, Vec<u8>> = manufacturer_data.into_iter().collect();
+        let manufacturer_data = manufacturer_data.into_iter()
+                                                 .map(|(id, data)| (format!("0x{:04X}", id), bytes_to_json(data)));
+        fields.push((String::from("manufacturer_data"), Json::Object(manufacturer_data.collect())));
+    }
+    if let Ok(service_data) = device.get_service_data() {
+        let service_data: BTreeMap<BluetoothUuid, Vec<u8>> = service_data.into_iter().collect();
+        let service_data = service_data.into_iter().map(|(uuid, data)| (uuid.to_string(), bytes_to_json(data)));
+        fields.push((String::from("service_data"), Json::Object(service_data.collect())));
+    }
+    let services = device.get_mock_services()?.iter().map(dump_service).collect::<Result<Vec<Json>, BluetoothError>>()?;
+    fields.push((String::from("services"), Json::Array(services)));
+    Ok(Json::Object(fields))
+}
+
+fn dump_service(service: &BluetoothGATTService) -> Result<Json, BluetoothError> {
+    let mut fields = vec![(String::from("id"), Json::String(service.get_id()))];
+    insert(&mut fields, "uuid", service.get_uuid());
+    insert(&mut fields, "primary", service.is_primary());
+    let characteristics = service.get_all_gatt_characteristics()?
+                                 .iter()
+                                 .map(dump_characteristic)
+                                 .collect::<Result<Vec<Json>, BluetoothError>>()?;
+    fields.push((String::from("characteristics"), Json::Array(characteristics)));
+    Ok(Json::Object(fields))
+}
+
+fn dump_characteristic(characteristic: &BluetoothGATTCharacteristic) -> Result<Json, BluetoothError> {
+    let mut fields = vec![(String::from("id"), Json::String(characteristic.get_id()))];
+    insert(&mut fields, "uuid", characteristic.get_uuid());
+    insert(&mut fields, "flags", characteristic.get_flags());
+    if let Ok(value) = characteristic.get_value() {
+        fields.push((String::from("value"), bytes_to_json(value)));
+    }
+    insert(&mut fields, "notifying", characteristic.is_notifying());
+    let descriptors = characteristic.get_all_gatt_descriptors()?
+                                    .iter()
+                                    .map(dump_descriptor)
+                                    .collect::<Result<Vec<Json>, BluetoothError>>()?;
+    fields.push((String::from("descriptors"), Json::Array(descriptors)));
+    Ok(Json::Object(fields))
+}
+
+fn dump_descriptor(descriptor: &BluetoothGATTDescriptor) -> Result<Json, BluetoothError> {
+    let mut fields = vec![(String::from("id"), Json::String(descriptor.get_id()))];
+    insert(&mut fields, "uuid", descriptor.get_uuid());
+    insert(&mut fields, "flags", descriptor.get_flags());
+    if let Ok(value) = descriptor.get_value() {
+        fields.push((String::from("value"), bytes_to_json(value)));
+    }
+    Ok(Json::Object(fields))
+}
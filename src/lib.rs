@@ -4,11 +4,35 @@
 
 #[cfg(all(target_os = "linux", feature = "bluetooth"))]
 extern crate blurz;
+#[cfg(all(target_os = "linux", feature = "bluetooth"))]
+extern crate dbus;
 #[cfg(all(target_os = "android", feature = "bluetooth"))]
 extern crate blurdroid;
 #[cfg(feature = "bluetooth-test")]
 extern crate blurmock;
 
+pub mod address;
+pub mod advertisement;
+pub mod assigned_numbers;
+pub mod async_bluetooth;
+pub mod backend;
+pub mod blocklist;
 pub mod bluetooth;
+pub mod connection;
+pub mod discovery_filter;
+pub mod error;
+pub mod events;
+pub mod filter;
+#[cfg(feature = "bluetooth-test")]
+pub mod fixture;
+pub mod manager;
+pub mod pairing;
+#[cfg(feature = "bluetooth-test")]
+pub mod preset;
+pub mod properties;
+pub mod uuid;
 #[cfg(not(any(all(target_os = "linux", feature = "bluetooth"), all(target_os = "android", feature = "bluetooth"))))]
 mod empty;
+#[cfg(feature = "bluetooth-test")]
+mod mock;
+mod operation_queue;
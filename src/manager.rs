@@ -0,0 +1,138 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+// Holds adapters of any backend side by side, e.g. the adapter of the system and mock adapters,
+// by their id. One of them is the default adapter, which a test runner can switch to a mock
+// adapter for a test, and back to the real one afterwards.
+
+#[cfg(feature = "bluetooth-test")]
+use address::BluetoothAddress;
+use bluetooth::BluetoothAdapter;
+use error::BluetoothError;
+use std::collections::BTreeMap;
+use std::mem;
+use std::sync::{Mutex, MutexGuard};
+
+#[derive(Debug, Default)]
+pub struct AdapterManager {
+    state: Mutex<ManagerState>,
+}
+
+#[derive(Debug, Default)]
+struct ManagerState {
+    adapters: BTreeMap<String, BluetoothAdapter>,
+    default_id: Option<String>,
+}
+
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    match mutex.lock() {
+        Ok(guard) => guard,
+        Err(poisoned) => poisoned.into_inner(),
+    }
+}
+
+impl AdapterManager {
+    pub fn new() -> AdapterManager {
+        AdapterManager::default()
+    }
+
+    // Adds the adapter by its current id, and returns the id. The first adapter becomes the
+    // default one. Fails with `InvalidState`, if an adapter with the same id was added already.
+    pub fn add_adapter(&self, adapter: BluetoothAdapter) -> Result<String, BluetoothError> {
+        let id = adapter.get_id();
+        let mut state = lock(&self.state);
+        if state.adapters.contains_key(&id) {
+            return Err(BluetoothError::InvalidState);
+        }
+        state.adapters.insert(id.clone(), adapter);
+        if state.default_id.is_none() {
+            state.default_id = Some(id.clone());
+        }
+        Ok(id)
+    }
+
+    // Creates a mock adapter with the id, and adds it.
+    #[cfg(feature = "bluetooth-test")]
+    pub fn add_mock_adapter(&self, id: String) -> Result<BluetoothAdapter, BluetoothError> {
+        let adapter = BluetoothAdapter::init_mock()?;
+        adapter.set_id(id);
+        self.add_adapter(adapter.clone())?;
+        Ok(adapter)
+    }
+
+    // The mock adapters among the added ones, by their id, like `BluetoothAdapter::list` lists
+    // the adapters of the system.
+    #[cfg(feature = "bluetooth-test")]
+    pub fn list_mock(&self) -> Vec<BluetoothAdapter> {
+        self.get_adapters().into_iter().filter(BluetoothAdapter::is_mock).collect()
+    }
+
+    #[cfg(feature = "bluetooth-test")]
+    pub fn init_mock_with_id(&self, id: &str) -> Result<BluetoothAdapter, BluetoothError> {
+        self.list_mock().into_iter().find(|adapter| adapter.get_id() == id).ok_or(BluetoothError::NotFound)
+    }
+
+    #[cfg(feature = "bluetooth-test")]
+    pub fn init_mock_with_address(&self, address: BluetoothAddress) -> Result<BluetoothAdapter, BluetoothError> {
+        self.list_mock()
+            .into_iter()
+            .find(|adapter| adapter.get_address().as_ref() == Ok(&address))
+            .ok_or(BluetoothError::NotFound)
+    }
+
+    // Removing the default adapter leaves the manager without one.
+    pub fn remove_adapter(&self, id: &str) -> Result<BluetoothAdapter, BluetoothError> {
+        let mut state = lock(&self.state);
+        let adapter = state.adapters.remove(id).ok_or(BluetoothError::NotFound)?;
+        if state.default_id.as_ref().is_some_and(|default_id| default_id == id) {
+            state.default_id = None;
+        }
+        Ok(adapter)
+    }
+
+    pub fn get_adapter(&self, id: &str) -> Option<BluetoothAdapter> {
+        lock(&self.state).adapters.get(id).cloned()
+    }
+
+    pub fn get_adapters(&self) -> Vec<BluetoothAdapter> {
+        lock(&self.state).adapters.values().cloned().collect()
+    }
+
+    pub fn get_adapter_ids(&self) -> Vec<String> {
+        lock(&self.state).adapters.keys().cloned().collect()
+    }
+
+    pub fn get_default_adapter(&self) -> Option<BluetoothAdapter> {
+        let state = lock(&self.state);
+        state.default_id.as_ref().and_then(|id| state.adapters.get(id)).cloned()
+    }
+
+    pub fn get_default_adapter_id(&self) -> Option<String> {
+        lock(&self.state).default_id.clone()
+    }
+
+    // Returns the id of the previous default adapter.
+    pub fn set_default_adapter(&self, id: &str) -> Result<Option<String>, BluetoothError> {
+        let mut state = lock(&self.state);
+        if !state.adapters.contains_key(id) {
+            return Err(BluetoothError::NotFound);
+        }
+        Ok(state.default_id.replace(id.to_owned()))
+    }
+
+    // Replaces the adapter with the id by an other one, e.g. a real adapter by a mock one with the
+    // same id, and returns the replaced one. Fails with `InvalidArgument`, if the id of the other
+    // adapter differs.
+    pub fn replace_adapter(&self, id: &str, adapter: BluetoothAdapter) -> Result<BluetoothAdapter, BluetoothError> {
+        let adapter_id = adapter.get_id();
+        if adapter_id != id {
+            return Err(BluetoothError::InvalidArgument(format!("adapter id {} instead of {}", adapter_id, id)));
+        }
+        let mut state = lock(&self.state);
+        match state.adapters.get_mut(id) {
+            Some(current) => Ok(mem::replace(current, adapter)),
+            None => Err(BluetoothError::NotFound),
+        }
+    }
+}
@@ -0,0 +1,150 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+// The behavior of the mock devices which blurmock does not model, the ATT_MTU and the user
+// interaction of pairing, behind the `DeviceBackend` implementation of `FakeBluetoothDevice`.
+
+use blurmock::fake_adapter::FakeBluetoothAdapter;
+use blurmock::fake_device::FakeBluetoothDevice;
+use blurmock::fake_service::FakeBluetoothGATTService;
+use bluetooth::{BluetoothDevice, ATT_DEFAULT_MTU};
+use error::BluetoothError;
+use pairing::{get_association_model, AssociationModel, IoCapability, PairingAgent};
+use std::ptr;
+use std::sync::{Arc, Mutex, MutexGuard, Weak};
+
+// The ATT_MTU fitting the largest attribute value in a single Read Response.
+const ATT_MAX_MTU: u16 = 517;
+
+#[derive(Clone, Debug)]
+pub struct MockDevice {
+    // The ATT_MTU of the connection.
+    pub mtu: u16,
+    // The largest ATT_MTU the remote device accepts in an exchange.
+    pub server_mtu: u16,
+    pub io_capability: IoCapability,
+    // The passkey the mock device displays, or expects.
+    pub passkey: u32,
+    // The PIN code of a legacy pairing.
+    pub pin_code: String,
+    // Whether the mock device rejects the pairing, after the user interaction.
+    pub rejected: bool,
+    // The services created on the mock device, as blurmock only lists them while it is connected.
+    pub services: Vec<Arc<FakeBluetoothGATTService>>,
+}
+
+impl Default for MockDevice {
+    fn default() -> MockDevice {
+        MockDevice {
+            mtu: ATT_DEFAULT_MTU,
+            server_mtu: ATT_MAX_MTU,
+            io_capability: IoCapability::NoInputNoOutput,
+            passkey: 123456,
+            pin_code: String::from("0000"),
+            rejected: false,
+            services: vec![],
+        }
+    }
+}
+
+// The mock devices by their adapter and their id, as blurmock hands out a new object for the
+// same device. The entries of the dropped adapters are removed on the next update.
+static MOCK_DEVICES: Mutex<Vec<(Weak<FakeBluetoothAdapter>, String, MockDevice)>> = Mutex::new(Vec::new());
+
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    match mutex.lock() {
+        Ok(guard) => guard,
+        Err(poisoned) => poisoned.into_inner(),
+    }
+}
+
+fn is_entry_of(entry: &(Weak<FakeBluetoothAdapter>, String, MockDevice), adapter: &Arc<FakeBluetoothAdapter>, id: &str)
+               -> bool {
+    entry.0.as_ptr() == Arc::as_ptr(adapter) && entry.1 == id
+}
+
+pub fn get(device: &FakeBluetoothDevice) -> Result<MockDevice, BluetoothError> {
+    let adapter = device.get_adapter()?;
+    let id = device.get_id();
+    let mock_devices = lock(&MOCK_DEVICES);
+    Ok(mock_devices.iter().find(|entry| is_entry_of(entry, &adapter, &id)).map(|entry| entry.2.clone()).unwrap_or_default())
+}
+
+pub fn update<F: FnOnce(&mut MockDevice)>(device: &FakeBluetoothDevice, update: F) -> Result<MockDevice, BluetoothError> {
+    let adapter = device.get_adapter()?;
+    let id = device.get_id();
+    let mut mock_devices = lock(&MOCK_DEVICES);
+    mock_devices.retain(|entry| entry.0.strong_count() > 0);
+    let index = match mock_devices.iter().position(|entry| is_entry_of(entry, &adapter, &id)) {
+        Some(index) => index,
+        None => {
+            mock_devices.push((Arc::downgrade(&adapter), id, MockDevice::default()));
+            mock_devices.len() - 1
+        },
+    };
+    update(&mut mock_devices[index].2);
+    Ok(mock_devices[index].2.clone())
+}
+
+pub fn remove(adapter: &FakeBluetoothAdapter, id: &str) {
+    lock(&MOCK_DEVICES).retain(|entry| !(ptr::eq(entry.0.as_ptr(), adapter) && entry.1 == id));
+}
+
+// The ATT_MTU is exchanged again on every connection.
+pub fn reset_mtu(device: &FakeBluetoothDevice) -> Result<(), BluetoothError> {
+    update(device, |mock_device| mock_device.mtu = ATT_DEFAULT_MTU).map(|_| ())
+}
+
+pub fn add_service(device: &FakeBluetoothDevice, service: Arc<FakeBluetoothGATTService>) -> Result<(), BluetoothError> {
+    let id = service.get_id();
+    update(device, |mock_device| {
+        mock_device.services.retain(|existing| existing.get_id() != id);
+        mock_device.services.push(service);
+    }).map(|_| ())
+}
+
+// The user interaction of pairing, through the registered agent. Without an agent, only the
+// Just Works association model is possible.
+pub fn pair(fake: &FakeBluetoothDevice,
+            device: &BluetoothDevice,
+            agent: Option<Arc<dyn PairingAgent>>)
+            -> Result<(), BluetoothError> {
+    if fake.is_paired()? {
+        return Err(BluetoothError::InvalidState);
+    }
+    let mock_device = get(fake)?;
+    if let Some(ref agent) = agent {
+        if fake.is_legacy_pairing()? {
+            if agent.request_pin_code(device)? != mock_device.pin_code {
+                return Err(BluetoothError::PermissionDenied);
+            }
+        } else {
+            let passkey = mock_device.passkey;
+            match get_association_model(agent.get_io_capability(), mock_device.io_capability) {
+                AssociationModel::JustWorks => (),
+                AssociationModel::NumericComparison => agent.request_confirmation(device, passkey)?,
+                AssociationModel::PasskeyInput | AssociationModel::PasskeyBothInput => {
+                    if agent.request_passkey(device)? != passkey {
+                        return Err(BluetoothError::PermissionDenied);
+                    }
+                },
+                // The user types the six digits on the mock device.
+                AssociationModel::PasskeyDisplay => {
+                    for entered in 0..7 {
+                        agent.display_passkey(device, passkey, entered);
+                    }
+                },
+            }
+        }
+    } else if fake.is_legacy_pairing()? {
+        return Err(BluetoothError::PermissionDenied);
+    }
+    if mock_device.rejected {
+        if let Some(agent) = agent {
+            agent.cancel();
+        }
+        return Err(BluetoothError::PermissionDenied);
+    }
+    fake.pair().map_err(BluetoothError::from)
+}
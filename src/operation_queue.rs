@@ -0,0 +1,165 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+// Serializes the GATT operations on the attributes of a device. The operations run one at
+// a time, in the order they were queued, whichever thread queued them.
+
+use error::BluetoothError;
+use std::collections::{HashSet, VecDeque};
+use std::sync::{Condvar, Mutex, MutexGuard};
+#[cfg(feature = "bluetooth-test")]
+use std::thread;
+#[cfg(feature = "bluetooth-test")]
+use std::time::Duration;
+
+#[derive(Debug)]
+pub(crate) struct OperationQueue {
+    state: Mutex<QueueState>,
+    // Notified whenever an operation finishes, or operations are cancelled.
+    changed: Condvar,
+    #[cfg(feature = "bluetooth-test")]
+    mock: Mutex<MockOperations>,
+}
+
+#[derive(Debug, Default)]
+struct QueueState {
+    next_ticket: u64,
+    // The tickets of the waiting operations, the first one runs next.
+    waiting: VecDeque<u64>,
+    running: bool,
+    cancelled: HashSet<u64>,
+}
+
+// A mock device, which like a real stack, rejects an operation while an other one is running.
+#[cfg(feature = "bluetooth-test")]
+#[derive(Debug)]
+struct MockOperations {
+    // How long each operation takes.
+    duration: Duration,
+    in_progress: bool,
+    queue_enabled: bool,
+}
+
+// Lets the next operation run, even if the current one panicked.
+struct RunningOperation<'a> {
+    queue: &'a OperationQueue,
+}
+
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    match mutex.lock() {
+        Ok(guard) => guard,
+        Err(poisoned) => poisoned.into_inner(),
+    }
+}
+
+impl Default for OperationQueue {
+    fn default() -> OperationQueue {
+        OperationQueue {
+            state: Mutex::new(QueueState::default()),
+            changed: Condvar::new(),
+            #[cfg(feature = "bluetooth-test")]
+            mock: Mutex::new(MockOperations {
+                duration: Duration::from_secs(0),
+                in_progress: false,
+                queue_enabled: true,
+            }),
+        }
+    }
+}
+
+impl Drop for RunningOperation<'_> {
+    fn drop(&mut self) {
+        lock(&self.queue.state).running = false;
+        self.queue.changed.notify_all();
+    }
+}
+
+impl OperationQueue {
+    // Waits for the operations queued earlier, then runs the operation. Fails with
+    // `BluetoothError::Cancelled`, if `cancel_pending` was called before it started.
+    pub(crate) fn run<T, F>(&self, operation: F) -> Result<T, BluetoothError>
+        where F: FnOnce() -> Result<T, BluetoothError>
+    {
+        #[cfg(feature = "bluetooth-test")]
+        {
+            if !lock(&self.mock).queue_enabled {
+                return operation();
+            }
+        }
+        let mut state = lock(&self.state);
+        let ticket = state.next_ticket;
+        state.next_ticket += 1;
+        state.waiting.push_back(ticket);
+        loop {
+            if state.cancelled.remove(&ticket) {
+                state.waiting.retain(|waiting| *waiting != ticket);
+                drop(state);
+                self.changed.notify_all();
+                return Err(BluetoothError::Cancelled);
+            }
+            if !state.running && state.waiting.front() == Some(&ticket) {
+                break;
+            }
+            state = match self.changed.wait(state) {
+                Ok(guard) => guard,
+                Err(poisoned) => poisoned.into_inner(),
+            };
+        }
+        state.waiting.pop_front();
+        state.running = true;
+        drop(state);
+        let _running = RunningOperation {
+            queue: self,
+        };
+        operation()
+    }
+
+    // Cancels the operations which did not start yet, and returns their number.
+    pub(crate) fn cancel_pending(&self) -> usize {
+        let mut state = lock(&self.state);
+        let waiting: Vec<u64> = state.waiting.iter().cloned().collect();
+        state.cancelled.extend(waiting);
+        let cancelled = state.waiting.len();
+        drop(state);
+        self.changed.notify_all();
+        cancelled
+    }
+
+    pub(crate) fn get_pending(&self) -> usize {
+        lock(&self.state).waiting.len()
+    }
+
+    // Runs the operation as the mock device: it takes the configured duration, and fails
+    // with `BluetoothError::InvalidState`, like org.bluez.Error.InProgress, if an other
+    // operation is running.
+    #[cfg(feature = "bluetooth-test")]
+    pub(crate) fn run_mock<T, F>(&self, operation: F) -> Result<T, BluetoothError>
+        where F: FnOnce() -> Result<T, BluetoothError>
+    {
+        let duration = {
+            let mut mock = lock(&self.mock);
+            if mock.in_progress {
+                return Err(BluetoothError::InvalidState);
+            }
+            mock.in_progress = true;
+            mock.duration
+        };
+        if duration > Duration::from_secs(0) {
+            thread::sleep(duration);
+        }
+        let result = operation();
+        lock(&self.mock).in_progress = false;
+        result
+    }
+
+    #[cfg(feature = "bluetooth-test")]
+    pub(crate) fn set_mock_duration(&self, duration: Duration) {
+        lock(&self.mock).duration = duration;
+    }
+
+    #[cfg(feature = "bluetooth-test")]
+    pub(crate) fn set_enabled(&self, enabled: bool) {
+        lock(&self.mock).queue_enabled = enabled;
+    }
+}
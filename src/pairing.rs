@@ -0,0 +1,170 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+// The user interaction of pairing, modeled after the BlueZ Agent1 interface.
+// http://git.kernel.org/cgit/bluetooth/bluez.git/tree/doc/agent-api.txt
+
+use bluetooth::BluetoothDevice;
+use error::BluetoothError;
+use std::sync::{Mutex, MutexGuard};
+use uuid::BluetoothUuid;
+
+// https://www.bluetooth.com/specifications/bluetooth-core-specification
+// Vol 3, Part H, 2.3.2: IO capabilities.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum IoCapability {
+    DisplayOnly,
+    DisplayYesNo,
+    KeyboardOnly,
+    #[default]
+    NoInputNoOutput,
+    KeyboardDisplay,
+}
+
+// How the user takes part in the pairing, from the point of view of the local device.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AssociationModel {
+    JustWorks,
+    // Both devices display the same number, and the user confirms it on both.
+    NumericComparison,
+    // The remote device displays the passkey, and the user types it in locally.
+    PasskeyInput,
+    // The passkey is displayed locally, and the user types it in on the remote device.
+    PasskeyDisplay,
+    // The user types the same passkey in on both devices.
+    PasskeyBothInput,
+}
+
+// The answers to the requests are `Ok` if the user accepted, and `Err`, usually
+// `BluetoothError::PermissionDenied`, if they rejected or cancelled the request.
+pub trait PairingAgent: Send + Sync {
+    fn get_io_capability(&self) -> IoCapability;
+
+    // The PIN code of a legacy pairing.
+    fn request_pin_code(&self, device: &BluetoothDevice) -> Result<String, BluetoothError>;
+
+    // The passkey, between 0 and 999999, the remote device displays.
+    fn request_passkey(&self, device: &BluetoothDevice) -> Result<u32, BluetoothError>;
+
+    // Called again for every key the user types on the remote device.
+    fn display_passkey(&self, device: &BluetoothDevice, passkey: u32, entered: u16);
+
+    fn request_confirmation(&self, device: &BluetoothDevice, passkey: u32) -> Result<(), BluetoothError>;
+
+    // Whether the remote device may connect to the service.
+    fn authorize_service(&self, device: &BluetoothDevice, uuid: BluetoothUuid) -> Result<(), BluetoothError>;
+
+    // The pending request was cancelled by the remote device.
+    fn cancel(&self) {}
+}
+
+// Vol 3, Part H, 2.3.5.1, Table 2.8: Mapping of IO capabilities to key generation method,
+// with LE Secure Connections.
+pub fn get_association_model(local: IoCapability, remote: IoCapability) -> AssociationModel {
+    use self::IoCapability::*;
+    match (local, remote) {
+        (NoInputNoOutput, _) | (_, NoInputNoOutput) => AssociationModel::JustWorks,
+        (DisplayOnly, DisplayOnly) | (DisplayOnly, DisplayYesNo) | (DisplayYesNo, DisplayOnly) =>
+            AssociationModel::JustWorks,
+        (DisplayYesNo, DisplayYesNo) | (DisplayYesNo, KeyboardDisplay) | (KeyboardDisplay, DisplayYesNo) |
+        (KeyboardDisplay, KeyboardDisplay) => AssociationModel::NumericComparison,
+        (KeyboardOnly, KeyboardOnly) => AssociationModel::PasskeyBothInput,
+        (KeyboardOnly, _) | (KeyboardDisplay, DisplayOnly) => AssociationModel::PasskeyInput,
+        (_, KeyboardOnly) | (_, KeyboardDisplay) => AssociationModel::PasskeyDisplay,
+    }
+}
+
+// A request received by a `ScriptedAgent`, with the id of the device.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum AgentRequest {
+    RequestPinCode(String),
+    RequestPasskey(String),
+    DisplayPasskey(String, u32, u16),
+    RequestConfirmation(String, u32),
+    AuthorizeService(String, BluetoothUuid),
+    Cancel,
+}
+
+// An agent answering every request with the values set on it, and recording the requests,
+// for tests and for pairing without a user.
+#[derive(Debug, Default)]
+pub struct ScriptedAgent {
+    pub io_capability: IoCapability,
+    // `None` rejects the request.
+    pub pin_code: Option<String>,
+    pub passkey: Option<u32>,
+    pub confirm: bool,
+    pub authorize: bool,
+    requests: Mutex<Vec<AgentRequest>>,
+}
+
+impl ScriptedAgent {
+    pub fn new(io_capability: IoCapability) -> ScriptedAgent {
+        ScriptedAgent {
+            io_capability,
+            ..ScriptedAgent::default()
+        }
+    }
+
+    pub fn get_requests(&self) -> Vec<AgentRequest> {
+        lock(&self.requests).clone()
+    }
+
+    fn record(&self, request: AgentRequest) {
+        lock(&self.requests).push(request);
+    }
+}
+
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    match mutex.lock() {
+        Ok(guard) => guard,
+        Err(poisoned) => poisoned.into_inner(),
+    }
+}
+
+fn answer<T: Clone>(value: &Option<T>) -> Result<T, BluetoothError> {
+    value.clone().ok_or(BluetoothError::PermissionDenied)
+}
+
+fn accept(accepted: bool) -> Result<(), BluetoothError> {
+    if accepted {
+        Ok(())
+    } else {
+        Err(BluetoothError::PermissionDenied)
+    }
+}
+
+impl PairingAgent for ScriptedAgent {
+    fn get_io_capability(&self) -> IoCapability {
+        self.io_capability
+    }
+
+    fn request_pin_code(&self, device: &BluetoothDevice) -> Result<String, BluetoothError> {
+        self.record(AgentRequest::RequestPinCode(device.get_id()));
+        answer(&self.pin_code)
+    }
+
+    fn request_passkey(&self, device: &BluetoothDevice) -> Result<u32, BluetoothError> {
+        self.record(AgentRequest::RequestPasskey(device.get_id()));
+        answer(&self.passkey)
+    }
+
+    fn display_passkey(&self, device: &BluetoothDevice, passkey: u32, entered: u16) {
+        self.record(AgentRequest::DisplayPasskey(device.get_id(), passkey, entered));
+    }
+
+    fn request_confirmation(&self, device: &BluetoothDevice, passkey: u32) -> Result<(), BluetoothError> {
+        self.record(AgentRequest::RequestConfirmation(device.get_id(), passkey));
+        accept(self.confirm)
+    }
+
+    fn authorize_service(&self, device: &BluetoothDevice, uuid: BluetoothUuid) -> Result<(), BluetoothError> {
+        self.record(AgentRequest::AuthorizeService(device.get_id(), uuid));
+        accept(self.authorize)
+    }
+
+    fn cancel(&self) {
+        self.record(AgentRequest::Cancel);
+    }
+}
@@ -0,0 +1,357 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+// The named fake adapters of the Web Bluetooth tests, built on a mock adapter.
+// https://github.com/servo/servo/blob/master/components/bluetooth/test.rs
+
+use address::BluetoothAddress;
+use bluetooth::{BluetoothAdapter, BluetoothDevice, BluetoothGATTCharacteristic};
+use bluetooth::{BluetoothGATTDescriptor, BluetoothGATTService};
+use error::BluetoothError;
+use std::collections::HashMap;
+use uuid::BluetoothUuid;
+
+// Adapter names
+pub const NOT_PRESENT_ADAPTER: &str = "NotPresentAdapter";
+pub const NOT_POWERED_ADAPTER: &str = "NotPoweredAdapter";
+pub const EMPTY_ADAPTER: &str = "EmptyAdapter";
+pub const GLUCOSE_HEART_RATE_ADAPTER: &str = "GlucoseHeartRateAdapter";
+pub const UNICODE_DEVICE_ADAPTER: &str = "UnicodeDeviceAdapter";
+pub const MISSING_SERVICE_HEART_RATE_ADAPTER: &str = "MissingServiceHeartRateAdapter";
+pub const MISSING_CHARACTERISTIC_HEART_RATE_ADAPTER: &str = "MissingCharacteristicHeartRateAdapter";
+pub const MISSING_DESCRIPTOR_HEART_RATE_ADAPTER: &str = "MissingDescriptorsHeartRateAdapter";
+pub const HEART_RATE_ADAPTER: &str = "HeartRateAdapter";
+pub const EMPTY_NAME_HEART_RATE_ADAPTER: &str = "EmptyNameHeartRateAdapter";
+pub const NO_NAME_HEART_RATE_ADAPTER: &str = "NoNameHeartRateAdapter";
+pub const TWO_HEART_RATE_SERVICES_ADAPTER: &str = "TwoHeartRateServicesAdapter";
+pub const BLOCKLIST_TEST_ADAPTER: &str = "BlocklistTestAdapter";
+
+pub const PRESETS: &[&str] = &[NOT_PRESENT_ADAPTER,
+                               NOT_POWERED_ADAPTER,
+                               EMPTY_ADAPTER,
+                               GLUCOSE_HEART_RATE_ADAPTER,
+                               UNICODE_DEVICE_ADAPTER,
+                               MISSING_SERVICE_HEART_RATE_ADAPTER,
+                               MISSING_CHARACTERISTIC_HEART_RATE_ADAPTER,
+                               MISSING_DESCRIPTOR_HEART_RATE_ADAPTER,
+                               HEART_RATE_ADAPTER,
+                               EMPTY_NAME_HEART_RATE_ADAPTER,
+                               NO_NAME_HEART_RATE_ADAPTER,
+                               TWO_HEART_RATE_SERVICES_ADAPTER,
+                               BLOCKLIST_TEST_ADAPTER];
+
+// Device names
+pub const CONNECTABLE_DEVICE_NAME: &str = "Connectable Device";
+pub const EMPTY_DEVICE_NAME: &str = "";
+pub const GLUCOSE_DEVICE_NAME: &str = "Glucose Device";
+pub const HEART_RATE_DEVICE_NAME: &str = "Heart Rate Device";
+pub const UNICODE_DEVICE_NAME: &str = "❤❤❤❤❤❤❤❤❤";
+
+// Device addresses
+pub const CONNECTABLE_DEVICE_ADDRESS: &str = "00:00:00:00:00:04";
+pub const GLUCOSE_DEVICE_ADDRESS: &str = "00:00:00:00:00:02";
+pub const HEART_RATE_DEVICE_ADDRESS: &str = "00:00:00:00:00:03";
+pub const UNICODE_DEVICE_ADDRESS: &str = "00:00:00:00:00:01";
+
+// Service UUIDs
+pub const BLOCKLIST_TEST_SERVICE_UUID: &str = "611c954a-263b-4f4a-aab6-01ddb953f985";
+pub const DEVICE_INFORMATION_UUID: &str = "0000180a-0000-1000-8000-00805f9b34fb";
+pub const GENERIC_ACCESS_SERVICE_UUID: &str = "00001800-0000-1000-8000-00805f9b34fb";
+pub const GLUCOSE_SERVICE_UUID: &str = "00001808-0000-1000-8000-00805f9b34fb";
+pub const HEART_RATE_SERVICE_UUID: &str = "0000180d-0000-1000-8000-00805f9b34fb";
+pub const HUMAN_INTERFACE_DEVICE_SERVICE_UUID: &str = "00001812-0000-1000-8000-00805f9b34fb";
+pub const TX_POWER_SERVICE_UUID: &str = "00001804-0000-1000-8000-00805f9b34fb";
+
+// Characteristic UUIDs
+pub const BLOCKLIST_EXCLUDE_READS_CHARACTERISTIC_UUID: &str = "bad1c9a2-9a5b-4015-8b60-1579bbbf2135";
+pub const BODY_SENSOR_LOCATION_CHARACTERISTIC_UUID: &str = "00002a38-0000-1000-8000-00805f9b34fb";
+pub const DEVICE_NAME_CHARACTERISTIC_UUID: &str = "00002a00-0000-1000-8000-00805f9b34fb";
+pub const HEART_RATE_MEASUREMENT_CHARACTERISTIC_UUID: &str = "00002a37-0000-1000-8000-00805f9b34fb";
+pub const PERIPHERAL_PRIVACY_FLAG_CHARACTERISTIC_UUID: &str = "00002a02-0000-1000-8000-00805f9b34fb";
+pub const SERIAL_NUMBER_STRING_UUID: &str = "00002a25-0000-1000-8000-00805f9b34fb";
+
+// Descriptor UUIDs
+pub const BLOCKLIST_EXCLUDE_READS_DESCRIPTOR_UUID: &str = "aaaaaaaa-aaaa-1181-0510-810819516110";
+pub const BLOCKLIST_DESCRIPTOR_UUID: &str = "07711111-6104-0970-7011-1107105110aa";
+pub const CHARACTERISTIC_USER_DESCRIPTION_UUID: &str = "00002901-0000-1000-8000-00805f9b34fb";
+pub const CLIENT_CHARACTERISTIC_CONFIGURATION_UUID: &str = "00002902-0000-1000-8000-00805f9b34fb";
+pub const NUMBER_OF_DIGITALS_UUID: &str = "00002909-0000-1000-8000-00805f9b34fb";
+
+pub const HEART_RATE_DEVICE_NAME_DESCRIPTION: &str = "The name of this device.";
+
+const READ_FLAG: &str = "read";
+const WRITE_FLAG: &str = "write";
+const NOTIFY_FLAG: &str = "notify";
+
+fn flags(flags: &[&str]) -> Vec<String> {
+    flags.iter().map(|flag| String::from(*flag)).collect()
+}
+
+fn uuids(uuids: &[&str]) -> Result<Vec<BluetoothUuid>, BluetoothError> {
+    uuids.iter().map(|uuid| BluetoothUuid::parse(uuid)).collect()
+}
+
+// The attributes get ids like the object paths of BlueZ, numbered in the order they are created.
+struct PresetBuilder {
+    adapter: BluetoothAdapter,
+    last_handle: u32,
+}
+
+impl PresetBuilder {
+    fn next_id(&mut self, parent: &str, kind: &str) -> String {
+        self.last_handle += 1;
+        format!("{}/{}{:04x}", parent, kind, self.last_handle)
+    }
+
+    fn create_device(&mut self, name: &str, address: &str) -> Result<BluetoothDevice, BluetoothError> {
+        let id = format!("{}/dev_{}", self.adapter.get_id(), address.replace(':', "_"));
+        let device = BluetoothDevice::create_mock_device(self.adapter.clone(), id)?;
+        device.set_name(Some(name.to_owned()))?;
+        device.set_address(BluetoothAddress::parse(address)?)?;
+        device.set_connectable(true)?;
+        Ok(device)
+    }
+
+    fn create_device_with_uuids(&mut self,
+                                name: &str,
+                                address: &str,
+                                service_uuids: &[&str])
+                                -> Result<BluetoothDevice, BluetoothError> {
+        let device = self.create_device(name, address)?;
+        device.set_uuids(uuids(service_uuids)?)?;
+        Ok(device)
+    }
+
+    fn create_service(&mut self, device: &BluetoothDevice, uuid: &str) -> Result<BluetoothGATTService, BluetoothError> {
+        let id = self.next_id(&device.get_id(), "service");
+        let service = BluetoothGATTService::create_mock_service(device.clone(), id)?;
+        service.set_uuid(BluetoothUuid::parse(uuid)?)?;
+        Ok(service)
+    }
+
+    fn create_characteristic(&mut self,
+                             service: &BluetoothGATTService,
+                             uuid: &str,
+                             characteristic_flags: &[&str])
+                             -> Result<BluetoothGATTCharacteristic, BluetoothError> {
+        let id = self.next_id(&service.get_id(), "char");
+        let characteristic = BluetoothGATTCharacteristic::create_mock_characteristic(service.clone(), id)?;
+        characteristic.set_uuid(BluetoothUuid::parse(uuid)?)?;
+        characteristic.set_flags(flags(characteristic_flags))?;
+        Ok(characteristic)
+    }
+
+    fn create_characteristic_with_value(&mut self,
+                                        service: &BluetoothGATTService,
+                                        uuid: &str,
+                                        characteristic_flags: &[&str],
+                                        value: Vec<u8>)
+                                        -> Result<BluetoothGATTCharacteristic, BluetoothError> {
+        let characteristic = self.create_characteristic(service, uuid, characteristic_flags)?;
+        characteristic.set_value(value)?;
+        Ok(characteristic)
+    }
+
+    fn create_descriptor_with_value(&mut self,
+                                    characteristic: &BluetoothGATTCharacteristic,
+                                    uuid: &str,
+                                    descriptor_flags: &[&str],
+                                    value: Vec<u8>)
+                                    -> Result<BluetoothGATTDescriptor, BluetoothError> {
+        let id = self.next_id(&characteristic.get_id(), "desc");
+        let descriptor = BluetoothGATTDescriptor::create_mock_descriptor(characteristic.clone(), id)?;
+        descriptor.set_uuid(BluetoothUuid::parse(uuid)?)?;
+        descriptor.set_flags(flags(descriptor_flags))?;
+        descriptor.set_value(value)?;
+        Ok(descriptor)
+    }
+
+    fn create_heart_rate_service(&mut self,
+                                 device: &BluetoothDevice,
+                                 empty: bool)
+                                 -> Result<BluetoothGATTService, BluetoothError> {
+        let service = self.create_service(device, HEART_RATE_SERVICE_UUID)?;
+        if empty {
+            return Ok(service);
+        }
+        self.create_characteristic_with_value(&service,
+                                              HEART_RATE_MEASUREMENT_CHARACTERISTIC_UUID,
+                                              &[NOTIFY_FLAG, READ_FLAG, WRITE_FLAG],
+                                              vec![0])?;
+        self.create_characteristic_with_value(&service,
+                                              BODY_SENSOR_LOCATION_CHARACTERISTIC_UUID,
+                                              &[READ_FLAG, WRITE_FLAG],
+                                              vec![49])?;
+        self.create_characteristic_with_value(&service,
+                                              BODY_SENSOR_LOCATION_CHARACTERISTIC_UUID,
+                                              &[READ_FLAG, WRITE_FLAG],
+                                              vec![50])?;
+        Ok(service)
+    }
+
+    fn create_generic_access_service(&mut self,
+                                     device: &BluetoothDevice,
+                                     empty: bool)
+                                     -> Result<BluetoothGATTService, BluetoothError> {
+        let service = self.create_service(device, GENERIC_ACCESS_SERVICE_UUID)?;
+        if empty {
+            return Ok(service);
+        }
+        let device_name = self.create_characteristic_with_value(&service,
+                                                                DEVICE_NAME_CHARACTERISTIC_UUID,
+                                                                &[READ_FLAG, WRITE_FLAG],
+                                                                HEART_RATE_DEVICE_NAME.as_bytes().to_vec())?;
+        self.create_descriptor_with_value(&device_name, NUMBER_OF_DIGITALS_UUID, &[READ_FLAG, WRITE_FLAG], vec![49])?;
+        self.create_descriptor_with_value(&device_name, NUMBER_OF_DIGITALS_UUID, &[READ_FLAG, WRITE_FLAG], vec![50])?;
+        // Servo leaves the flags of these two unset.
+        self.create_descriptor_with_value(&device_name,
+                                          CHARACTERISTIC_USER_DESCRIPTION_UUID,
+                                          &[],
+                                          HEART_RATE_DEVICE_NAME_DESCRIPTION.as_bytes().to_vec())?;
+        self.create_descriptor_with_value(&device_name, CLIENT_CHARACTERISTIC_CONFIGURATION_UUID, &[], vec![0])?;
+        self.create_characteristic(&service, PERIPHERAL_PRIVACY_FLAG_CHARACTERISTIC_UUID, &[READ_FLAG, WRITE_FLAG])?;
+        Ok(service)
+    }
+
+    fn create_heart_rate_device(&mut self, empty: bool) -> Result<BluetoothDevice, BluetoothError> {
+        let device = self.create_device_with_uuids(HEART_RATE_DEVICE_NAME,
+                                                   HEART_RATE_DEVICE_ADDRESS,
+                                                   &[GENERIC_ACCESS_SERVICE_UUID, HEART_RATE_SERVICE_UUID])?;
+        if empty {
+            return Ok(device);
+        }
+        self.create_generic_access_service(&device, false)?;
+        self.create_heart_rate_service(&device, false)?;
+        Ok(device)
+    }
+
+    fn create_missing_characteristic_heart_rate_device(&mut self) -> Result<(), BluetoothError> {
+        let device = self.create_heart_rate_device(true)?;
+        self.create_generic_access_service(&device, true)?;
+        self.create_heart_rate_service(&device, true)?;
+        Ok(())
+    }
+
+    fn create_missing_descriptor_heart_rate_device(&mut self) -> Result<(), BluetoothError> {
+        let device = self.create_heart_rate_device(true)?;
+        let generic_access_service = self.create_generic_access_service(&device, true)?;
+        self.create_characteristic_with_value(&generic_access_service,
+                                              DEVICE_NAME_CHARACTERISTIC_UUID,
+                                              &[READ_FLAG, WRITE_FLAG],
+                                              HEART_RATE_DEVICE_NAME.as_bytes().to_vec())?;
+        self.create_characteristic(&generic_access_service,
+                                   PERIPHERAL_PRIVACY_FLAG_CHARACTERISTIC_UUID,
+                                   &[READ_FLAG, WRITE_FLAG])?;
+        self.create_heart_rate_service(&device, false)?;
+        Ok(())
+    }
+
+    fn create_two_heart_rate_services_device(&mut self) -> Result<(), BluetoothError> {
+        let device = self.create_heart_rate_device(true)?;
+        device.set_uuids(uuids(&[GENERIC_ACCESS_SERVICE_UUID, HEART_RATE_SERVICE_UUID, HEART_RATE_SERVICE_UUID])?)?;
+        self.create_generic_access_service(&device, false)?;
+        let first_heart_rate_service = self.create_heart_rate_service(&device, true)?;
+        let second_heart_rate_service = self.create_heart_rate_service(&device, true)?;
+        self.create_characteristic_with_value(&first_heart_rate_service,
+                                              HEART_RATE_MEASUREMENT_CHARACTERISTIC_UUID,
+                                              &[NOTIFY_FLAG],
+                                              vec![0])?;
+        self.create_characteristic_with_value(&first_heart_rate_service,
+                                              BODY_SENSOR_LOCATION_CHARACTERISTIC_UUID,
+                                              &[READ_FLAG, WRITE_FLAG],
+                                              vec![49])?;
+        self.create_characteristic_with_value(&second_heart_rate_service,
+                                              BODY_SENSOR_LOCATION_CHARACTERISTIC_UUID,
+                                              &[READ_FLAG, WRITE_FLAG],
+                                              vec![50])?;
+        Ok(())
+    }
+
+    fn create_blocklisted_device(&mut self) -> Result<(), BluetoothError> {
+        let device = self.create_device_with_uuids(CONNECTABLE_DEVICE_NAME,
+                                                   CONNECTABLE_DEVICE_ADDRESS,
+                                                   &[BLOCKLIST_TEST_SERVICE_UUID,
+                                                     DEVICE_INFORMATION_UUID,
+                                                     GENERIC_ACCESS_SERVICE_UUID,
+                                                     HEART_RATE_SERVICE_UUID,
+                                                     HUMAN_INTERFACE_DEVICE_SERVICE_UUID])?;
+        let blocklist_test_service = self.create_service(&device, BLOCKLIST_TEST_SERVICE_UUID)?;
+        let exclude_reads = self.create_characteristic(&blocklist_test_service,
+                                                       BLOCKLIST_EXCLUDE_READS_CHARACTERISTIC_UUID,
+                                                       &[READ_FLAG, WRITE_FLAG])?;
+        self.create_descriptor_with_value(&exclude_reads,
+                                          BLOCKLIST_EXCLUDE_READS_DESCRIPTOR_UUID,
+                                          &[READ_FLAG, WRITE_FLAG],
+                                          vec![54; 3])?;
+        self.create_descriptor_with_value(&exclude_reads, BLOCKLIST_DESCRIPTOR_UUID, &[READ_FLAG], vec![54; 3])?;
+        let device_information_service = self.create_service(&device, DEVICE_INFORMATION_UUID)?;
+        self.create_characteristic(&device_information_service, SERIAL_NUMBER_STRING_UUID, &[])?;
+        self.create_generic_access_service(&device, false)?;
+        self.create_heart_rate_service(&device, false)?;
+        self.create_service(&device, HUMAN_INTERFACE_DEVICE_SERVICE_UUID)?;
+        Ok(())
+    }
+
+    fn create_glucose_heart_rate_devices(&mut self) -> Result<(), BluetoothError> {
+        let glucose_device = self.create_device_with_uuids(GLUCOSE_DEVICE_NAME,
+                                                           GLUCOSE_DEVICE_ADDRESS,
+                                                           &[GLUCOSE_SERVICE_UUID, TX_POWER_SERVICE_UUID])?;
+        let heart_rate_device = self.create_heart_rate_device(true)?;
+
+        let mut manufacturer_data = HashMap::new();
+        manufacturer_data.insert(17, vec![1, 2, 3]);
+        glucose_device.set_manufacturer_data(manufacturer_data)?;
+
+        let mut service_data = HashMap::new();
+        service_data.insert(BluetoothUuid::parse(GLUCOSE_SERVICE_UUID)?, vec![1, 2, 3]);
+        glucose_device.set_service_data(service_data)?;
+
+        let mut service_data = HashMap::new();
+        service_data.insert(BluetoothUuid::parse(HEART_RATE_SERVICE_UUID)?, vec![1, 2, 3]);
+        heart_rate_device.set_service_data(service_data)?;
+        Ok(())
+    }
+}
+
+// Fails with `InvalidArgument` for a name not in `PRESETS`.
+pub fn create_preset(name: &str) -> Result<BluetoothAdapter, BluetoothError> {
+    let adapter = BluetoothAdapter::init_mock()?;
+    adapter.set_id(name.to_owned());
+    adapter.set_name(name.to_owned())?;
+    adapter.set_powered(true)?;
+    adapter.set_discoverable(true)?;
+    let mut builder = PresetBuilder {
+        adapter: adapter.clone(),
+        last_handle: 0,
+    };
+    match name {
+        NOT_PRESENT_ADAPTER => adapter.set_present(false)?,
+        NOT_POWERED_ADAPTER => adapter.set_powered(false)?,
+        EMPTY_ADAPTER => (),
+        GLUCOSE_HEART_RATE_ADAPTER => builder.create_glucose_heart_rate_devices()?,
+        UNICODE_DEVICE_ADAPTER => {
+            builder.create_device(UNICODE_DEVICE_NAME, UNICODE_DEVICE_ADDRESS)?;
+        },
+        MISSING_SERVICE_HEART_RATE_ADAPTER => {
+            builder.create_heart_rate_device(true)?;
+        },
+        MISSING_CHARACTERISTIC_HEART_RATE_ADAPTER => builder.create_missing_characteristic_heart_rate_device()?,
+        MISSING_DESCRIPTOR_HEART_RATE_ADAPTER => builder.create_missing_descriptor_heart_rate_device()?,
+        HEART_RATE_ADAPTER => {
+            builder.create_heart_rate_device(false)?;
+        },
+        EMPTY_NAME_HEART_RATE_ADAPTER => {
+            let device = builder.create_heart_rate_device(false)?;
+            device.set_name(Some(EMPTY_DEVICE_NAME.to_owned()))?;
+        },
+        NO_NAME_HEART_RATE_ADAPTER => {
+            let device = builder.create_heart_rate_device(false)?;
+            device.set_name(None)?;
+        },
+        TWO_HEART_RATE_SERVICES_ADAPTER => builder.create_two_heart_rate_services_device()?,
+        BLOCKLIST_TEST_ADAPTER => builder.create_blocklisted_device()?,
+        _ => return Err(BluetoothError::InvalidArgument(name.to_owned())),
+    }
+    Ok(adapter)
+}
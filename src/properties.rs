@@ -0,0 +1,163 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+// Typed sets of the characteristic properties and descriptor permissions, which the backends
+// report as strings.
+// BlueZ: http://git.kernel.org/cgit/bluetooth/bluez.git/tree/doc/gatt-api.txt
+// Android: https://developer.android.com/reference/android/bluetooth/BluetoothGattCharacteristic
+
+use std::fmt;
+use std::ops::{BitAnd, BitOr, BitOrAssign, Sub};
+
+// Defines a set of bits with an associated constant for each bit, and the backend flag names
+// mapping to it. The first name is the BlueZ one, which `to_flags` returns.
+macro_rules! flags_type(
+    ($type_name: ident, $bits_type: ty, { $($flag: ident = $bit: expr => [$($name: expr),+],)+ }) => {
+        #[derive(Clone, Copy, Default, PartialEq, Eq, Hash)]
+        pub struct $type_name {
+            bits: $bits_type,
+        }
+
+        impl $type_name {
+            $(pub const $flag: $type_name = $type_name { bits: $bit };)+
+
+            const NAMES: &'static [($type_name, &'static [&'static str])] = &[
+                $(($type_name::$flag, &[$($name),+]),)+
+            ];
+
+            pub fn empty() -> $type_name {
+                $type_name::default()
+            }
+
+            pub fn all() -> $type_name {
+                $type_name { bits: 0 $(| $bit)+ }
+            }
+
+            pub fn bits(&self) -> $bits_type {
+                self.bits
+            }
+
+            // Unknown bits are dropped.
+            pub fn from_bits_truncate(bits: $bits_type) -> $type_name {
+                $type_name { bits: bits & $type_name::all().bits }
+            }
+
+            pub fn is_empty(&self) -> bool {
+                self.bits == 0
+            }
+
+            pub fn contains(&self, other: $type_name) -> bool {
+                self.bits & other.bits == other.bits
+            }
+
+            pub fn intersects(&self, other: $type_name) -> bool {
+                self.bits & other.bits != 0
+            }
+
+            pub fn insert(&mut self, other: $type_name) {
+                self.bits |= other.bits;
+            }
+
+            pub fn remove(&mut self, other: $type_name) {
+                self.bits &= !other.bits;
+            }
+
+            // The flag with the given backend name, e.g. "write-without-response" from BlueZ,
+            // or "WRITE_NO_RESPONSE" from Android.
+            pub fn from_flag(flag: &str) -> Option<$type_name> {
+                let flag = normalize(flag);
+                $type_name::NAMES.iter()
+                                 .find(|&&(_, names)| names.contains(&flag.as_str()))
+                                 .map(|&(value, _)| value)
+            }
+
+            // Flags no backend reports under that name are ignored.
+            pub fn from_flags<S: AsRef<str>>(flags: &[S]) -> $type_name {
+                flags.iter()
+                     .filter_map(|flag| $type_name::from_flag(flag.as_ref()))
+                     .fold($type_name::empty(), |set, flag| set | flag)
+            }
+
+            pub fn to_flags(&self) -> Vec<String> {
+                $type_name::NAMES.iter()
+                                 .filter(|&&(value, _)| self.contains(value))
+                                 .map(|&(_, names)| String::from(names[0]))
+                                 .collect()
+            }
+        }
+
+        impl BitOr for $type_name {
+            type Output = $type_name;
+
+            fn bitor(self, other: $type_name) -> $type_name {
+                $type_name { bits: self.bits | other.bits }
+            }
+        }
+
+        impl BitOrAssign for $type_name {
+            fn bitor_assign(&mut self, other: $type_name) {
+                self.bits |= other.bits;
+            }
+        }
+
+        impl BitAnd for $type_name {
+            type Output = $type_name;
+
+            fn bitand(self, other: $type_name) -> $type_name {
+                $type_name { bits: self.bits & other.bits }
+            }
+        }
+
+        impl Sub for $type_name {
+            type Output = $type_name;
+
+            fn sub(self, other: $type_name) -> $type_name {
+                $type_name { bits: self.bits & !other.bits }
+            }
+        }
+
+        impl fmt::Debug for $type_name {
+            fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                write!(f, "{}({})", stringify!($type_name), self.to_flags().join(" | "))
+            }
+        }
+    };
+);
+
+// Lower case, with dashes, so "WRITE_NO_RESPONSE" and "write-no-response" are the same name.
+fn normalize(flag: &str) -> String {
+    flag.trim().to_lowercase().replace('_', "-")
+}
+
+flags_type!(CharacteristicProperties, u32, {
+    BROADCAST = 1 << 0 => ["broadcast"],
+    READ = 1 << 1 => ["read"],
+    WRITE_WITHOUT_RESPONSE = 1 << 2 => ["write-without-response", "write-no-response"],
+    WRITE = 1 << 3 => ["write"],
+    NOTIFY = 1 << 4 => ["notify"],
+    INDICATE = 1 << 5 => ["indicate"],
+    AUTHENTICATED_SIGNED_WRITES = 1 << 6 => ["authenticated-signed-writes", "signed-write"],
+    EXTENDED_PROPERTIES = 1 << 7 => ["extended-properties", "extended-props"],
+    RELIABLE_WRITE = 1 << 8 => ["reliable-write"],
+    WRITABLE_AUXILIARIES = 1 << 9 => ["writable-auxiliaries"],
+    ENCRYPT_READ = 1 << 10 => ["encrypt-read", "read-encrypted"],
+    ENCRYPT_WRITE = 1 << 11 => ["encrypt-write", "write-encrypted"],
+    ENCRYPT_AUTHENTICATED_READ = 1 << 12 => ["encrypt-authenticated-read", "read-encrypted-mitm"],
+    ENCRYPT_AUTHENTICATED_WRITE = 1 << 13 => ["encrypt-authenticated-write", "write-encrypted-mitm"],
+    SECURE_READ = 1 << 14 => ["secure-read"],
+    SECURE_WRITE = 1 << 15 => ["secure-write"],
+    AUTHORIZE = 1 << 16 => ["authorize"],
+});
+
+flags_type!(DescriptorPermissions, u16, {
+    READ = 1 << 0 => ["read"],
+    WRITE = 1 << 1 => ["write"],
+    ENCRYPT_READ = 1 << 2 => ["encrypt-read", "read-encrypted"],
+    ENCRYPT_WRITE = 1 << 3 => ["encrypt-write", "write-encrypted"],
+    ENCRYPT_AUTHENTICATED_READ = 1 << 4 => ["encrypt-authenticated-read", "read-encrypted-mitm"],
+    ENCRYPT_AUTHENTICATED_WRITE = 1 << 5 => ["encrypt-authenticated-write", "write-encrypted-mitm"],
+    SECURE_READ = 1 << 6 => ["secure-read"],
+    SECURE_WRITE = 1 << 7 => ["secure-write"],
+    AUTHORIZE = 1 << 8 => ["authorize"],
+});
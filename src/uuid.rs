@@ -0,0 +1,114 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+use error::BluetoothError;
+use std::fmt;
+use std::str::FromStr;
+
+// 00000000-0000-1000-8000-00805F9B34FB
+// https://www.bluetooth.com/specifications/assigned-numbers/service-discovery
+const BLUETOOTH_BASE_UUID: u128 = 0x0000_0000_0000_1000_8000_0080_5F9B_34FB;
+const BLUETOOTH_BASE_UUID_MASK: u128 = 0x0000_0000_FFFF_FFFF_FFFF_FFFF_FFFF_FFFF;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct BluetoothUuid(u128);
+
+impl BluetoothUuid {
+    pub fn from_u16(alias: u16) -> BluetoothUuid {
+        BluetoothUuid::from_u32(alias as u32)
+    }
+
+    pub fn from_u32(alias: u32) -> BluetoothUuid {
+        BluetoothUuid(((alias as u128) << 96) | BLUETOOTH_BASE_UUID)
+    }
+
+    pub fn from_u128(value: u128) -> BluetoothUuid {
+        BluetoothUuid(value)
+    }
+
+    pub fn from_bytes(bytes: [u8; 16]) -> BluetoothUuid {
+        BluetoothUuid(u128::from_be_bytes(bytes))
+    }
+
+    // Accepts "180d", "0x2A37", "0000180f" and the full
+    // "0000180d-0000-1000-8000-00805f9b34fb" form, in any case.
+    pub fn parse(uuid: &str) -> Result<BluetoothUuid, BluetoothError> {
+        let trimmed = uuid.trim();
+        let short = if trimmed.starts_with("0x") || trimmed.starts_with("0X") {
+            &trimmed[2..]
+        } else {
+            trimmed
+        };
+        let invalid = || BluetoothError::InvalidArgument(uuid.to_owned());
+        let digits: String = match (short.len(), trimmed.len()) {
+            (4, _) | (8, _) => {
+                return parse_hex(short).map(|alias| BluetoothUuid::from_u32(alias as u32)).ok_or_else(invalid);
+            },
+            (_, 32) => trimmed.to_owned(),
+            (_, 36) => {
+                for (index, character) in trimmed.char_indices() {
+                    let dash_expected = index == 8 || index == 13 || index == 18 || index == 23;
+                    if dash_expected != (character == '-') {
+                        return Err(invalid());
+                    }
+                }
+                trimmed.chars().filter(|character| *character != '-').collect()
+            },
+            _ => return Err(invalid()),
+        };
+        parse_hex(&digits).map(BluetoothUuid).ok_or_else(invalid)
+    }
+
+    pub fn as_u128(&self) -> u128 {
+        self.0
+    }
+
+    pub fn to_bytes(&self) -> [u8; 16] {
+        self.0.to_be_bytes()
+    }
+
+    pub fn is_base_uuid(&self) -> bool {
+        self.0 & BLUETOOTH_BASE_UUID_MASK == BLUETOOTH_BASE_UUID
+    }
+
+    // The 32-bit alias, if this UUID is derived from the Bluetooth base UUID.
+    pub fn to_u32(&self) -> Option<u32> {
+        if self.is_base_uuid() {
+            Some((self.0 >> 96) as u32)
+        } else {
+            None
+        }
+    }
+
+    // The 16-bit alias, if this UUID is derived from the Bluetooth base UUID
+    // and fits in 16 bits.
+    pub fn to_u16(&self) -> Option<u16> {
+        match self.to_u32() {
+            Some(alias) if alias <= 0xFFFF => Some(alias as u16),
+            _ => None,
+        }
+    }
+}
+
+fn parse_hex(digits: &str) -> Option<u128> {
+    if digits.is_empty() || !digits.chars().all(|character| character.is_ascii_hexdigit()) {
+        return None;
+    }
+    u128::from_str_radix(digits, 16).ok()
+}
+
+impl FromStr for BluetoothUuid {
+    type Err = BluetoothError;
+
+    fn from_str(uuid: &str) -> Result<BluetoothUuid, BluetoothError> {
+        BluetoothUuid::parse(uuid)
+    }
+}
+
+impl fmt::Display for BluetoothUuid {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let hex = format!("{:032x}", self.0);
+        write!(f, "{}-{}-{}-{}-{}", &hex[0..8], &hex[8..12], &hex[12..16], &hex[16..20], &hex[20..32])
+    }
+}
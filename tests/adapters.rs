@@ -0,0 +1,69 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+#![cfg(feature = "bluetooth-test")]
+
+extern crate device;
+
+use device::bluetooth::{BluetoothAdapter, BluetoothAddress, BluetoothDevice, BluetoothError};
+use device::manager::AdapterManager;
+
+fn listed_ids(manager: &AdapterManager) -> Vec<String> {
+    manager.list_mock().iter().map(BluetoothAdapter::get_id).collect()
+}
+
+#[test]
+fn mock_adapters_are_listed_by_id() {
+    let manager = AdapterManager::new();
+    manager.add_mock_adapter(String::from("hci1")).unwrap();
+    manager.add_mock_adapter(String::from("hci0")).unwrap();
+    assert_eq!(listed_ids(&manager), vec![String::from("hci0"), String::from("hci1")]);
+
+    manager.remove_adapter("hci0").unwrap();
+    assert_eq!(listed_ids(&manager), vec![String::from("hci1")]);
+    assert!(listed_ids(&AdapterManager::new()).is_empty());
+}
+
+#[test]
+fn only_mock_adapters_are_listed() {
+    let manager = AdapterManager::new();
+    manager.add_mock_adapter(String::from("mock")).unwrap();
+    if let Ok(adapter) = BluetoothAdapter::init() {
+        let id = manager.add_adapter(adapter).unwrap();
+        assert_eq!(manager.init_mock_with_id(&id).err(), Some(BluetoothError::NotFound));
+    }
+    assert_eq!(listed_ids(&manager), vec![String::from("mock")]);
+}
+
+#[test]
+fn mock_adapter_by_id() {
+    let manager = AdapterManager::new();
+    let built_in = manager.add_mock_adapter(String::from("built_in")).unwrap();
+    let dongle = manager.add_mock_adapter(String::from("dongle")).unwrap();
+    BluetoothDevice::create_mock_device(dongle, String::from("device")).unwrap();
+
+    let adapter = manager.init_mock_with_id("dongle").unwrap();
+    assert_eq!(adapter.get_devices().unwrap().len(), 1);
+    assert_eq!(built_in.get_devices().unwrap().len(), 0);
+    assert_eq!(manager.init_mock_with_id("missing").err(), Some(BluetoothError::NotFound));
+}
+
+#[test]
+fn mock_adapter_by_address() {
+    let built_in_address = BluetoothAddress::parse("00:00:00:00:0A:01").unwrap();
+    let dongle_address = BluetoothAddress::parse("00:00:00:00:0A:02").unwrap();
+    let manager = AdapterManager::new();
+    manager.add_mock_adapter(String::from("built_in")).unwrap().set_address(built_in_address).unwrap();
+    manager.add_mock_adapter(String::from("dongle")).unwrap().set_address(dongle_address).unwrap();
+
+    assert_eq!(manager.init_mock_with_address(dongle_address).unwrap().get_id(), "dongle");
+    assert_eq!(manager.init_mock_with_address(built_in_address).unwrap().get_id(), "built_in");
+    let missing = BluetoothAddress::parse("00:00:00:00:0A:03").unwrap();
+    assert_eq!(manager.init_mock_with_address(missing).err(), Some(BluetoothError::NotFound));
+}
+
+#[test]
+fn unknown_adapter_id() {
+    assert!(BluetoothAdapter::init_with_id("/org/bluez/unknown").is_err());
+}
@@ -0,0 +1,99 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+extern crate device;
+
+use device::address::{BluetoothAddress, BluetoothAddressType};
+use device::error::BluetoothError;
+
+const BYTES: [u8; 6] = [0x01, 0x2A, 0x00, 0x4D, 0x00, 0x04];
+
+fn address_type(address: &str) -> Result<BluetoothAddressType, BluetoothError> {
+    BluetoothAddress::parse(address).unwrap().address_type(true)
+}
+
+#[test]
+fn parse_separators() {
+    let address = BluetoothAddress::new(BYTES);
+    assert_eq!(BluetoothAddress::parse("01:2A:00:4D:00:04"), Ok(address));
+    assert_eq!(BluetoothAddress::parse("01-2A-00-4D-00-04"), Ok(address));
+    assert_eq!(BluetoothAddress::parse(" 01:2A:00:4D:00:04\n"), Ok(address));
+    assert_eq!("01-2a-00-4d-00-04".parse::<BluetoothAddress>(), Ok(address));
+    assert_eq!(address.bytes(), BYTES);
+    // Formatting always uses ':' and uppercase digits.
+    assert_eq!(BluetoothAddress::parse("01-2a-00-4d-00-04").unwrap().to_string(), "01:2A:00:4D:00:04");
+}
+
+#[test]
+fn equality_ignores_case() {
+    let upper = BluetoothAddress::parse("AB:CD:EF:01:23:45").unwrap();
+    assert_eq!(BluetoothAddress::parse("ab:cd:ef:01:23:45"), Ok(upper));
+    assert_eq!(BluetoothAddress::parse("aB:Cd:eF:01:23:45"), Ok(upper));
+    assert_eq!(BluetoothAddress::parse("ab-CD-ef-01-23-45"), Ok(upper));
+    assert!(BluetoothAddress::parse("ab:cd:ef:01:23:46").unwrap() != upper);
+}
+
+#[test]
+fn malformed_addresses() {
+    for address in &["",
+                     "01:2A:00:4D:00",
+                     "01:2A:00:4D:00:04:05",
+                     "01:2A:00:4D:00:4",
+                     "01:2A:00:4D:00:004",
+                     "1:2A:00:4D:00:04",
+                     "012A004D0004",
+                     "01:2A:00:4D:00:04:",
+                     ":01:2A:00:4D:00:04",
+                     "01::2A:00:4D:00:04",
+                     "01:2A-00:4D:00:04",
+                     "01 2A 00 4D 00 04",
+                     "01:2A:00:4D:00:0G",
+                     "01:2A:00:4D:00:+4"] {
+        assert_eq!(BluetoothAddress::parse(address), Err(BluetoothError::InvalidArgument(address.to_string())));
+    }
+}
+
+#[test]
+fn public_addresses() {
+    // Whatever the two most significant bits are.
+    for address in &["01:2A:00:4D:00:04", "C0:00:00:00:00:00", "FF:FF:FF:FF:FF:FF", "00:00:00:00:00:00"] {
+        assert_eq!(BluetoothAddress::parse(address).unwrap().address_type(false),
+                   Ok(BluetoothAddressType::Public));
+    }
+}
+
+#[test]
+fn random_static_addresses() {
+    assert_eq!(address_type("C1:2A:00:4D:00:04"), Ok(BluetoothAddressType::RandomStatic));
+    assert_eq!(address_type("FF:FF:FF:FF:FF:FE"), Ok(BluetoothAddressType::RandomStatic));
+    assert_eq!(address_type("C0:00:00:00:00:01"), Ok(BluetoothAddressType::RandomStatic));
+    // The random part may be neither all 0 nor all 1.
+    assert!(matches!(address_type("C0:00:00:00:00:00"), Err(BluetoothError::InvalidArgument(_))));
+    assert!(matches!(address_type("FF:FF:FF:FF:FF:FF"), Err(BluetoothError::InvalidArgument(_))));
+}
+
+#[test]
+fn resolvable_private_addresses() {
+    assert_eq!(address_type("41:2A:00:4D:00:04"), Ok(BluetoothAddressType::RandomResolvablePrivate));
+    assert_eq!(address_type("40:00:00:00:00:00"), Ok(BluetoothAddressType::RandomResolvablePrivate));
+    assert_eq!(address_type("7F:FF:FF:FF:FF:FF"), Ok(BluetoothAddressType::RandomResolvablePrivate));
+}
+
+#[test]
+fn non_resolvable_private_addresses() {
+    assert_eq!(address_type("01:2A:00:4D:00:04"), Ok(BluetoothAddressType::RandomNonResolvablePrivate));
+    assert_eq!(address_type("00:00:00:00:00:01"), Ok(BluetoothAddressType::RandomNonResolvablePrivate));
+    assert_eq!(address_type("3F:FF:FF:FF:FF:FE"), Ok(BluetoothAddressType::RandomNonResolvablePrivate));
+    // The random part may be neither all 0 nor all 1.
+    assert!(matches!(address_type("00:00:00:00:00:00"), Err(BluetoothError::InvalidArgument(_))));
+    assert!(matches!(address_type("3F:FF:FF:FF:FF:FF"), Err(BluetoothError::InvalidArgument(_))));
+}
+
+#[test]
+fn reserved_random_addresses() {
+    // The most significant bits 0b10 are reserved.
+    assert_eq!(address_type("81:2A:00:4D:00:04"),
+               Err(BluetoothError::InvalidArgument(String::from("81:2A:00:4D:00:04"))));
+    assert!(matches!(address_type("BF:FF:FF:FF:FF:FE"), Err(BluetoothError::InvalidArgument(_))));
+}
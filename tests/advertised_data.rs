@@ -0,0 +1,164 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+#![cfg(feature = "bluetooth-test")]
+
+extern crate device;
+
+use device::backend::{AdapterBackend, CharacteristicBackend, DescriptorBackend, DeviceBackend, ServiceBackend};
+use device::bluetooth::{BluetoothAdapter, BluetoothDevice, BluetoothError, BluetoothUuid};
+use std::collections::HashMap;
+use std::sync::Arc;
+
+// A backend whose device reports its service data keyed by the given strings, and does not
+// implement the manufacturer data.
+#[derive(Debug)]
+struct ServiceDataAdapter {
+    device: Arc<ServiceDataDevice>,
+}
+
+#[derive(Debug)]
+struct ServiceDataDevice {
+    service_data: Option<HashMap<String, Vec<u8>>>,
+}
+
+// The services, characteristics and descriptors of the devices, which have no properties.
+#[derive(Debug)]
+struct StubAttribute {
+    id: String,
+}
+
+impl AdapterBackend for ServiceDataAdapter {
+    fn get_id(&self) -> String {
+        String::from("service_data_adapter")
+    }
+
+    fn create_device(self: Arc<Self>, _id: String) -> Arc<dyn DeviceBackend> {
+        self.device.clone()
+    }
+
+    fn get_device_list(&self) -> Result<Vec<String>, BluetoothError> {
+        Ok(vec![String::from("service_data_device")])
+    }
+}
+
+impl DeviceBackend for ServiceDataDevice {
+    fn get_id(&self) -> String {
+        String::from("service_data_device")
+    }
+
+    fn create_service(self: Arc<Self>, id: String) -> Arc<dyn ServiceBackend> {
+        Arc::new(StubAttribute { id })
+    }
+
+    fn get_service_data(&self) -> Result<HashMap<String, Vec<u8>>, BluetoothError> {
+        self.service_data.clone().ok_or(BluetoothError::NotSupported)
+    }
+}
+
+impl ServiceBackend for StubAttribute {
+    fn get_id(&self) -> String {
+        self.id.clone()
+    }
+
+    fn create_characteristic(self: Arc<Self>, id: String) -> Arc<dyn CharacteristicBackend> {
+        Arc::new(StubAttribute { id })
+    }
+}
+
+impl CharacteristicBackend for StubAttribute {
+    fn get_id(&self) -> String {
+        self.id.clone()
+    }
+
+    fn create_descriptor(self: Arc<Self>, id: String) -> Arc<dyn DescriptorBackend> {
+        Arc::new(StubAttribute { id })
+    }
+}
+
+impl DescriptorBackend for StubAttribute {
+    fn get_id(&self) -> String {
+        self.id.clone()
+    }
+}
+
+fn create_mock_device() -> BluetoothDevice {
+    let adapter = BluetoothAdapter::init_mock().unwrap();
+    BluetoothDevice::create_mock_device(adapter, String::from("device")).unwrap()
+}
+
+fn create_backend_device(service_data: Option<&[(&str, &[u8])]>) -> BluetoothDevice {
+    let service_data = service_data.map(|service_data| {
+        service_data.iter().map(|&(uuid, data)| (uuid.to_owned(), data.to_vec())).collect()
+    });
+    let backend = ServiceDataAdapter {
+        device: Arc::new(ServiceDataDevice { service_data }),
+    };
+    let adapter = BluetoothAdapter::init_with_backend(Arc::new(backend));
+    adapter.get_devices().unwrap().remove(0)
+}
+
+#[test]
+fn manufacturer_data_round_trip() {
+    let device = create_mock_device();
+    let mut manufacturer_data = HashMap::new();
+    manufacturer_data.insert(0x004C, vec![0x02, 0x15]);
+    manufacturer_data.insert(0xFFFF, vec![]);
+    manufacturer_data.insert(0, vec![1, 2, 3]);
+    device.set_manufacturer_data(manufacturer_data.clone()).unwrap();
+    assert_eq!(device.get_manufacturer_data(), Ok(manufacturer_data));
+
+    // The data of the last advertisement replaces the previous one.
+    let mut replaced = HashMap::new();
+    replaced.insert(17, vec![4]);
+    device.set_manufacturer_data(replaced.clone()).unwrap();
+    assert_eq!(device.get_manufacturer_data(), Ok(replaced));
+}
+
+#[test]
+fn service_data_round_trip() {
+    let device = create_mock_device();
+    let mut service_data = HashMap::new();
+    service_data.insert(BluetoothUuid::from_u16(0x180F), vec![100]);
+    service_data.insert(BluetoothUuid::from_u32(0x1234_5678), vec![]);
+    service_data.insert(BluetoothUuid::parse("f000aa00-0451-4000-b000-000000000000").unwrap(), vec![1, 2]);
+    device.set_service_data(service_data.clone()).unwrap();
+    assert_eq!(device.get_service_data(), Ok(service_data));
+}
+
+#[test]
+fn empty_maps() {
+    let device = create_mock_device();
+    // A mock device without advertisement data has no maps at all.
+    assert!(device.get_manufacturer_data().is_err());
+    assert!(device.get_service_data().is_err());
+    device.set_manufacturer_data(HashMap::new()).unwrap();
+    device.set_service_data(HashMap::new()).unwrap();
+    assert_eq!(device.get_manufacturer_data(), Ok(HashMap::new()));
+    assert_eq!(device.get_service_data(), Ok(HashMap::new()));
+}
+
+#[test]
+fn service_data_uuids_of_the_backend_are_parsed() {
+    let device = create_backend_device(Some(&[("0000180f-0000-1000-8000-00805F9B34FB", &[100]), ("180d", &[1])]));
+    let mut service_data = HashMap::new();
+    service_data.insert(BluetoothUuid::from_u16(0x180F), vec![100]);
+    service_data.insert(BluetoothUuid::from_u16(0x180D), vec![1]);
+    assert_eq!(device.get_service_data(), Ok(service_data));
+
+    let device = create_backend_device(Some(&[("180f", &[100]), ("battery", &[1])]));
+    assert_eq!(device.get_service_data(), Err(BluetoothError::InvalidArgument(String::from("battery"))));
+    let device = create_backend_device(Some(&[]));
+    assert_eq!(device.get_service_data(), Ok(HashMap::new()));
+}
+
+#[test]
+fn not_supported() {
+    // The backend implements neither the manufacturer data, nor the test functions.
+    let device = create_backend_device(None);
+    assert_eq!(device.get_manufacturer_data(), Err(BluetoothError::NotSupported));
+    assert_eq!(device.get_service_data(), Err(BluetoothError::NotSupported));
+    assert_eq!(device.set_manufacturer_data(HashMap::new()), Err(BluetoothError::NotSupported));
+    assert_eq!(device.set_service_data(HashMap::new()), Err(BluetoothError::NotSupported));
+}
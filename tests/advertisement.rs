@@ -0,0 +1,197 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+extern crate device;
+
+use device::advertisement::{AdStructure, AdvertisementData};
+use device::uuid::BluetoothUuid;
+
+// A fixed seed xorshift generator, so a failing case can be reproduced.
+struct Random(u64);
+
+impl Random {
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    fn below(&mut self, bound: usize) -> usize {
+        (self.next() % bound as u64) as usize
+    }
+
+    fn bytes(&mut self, length: usize) -> Vec<u8> {
+        (0..length).map(|_| self.next() as u8).collect()
+    }
+
+    fn text(&mut self, length: usize) -> String {
+        let characters = ['a', 'Z', '0', ' ', '-', 'é', '♥'];
+        (0..length).map(|_| characters[self.below(characters.len())]).collect()
+    }
+}
+
+fn random_structure(random: &mut Random) -> AdStructure {
+    let complete = random.below(2) == 1;
+    match random.below(13) {
+        0 => AdStructure::Flags(random.next() as u8),
+        1 => AdStructure::Uuids16 {
+            complete,
+            uuids: (0..random.below(8)).map(|_| random.next() as u16).collect(),
+        },
+        2 => AdStructure::Uuids32 {
+            complete,
+            uuids: (0..random.below(5)).map(|_| random.next() as u32).collect(),
+        },
+        3 => AdStructure::Uuids128 {
+            complete,
+            uuids: (0..random.below(3))
+                .map(|_| BluetoothUuid::from_u128(((random.next() as u128) << 64) | random.next() as u128))
+                .collect(),
+        },
+        4 => {
+            let length = random.below(20);
+            AdStructure::LocalName {
+                complete,
+                name: random.text(length),
+            }
+        },
+        5 => AdStructure::TxPowerLevel(random.next() as i8),
+        6 => {
+            let length = random.below(10);
+            AdStructure::ServiceData16(random.next() as u16, random.bytes(length))
+        },
+        7 => {
+            let length = random.below(10);
+            AdStructure::ServiceData32(random.next() as u32, random.bytes(length))
+        },
+        8 => {
+            let length = random.below(10);
+            AdStructure::ServiceData128(BluetoothUuid::from_u128(random.next() as u128), random.bytes(length))
+        },
+        9 => AdStructure::Appearance(random.next() as u16),
+        10 => {
+            let length = random.below(20);
+            AdStructure::Uri(format!("\u{17}{}", random.text(length)))
+        },
+        11 => {
+            let length = random.below(10);
+            AdStructure::ManufacturerData(random.next() as u16, random.bytes(length))
+        },
+        _ => {
+            // AD types without a variant of their own.
+            let ad_types = [0x0D, 0x10, 0x1B, 0x2D, 0x30];
+            let length = random.below(10);
+            AdStructure::Other {
+                ad_type: ad_types[random.below(ad_types.len())],
+                data: random.bytes(length),
+            }
+        },
+    }
+}
+
+// Length prefixed chunks of known and unknown AD types, with random content, so most of
+// them parse, but some are malformed, truncated or followed by padding.
+fn random_bytes(random: &mut Random) -> Vec<u8> {
+    let ad_types = [0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08, 0x09, 0x0A,
+                    0x16, 0x19, 0x20, 0x21, 0x24, 0xFF, 0x0D, 0x2D];
+    let mut bytes = vec![];
+    for _ in 0..random.below(6) {
+        let length = random.below(20);
+        bytes.push(length as u8);
+        bytes.push(ad_types[random.below(ad_types.len())]);
+        let data = random.bytes(length.saturating_sub(1));
+        bytes.extend(data);
+    }
+    let length = bytes.len();
+    bytes.truncate(length - random.below(3).min(length));
+    bytes
+}
+
+#[test]
+fn parse_known_advertisement() {
+    let bytes = [
+        0x02, 0x01, 0x06,
+        0x05, 0x03, 0x0D, 0x18, 0x0F, 0x18,
+        0x09, 0x09, b'H', b'e', b'a', b'r', b't', b' ', b'R', b'a',
+        0x02, 0x0A, 0xF4,
+        0x03, 0x19, 0x41, 0x03,
+        0x05, 0xFF, 0x4C, 0x00, 0x01, 0x02,
+        0x04, 0x16, 0x0F, 0x18, 0x64,
+        0x0D, 0x24, 0x17, b'/', b'/', b'e', b'x', b'a', b'm', b'p', b'l', b'e', b'.', b'o',
+        0x00, 0x00, 0x00,
+    ];
+    let data = AdvertisementData::parse(&bytes).unwrap();
+    assert_eq!(data.get_flags(), Some(0x06));
+    assert_eq!(data.get_uuids(), vec![BluetoothUuid::from_u16(0x180D), BluetoothUuid::from_u16(0x180F)]);
+    assert_eq!(data.get_local_name(), Some(String::from("Heart Ra")));
+    assert_eq!(data.get_tx_power_level(), Some(-12));
+    assert_eq!(data.get_appearance(), Some(0x0341));
+    assert_eq!(data.get_manufacturer_data().get(&0x004C), Some(&vec![0x01, 0x02]));
+    assert_eq!(data.get_service_data().get(&BluetoothUuid::from_u16(0x180F)), Some(&vec![0x64]));
+    assert_eq!(data.get_uris(), vec![String::from("https://example.o")]);
+    // The zero padding is not written back.
+    assert_eq!(data.to_bytes().unwrap(), &bytes[..bytes.len() - 3]);
+}
+
+#[test]
+fn parse_128_bit_uuids_least_significant_octet_first() {
+    let uuid = BluetoothUuid::parse("f000aa00-0451-4000-b000-000000000000").unwrap();
+    let mut bytes = vec![0x11, 0x07];
+    bytes.extend(uuid.to_bytes().iter().rev());
+    let data = AdvertisementData::parse(&bytes).unwrap();
+    assert_eq!(data.get_uuids(), vec![uuid]);
+    assert_eq!(data.to_bytes().unwrap(), bytes);
+}
+
+#[test]
+fn malformed_structures_are_kept_as_other() {
+    // A flags structure with two bytes, and a local name which is not UTF-8.
+    let bytes = [0x03, 0x01, 0x06, 0x00, 0x03, 0x09, 0xC3, 0x28];
+    let data = AdvertisementData::parse(&bytes).unwrap();
+    assert_eq!(data.structures, vec![
+        AdStructure::Other { ad_type: 0x01, data: vec![0x06, 0x00] },
+        AdStructure::Other { ad_type: 0x09, data: vec![0xC3, 0x28] },
+    ]);
+    assert_eq!(data.get_flags(), None);
+    assert_eq!(data.to_bytes().unwrap(), bytes);
+}
+
+#[test]
+fn truncated_structure_is_an_error() {
+    assert!(AdvertisementData::parse(&[0x02, 0x01, 0x06, 0x05, 0xFF, 0x4C]).is_err());
+}
+
+#[test]
+fn oversized_structure_is_not_written() {
+    let data = AdvertisementData::new(vec![AdStructure::ManufacturerData(0x004C, vec![0; 253])]);
+    assert!(data.to_bytes().is_err());
+}
+
+#[test]
+fn fuzz_random_bytes_round_trip() {
+    let mut random = Random(0x2545_F491_4F6C_DD1D);
+    for _ in 0..10000 {
+        let bytes = random_bytes(&mut random);
+        let data = match AdvertisementData::parse(&bytes) {
+            Ok(data) => data,
+            Err(_) => continue,
+        };
+        let written = data.to_bytes().unwrap();
+        assert!(bytes.starts_with(&written), "{:?} was written as {:?}", bytes, written);
+        assert!(bytes[written.len()..].first().is_none_or(|length| *length == 0));
+        assert_eq!(AdvertisementData::parse(&written).unwrap(), data);
+    }
+}
+
+#[test]
+fn fuzz_random_structures_round_trip() {
+    let mut random = Random(0x9E37_79B9_7F4A_7C15);
+    for _ in 0..10000 {
+        let structures = (0..random.below(6)).map(|_| random_structure(&mut random)).collect();
+        let data = AdvertisementData::new(structures);
+        let bytes = data.to_bytes().unwrap();
+        assert_eq!(AdvertisementData::parse(&bytes).unwrap(), data, "{:?}", bytes);
+    }
+}
@@ -0,0 +1,80 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+extern crate device;
+
+use device::assigned_numbers;
+use device::error::BluetoothError;
+use device::uuid::BluetoothUuid;
+
+#[test]
+fn names_resolve_to_uuids() {
+    assert_eq!(assigned_numbers::service_uuid("heart_rate"), Some(BluetoothUuid::from_u16(0x180D)));
+    assert_eq!(assigned_numbers::characteristic_uuid("battery_level"), Some(BluetoothUuid::from_u16(0x2A19)));
+    assert_eq!(assigned_numbers::descriptor_uuid("gatt.client_characteristic_configuration"),
+               Some(BluetoothUuid::from_u16(0x2902)));
+    assert_eq!(assigned_numbers::service_uuid("battery_level"), None);
+    assert_eq!(assigned_numbers::service_uuid("no_such_service"), None);
+}
+
+#[test]
+fn uuids_resolve_to_names() {
+    let heart_rate = BluetoothUuid::parse("0000180d-0000-1000-8000-00805f9b34fb").unwrap();
+    assert_eq!(assigned_numbers::service_name(&heart_rate), Some("heart_rate"));
+    assert_eq!(assigned_numbers::characteristic_name(&BluetoothUuid::from_u16(0x2A37)),
+               Some("heart_rate_measurement"));
+    assert_eq!(assigned_numbers::descriptor_name(&BluetoothUuid::from_u16(0x2901)),
+               Some("gatt.characteristic_user_description"));
+    let vendor = BluetoothUuid::parse("f000aa00-0451-4000-b000-000000000000").unwrap();
+    assert_eq!(assigned_numbers::service_name(&vendor), None);
+}
+
+#[test]
+fn resolve_accepts_names_and_uuids() {
+    assert_eq!(assigned_numbers::resolve_service("heart_rate"), Ok(BluetoothUuid::from_u16(0x180D)));
+    assert_eq!(assigned_numbers::resolve_service("0000180f-0000-1000-8000-00805f9b34fb"),
+               Ok(BluetoothUuid::from_u16(0x180F)));
+    assert_eq!(assigned_numbers::resolve_characteristic("gap.device_name"), Ok(BluetoothUuid::from_u16(0x2A00)));
+    assert!(assigned_numbers::resolve_descriptor("heart_rate").is_err());
+}
+
+#[test]
+fn resolve_rejects_aliases_and_uppercase_uuids() {
+    let invalid = ["180d",
+                   "0x180D",
+                   "0x2A37",
+                   "2a37",
+                   "00002a37",
+                   "0000180D-0000-1000-8000-00805F9B34FB",
+                   "0000180d00001000800000805f9b34fb",
+                   "0000180d-0000-1000-8000-00805f9b34f",
+                   "0000180d-0000-1000-8000-00805f9b34fbb",
+                   "0000180d0-000-1000-8000-00805f9b34fb",
+                   "0000180g-0000-1000-8000-00805f9b34fb",
+                   " 0000180d-0000-1000-8000-00805f9b34fb",
+                   "Heart_Rate",
+                   ""];
+    for name in &invalid {
+        assert_eq!(assigned_numbers::resolve_service(name), Err(BluetoothError::InvalidArgument(name.to_string())));
+        assert!(assigned_numbers::resolve_characteristic(name).is_err(), "{}", name);
+        assert!(assigned_numbers::resolve_descriptor(name).is_err(), "{}", name);
+    }
+    let vendor = "f000aa00-0451-4000-b000-000000000000";
+    assert_eq!(assigned_numbers::resolve_characteristic(vendor), BluetoothUuid::parse(vendor));
+    assert_eq!(assigned_numbers::resolve_descriptor("00002902-0000-1000-8000-00805f9b34fb"),
+               Ok(BluetoothUuid::from_u16(0x2902)));
+}
+
+#[test]
+fn characteristics_after_0x2a6f() {
+    for &(name, uuid) in &[("true_wind_speed", 0x2A70),
+                           ("body_composition_measurement", 0x2A9C),
+                           ("weight_measurement", 0x2A9D),
+                           ("cgm_measurement", 0x2AA7),
+                           ("fitness_machine_feature", 0x2ACC),
+                           ("reconnection_configuration_control_point", 0x2B1F)] {
+        assert_eq!(assigned_numbers::resolve_characteristic(name), Ok(BluetoothUuid::from_u16(uuid)));
+        assert_eq!(assigned_numbers::characteristic_name(&BluetoothUuid::from_u16(uuid)), Some(name));
+    }
+}
@@ -0,0 +1,222 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+#![cfg(feature = "bluetooth-test")]
+
+extern crate device;
+
+use device::async_bluetooth::{AsyncBluetoothAdapter, AsyncBluetoothDevice, AsyncBluetoothGATTCharacteristic};
+use device::bluetooth::{BluetoothAdapter, BluetoothDevice, BluetoothError, BluetoothGATTCharacteristic};
+use device::bluetooth::{BluetoothGATTService, BluetoothUuid, WriteType};
+use device::discovery_filter::DiscoveryFilter;
+use device::error::ATT_INVALID_OFFSET;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::task::{Context, Poll, Wake, Waker};
+use std::thread::{self, Thread};
+use std::time::{Duration, Instant};
+
+// A single future executor, which parks the thread until the future wakes it.
+struct ThreadWaker {
+    thread: Thread,
+    wakeups: AtomicUsize,
+}
+
+impl Wake for ThreadWaker {
+    fn wake(self: Arc<Self>) {
+        self.wakeups.fetch_add(1, Ordering::SeqCst);
+        self.thread.unpark();
+    }
+}
+
+struct Execution<T> {
+    output: T,
+    // Whether the first poll returned `Pending`.
+    pending: bool,
+    wakeups: usize,
+}
+
+fn block_on<F: Future>(future: F) -> Execution<F::Output> {
+    let thread_waker = Arc::new(ThreadWaker {
+        thread: thread::current(),
+        wakeups: AtomicUsize::new(0),
+    });
+    let waker = Waker::from(thread_waker.clone());
+    let mut context = Context::from_waker(&waker);
+    let mut future = Box::pin(future);
+    let mut pending = None;
+    loop {
+        match Pin::as_mut(&mut future).poll(&mut context) {
+            Poll::Ready(output) => {
+                return Execution {
+                    output,
+                    pending: pending.unwrap_or(false),
+                    wakeups: thread_waker.wakeups.load(Ordering::SeqCst),
+                };
+            },
+            Poll::Pending => {
+                pending.get_or_insert(true);
+                thread::park();
+            },
+        }
+    }
+}
+
+fn create_characteristic(flags: &[&str]) -> (BluetoothAdapter, BluetoothDevice, AsyncBluetoothGATTCharacteristic) {
+    let adapter = BluetoothAdapter::init_mock().unwrap();
+    let device = BluetoothDevice::create_mock_device(adapter.clone(), String::from("device")).unwrap();
+    device.set_connected(true).unwrap();
+    let service = BluetoothGATTService::create_mock_service(device.clone(), String::from("service")).unwrap();
+    let characteristic =
+        BluetoothGATTCharacteristic::create_mock_characteristic(service, String::from("characteristic")).unwrap();
+    characteristic.set_flags(flags.iter().map(|flag| String::from(*flag)).collect()).unwrap();
+    (adapter, device, AsyncBluetoothGATTCharacteristic::new(characteristic))
+}
+
+#[test]
+fn mock_delay_defers_the_result_and_wakes_the_task() {
+    let adapter = BluetoothAdapter::init_mock().unwrap();
+    adapter.set_name(String::from("adapter")).unwrap();
+    adapter.set_mock_delay(Duration::from_millis(100)).unwrap();
+    let async_adapter = AsyncBluetoothAdapter::new(adapter);
+
+    let start = Instant::now();
+    let execution = block_on(async_adapter.get_name());
+    assert!(start.elapsed() >= Duration::from_millis(100));
+    assert_eq!(execution.output, Ok(String::from("adapter")));
+    assert!(execution.pending);
+    assert_eq!(execution.wakeups, 1);
+}
+
+#[test]
+fn results_without_a_delay() {
+    let (_adapter, _device, characteristic) = create_characteristic(&["read"]);
+    characteristic.get_inner().set_value(vec![1, 2, 3]).unwrap();
+    assert_eq!(block_on(characteristic.read_value()).output, Ok(vec![1, 2, 3]));
+    assert_eq!(block_on(characteristic.read_value_at(1)).output, Ok(vec![2, 3]));
+}
+
+#[test]
+fn errors_are_propagated() {
+    let (adapter, device, characteristic) = create_characteristic(&["read"]);
+    adapter.set_mock_delay(Duration::from_millis(10)).unwrap();
+    characteristic.get_inner().set_value(vec![1]).unwrap();
+    let execution = block_on(characteristic.read_value_at(5));
+    assert_eq!(execution.output, Err(BluetoothError::GattError(ATT_INVALID_OFFSET)));
+    assert_eq!(execution.wakeups, 1);
+    let write = characteristic.write_value_with_type(vec![1], WriteType::WithResponse);
+    assert_eq!(block_on(write).output, Err(BluetoothError::NotSupported));
+
+    device.set_connected(false).unwrap();
+    let async_adapter = AsyncBluetoothAdapter::new(adapter);
+    let async_device = block_on(async_adapter.get_device_by_id(String::from("device"))).output.unwrap().unwrap();
+    assert_eq!(block_on(async_device.get_gatt_services()).output.err(), Some(BluetoothError::NotConnected));
+    assert_eq!(block_on(async_device.get_mtu()).output, Err(BluetoothError::NotConnected));
+}
+
+#[test]
+fn mtu_and_long_writes() {
+    let (adapter, device, characteristic) = create_characteristic(&["read", "write", "reliable-write"]);
+    let async_device = block_on(AsyncBluetoothAdapter::new(adapter).get_devices()).output.unwrap().remove(0);
+    assert_eq!(async_device.get_id(), device.get_id());
+    assert_eq!(block_on(async_device.get_mtu()).output, Ok(23));
+    assert_eq!(block_on(async_device.request_mtu(50)).output, Ok(50));
+
+    let value: Vec<u8> = (0..100).collect();
+    block_on(characteristic.write_long_value(value.clone())).output.unwrap();
+    assert_eq!(characteristic.get_inner().get_value(), Ok(value.clone()));
+
+    let mut reliable_write = block_on(characteristic.begin_reliable_write()).output.unwrap();
+    reliable_write.prepare_write(0, vec![7, 7]).unwrap();
+    assert_eq!(reliable_write.get_prepared_writes(), &[(0, vec![7, 7])]);
+    block_on(reliable_write.execute()).output.unwrap();
+    let mut value = value;
+    // The bytes after the prepared part keep their value.
+    value[..2].copy_from_slice(&[7, 7]);
+    assert_eq!(characteristic.get_inner().get_value(), Ok(value));
+}
+
+#[test]
+fn pairing() {
+    let (adapter, device, _characteristic) = create_characteristic(&[]);
+    let async_device = block_on(AsyncBluetoothAdapter::new(adapter).get_devices()).output.unwrap().remove(0);
+    // Without an agent, the mock device pairs with Just Works.
+    assert_eq!(block_on(async_device.pair()).output, Ok(()));
+    assert_eq!(device.is_paired(), Ok(true));
+    assert_eq!(block_on(async_device.pair()).output, Err(BluetoothError::InvalidState));
+}
+
+fn ids(devices: Vec<AsyncBluetoothDevice>) -> Vec<String> {
+    let mut ids: Vec<String> = devices.iter().map(AsyncBluetoothDevice::get_id).collect();
+    ids.sort();
+    ids
+}
+
+#[test]
+fn discovery_session() {
+    let adapter = BluetoothAdapter::init_mock().unwrap();
+    for &(id, service) in &[("heart_rate", 0x180D), ("battery", 0x180F)] {
+        let device = BluetoothDevice::create_mock_device(adapter.clone(), String::from(id)).unwrap();
+        device.set_uuids(vec![BluetoothUuid::from_u16(service)]).unwrap();
+    }
+    adapter.set_mock_delay(Duration::from_millis(10)).unwrap();
+    let async_adapter = AsyncBluetoothAdapter::new(adapter.clone());
+    let mut filter = DiscoveryFilter::new();
+    filter.uuids = vec![BluetoothUuid::from_u16(0x180D)];
+    let execution = block_on(async_adapter.create_discovery_session_with_filter(filter.clone()));
+    assert!(execution.pending);
+    let session = execution.output.unwrap();
+    assert_eq!(session.get_filter(), &filter);
+
+    assert_eq!(block_on(session.start_discovery()).output, Ok(()));
+    assert_eq!(block_on(session.get_discovered_devices()).output.map(ids), Ok(vec![String::from("heart_rate")]));
+    assert_eq!(block_on(session.get_new_devices()).output.map(ids), Ok(vec![String::from("heart_rate")]));
+    assert_eq!(block_on(session.get_new_devices()).output.map(ids), Ok(vec![]));
+    adapter.set_can_stop_discovery(false).unwrap();
+    assert!(matches!(block_on(session.stop_discovery()).output, Err(BluetoothError::Backend(_))));
+
+    // The calls run in order on the session's thread.
+    let session = block_on(async_adapter.create_discovery_session()).output.unwrap();
+    let first = session.get_new_devices();
+    let second = session.get_new_devices();
+    assert_eq!(block_on(second).output.map(ids), Ok(vec![]));
+    assert_eq!(block_on(first).output.map(ids), Ok(vec![String::from("battery"), String::from("heart_rate")]));
+}
+
+#[test]
+fn invalid_discovery_filter() {
+    let async_adapter = AsyncBluetoothAdapter::new(BluetoothAdapter::init_mock().unwrap());
+    let mut filter = DiscoveryFilter::new();
+    filter.rssi = Some(-70);
+    filter.pathloss = Some(10);
+    let session = block_on(async_adapter.create_discovery_session_with_filter(filter)).output;
+    assert!(matches!(session, Err(BluetoothError::InvalidArgument(_))));
+}
+
+#[test]
+fn notifications() {
+    let (_adapter, _device, characteristic) = create_characteristic(&["notify"]);
+    characteristic.get_inner().set_value(vec![0]).unwrap();
+    block_on(characteristic.start_notify()).output.unwrap();
+    let mut notifications = characteristic.get_notifications().unwrap();
+
+    let inner = characteristic.get_inner().clone();
+    let pusher = thread::spawn(move || {
+        thread::sleep(Duration::from_millis(50));
+        inner.push_notification(vec![1]).unwrap();
+        inner.push_notification(vec![2]).unwrap();
+    });
+    let execution = block_on(notifications.recv());
+    assert_eq!(execution.output, Some(vec![1]));
+    assert!(execution.pending);
+    pusher.join().unwrap();
+    assert_eq!(block_on(notifications.recv()).output, Some(vec![2]));
+
+    block_on(characteristic.stop_notify()).output.unwrap();
+    assert_eq!(block_on(notifications.recv()).output, None);
+    assert_eq!(block_on(notifications.recv()).output, None);
+    assert_eq!(characteristic.get_notifications().err(), Some(BluetoothError::InvalidState));
+}
@@ -0,0 +1,253 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+extern crate device;
+
+use device::backend::{self, AdapterBackend, CharacteristicBackend, DescriptorBackend};
+use device::backend::{DeviceBackend, ServiceBackend};
+use device::bluetooth::{BluetoothAdapter, BluetoothAddress, BluetoothDevice, BluetoothError, BluetoothUuid, WriteType};
+use device::pairing::{AgentRequest, IoCapability, PairingAgent, ScriptedAgent};
+use std::sync::{Arc, Mutex};
+
+const PASSKEY: u32 = 123456;
+
+// A backend with a single connected device, exposing the Battery Level characteristic of the
+// Battery service. The device pairs with numeric comparison, and exchanges an ATT_MTU up to 247.
+#[derive(Debug)]
+struct BatteryAdapter {
+    device: Arc<BatteryDevice>,
+}
+
+#[derive(Debug)]
+struct BatteryDevice {
+    mtu: Mutex<u16>,
+    paired: Mutex<bool>,
+    level: Arc<BatteryLevel>,
+}
+
+#[derive(Debug)]
+struct BatteryService {
+    level: Arc<BatteryLevel>,
+}
+
+#[derive(Debug)]
+struct BatteryLevel {
+    value: Mutex<Vec<u8>>,
+    write_types: Mutex<Vec<WriteType>>,
+}
+
+#[derive(Debug)]
+struct NoDescriptor;
+
+impl AdapterBackend for BatteryAdapter {
+    fn get_id(&self) -> String {
+        String::from("battery_adapter")
+    }
+
+    fn create_device(self: Arc<Self>, _id: String) -> Arc<dyn DeviceBackend> {
+        self.device.clone()
+    }
+
+    fn get_device_list(&self) -> Result<Vec<String>, BluetoothError> {
+        Ok(vec![String::from("battery_device")])
+    }
+
+    fn is_powered(&self) -> Result<bool, BluetoothError> {
+        Ok(true)
+    }
+
+    fn register_agent(&self, _agent: Arc<dyn PairingAgent>) -> Result<(), BluetoothError> {
+        Ok(())
+    }
+}
+
+impl DeviceBackend for BatteryDevice {
+    fn get_id(&self) -> String {
+        String::from("battery_device")
+    }
+
+    fn create_service(self: Arc<Self>, _id: String) -> Arc<dyn ServiceBackend> {
+        Arc::new(BatteryService {
+            level: self.level.clone(),
+        })
+    }
+
+    fn get_address(&self) -> Result<String, BluetoothError> {
+        Ok(String::from("01:02:03:04:05:06"))
+    }
+
+    fn get_name(&self) -> Result<String, BluetoothError> {
+        Ok(String::from("Battery"))
+    }
+
+    fn get_gatt_services(&self) -> Result<Vec<String>, BluetoothError> {
+        Ok(vec![String::from("battery_service")])
+    }
+
+    fn is_connected(&self) -> Result<bool, BluetoothError> {
+        Ok(true)
+    }
+
+    fn is_paired(&self) -> Result<bool, BluetoothError> {
+        Ok(*self.paired.lock().unwrap())
+    }
+
+    fn get_mtu(&self) -> Result<u16, BluetoothError> {
+        Ok(*self.mtu.lock().unwrap())
+    }
+
+    fn request_mtu(&self, mtu: u16) -> Result<u16, BluetoothError> {
+        let mut current = self.mtu.lock().unwrap();
+        *current = mtu.min(247);
+        Ok(*current)
+    }
+
+    fn pair_with_agent(&self, device: &BluetoothDevice, agent: Option<Arc<dyn PairingAgent>>)
+                       -> Result<(), BluetoothError> {
+        let agent = agent.ok_or(BluetoothError::PermissionDenied)?;
+        agent.request_confirmation(device, PASSKEY)?;
+        *self.paired.lock().unwrap() = true;
+        Ok(())
+    }
+}
+
+impl ServiceBackend for BatteryService {
+    fn get_id(&self) -> String {
+        String::from("battery_service")
+    }
+
+    fn create_characteristic(self: Arc<Self>, _id: String) -> Arc<dyn CharacteristicBackend> {
+        self.level.clone()
+    }
+
+    fn get_uuid(&self) -> Result<String, BluetoothError> {
+        Ok(BluetoothUuid::from_u16(0x180F).to_string())
+    }
+
+    fn get_gatt_characteristics(&self) -> Result<Vec<String>, BluetoothError> {
+        Ok(vec![String::from("battery_level")])
+    }
+}
+
+impl CharacteristicBackend for BatteryLevel {
+    fn get_id(&self) -> String {
+        String::from("battery_level")
+    }
+
+    fn create_descriptor(self: Arc<Self>, _id: String) -> Arc<dyn DescriptorBackend> {
+        Arc::new(NoDescriptor)
+    }
+
+    fn get_uuid(&self) -> Result<String, BluetoothError> {
+        Ok(BluetoothUuid::from_u16(0x2A19).to_string())
+    }
+
+    fn get_flags(&self) -> Result<Vec<String>, BluetoothError> {
+        Ok(vec![String::from("read"), String::from("write"), String::from("write-without-response")])
+    }
+
+    fn read_value(&self) -> Result<Vec<u8>, BluetoothError> {
+        Ok(self.value.lock().unwrap().clone())
+    }
+
+    fn write_value(&self, values: Vec<u8>) -> Result<(), BluetoothError> {
+        *self.value.lock().unwrap() = values;
+        Ok(())
+    }
+
+    fn write_value_with_type(&self, values: Vec<u8>, write_type: WriteType) -> Result<(), BluetoothError> {
+        self.write_types.lock().unwrap().push(write_type);
+        self.write_value(values)
+    }
+}
+
+impl DescriptorBackend for NoDescriptor {
+    fn get_id(&self) -> String {
+        String::new()
+    }
+}
+
+fn battery_adapter() -> Arc<BatteryAdapter> {
+    Arc::new(BatteryAdapter {
+        device: Arc::new(BatteryDevice {
+            mtu: Mutex::new(23),
+            paired: Mutex::new(false),
+            level: Arc::new(BatteryLevel {
+                value: Mutex::new(vec![100]),
+                write_types: Mutex::new(vec![]),
+            }),
+        }),
+    })
+}
+
+fn create_battery_adapter() -> Result<Arc<dyn AdapterBackend>, BluetoothError> {
+    Ok(battery_adapter())
+}
+
+#[test]
+fn adapter_with_backend() {
+    let adapter = BluetoothAdapter::init_with_backend(battery_adapter());
+    assert_eq!(adapter.get_id(), "battery_adapter");
+    assert_eq!(adapter.is_powered(), Ok(true));
+    let address = BluetoothAddress::parse("01:02:03:04:05:06").unwrap();
+    let device = adapter.get_device(address).unwrap().unwrap();
+    assert_eq!(device.get_name(), Ok(String::from("Battery")));
+
+    let services = device.get_gatt_services().unwrap();
+    assert_eq!(services[0].get_uuid(), Ok(BluetoothUuid::from_u16(0x180F)));
+    let characteristic = services[0].get_gatt_characteristics().unwrap().remove(0);
+    assert_eq!(characteristic.read_value(), Ok(vec![100]));
+    characteristic.write_value(vec![42]).unwrap();
+    assert_eq!(characteristic.read_value(), Ok(vec![42]));
+}
+
+#[test]
+fn calls_the_backend_does_not_implement() {
+    let adapter = BluetoothAdapter::init_with_backend(battery_adapter());
+    assert_eq!(adapter.get_name(), Err(BluetoothError::NotSupported));
+    assert!(adapter.create_discovery_session().is_err());
+    let device = adapter.get_devices().unwrap().remove(0);
+    assert_eq!(device.connect(), Err(BluetoothError::NotSupported));
+    assert_eq!(device.cancel_pairing(), Err(BluetoothError::NotSupported));
+}
+
+#[test]
+fn mtu_pairing_and_write_type_of_the_backend() {
+    let backend = battery_adapter();
+    let adapter = BluetoothAdapter::init_with_backend(backend.clone());
+    let device = adapter.get_devices().unwrap().remove(0);
+    assert_eq!(device.get_mtu(), Ok(23));
+    assert_eq!(device.request_mtu(512), Ok(247));
+    assert_eq!(device.get_mtu(), Ok(247));
+
+    assert_eq!(device.pair(), Err(BluetoothError::PermissionDenied));
+    let mut agent = ScriptedAgent::new(IoCapability::DisplayYesNo);
+    agent.confirm = true;
+    let agent = Arc::new(agent);
+    adapter.register_agent(agent.clone()).unwrap();
+    device.pair().unwrap();
+    assert_eq!(device.is_paired(), Ok(true));
+    assert_eq!(agent.get_requests(), vec![AgentRequest::RequestConfirmation(device.get_id(), PASSKEY)]);
+
+    let characteristic = device.get_gatt_services().unwrap()[0].get_gatt_characteristics().unwrap().remove(0);
+    characteristic.write_value_with_type(vec![50], WriteType::WithoutResponse).unwrap();
+    characteristic.write_value_with_type(vec![49], WriteType::WithResponse).unwrap();
+    assert_eq!(characteristic.read_value(), Ok(vec![49]));
+    assert_eq!(*backend.device.level.write_types.lock().unwrap(),
+               vec![WriteType::WithoutResponse, WriteType::WithResponse]);
+}
+
+#[test]
+fn registered_backend() {
+    backend::register_backend("battery", create_battery_adapter).unwrap();
+    assert!(backend::get_registered_backends().contains(&String::from("battery")));
+    assert_eq!(backend::register_backend("battery", create_battery_adapter), Err(BluetoothError::InvalidState));
+
+    let adapter = BluetoothAdapter::init_registered_backend("battery").unwrap();
+    assert_eq!(adapter.get_devices().unwrap().len(), 1);
+
+    backend::unregister_backend("battery").unwrap();
+    assert_eq!(backend::unregister_backend("battery"), Err(BluetoothError::NotFound));
+    assert_eq!(BluetoothAdapter::init_registered_backend("battery").err(), Some(BluetoothError::NotFound));
+}
@@ -0,0 +1,224 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+#![cfg(feature = "bluetooth-test")]
+
+extern crate device;
+
+use device::blocklist::{Blocklist, BlocklistEntry};
+use device::bluetooth::{BluetoothAdapter, BluetoothDevice, BluetoothError, BluetoothGATTCharacteristic};
+use device::bluetooth::{BluetoothGATTDescriptor, BluetoothGATTService, BluetoothUuid, WriteType};
+
+const SERVICE_UUID: &str = "0000180d-0000-1000-8000-00805f9b34fb";
+const CHARACTERISTIC_UUID: &str = "00002a37-0000-1000-8000-00805f9b34fb";
+const DESCRIPTOR_UUID: &str = "00002902-0000-1000-8000-00805f9b34fb";
+
+fn uuid(uuid: &str) -> BluetoothUuid {
+    BluetoothUuid::parse(uuid).unwrap()
+}
+
+fn strings(values: &[&str]) -> Vec<String> {
+    values.iter().map(|value| value.to_string()).collect()
+}
+
+// A connected mock device with one characteristic, which has one descriptor.
+fn create_attributes() -> (BluetoothAdapter, BluetoothGATTCharacteristic, BluetoothGATTDescriptor) {
+    let adapter = BluetoothAdapter::init_mock().unwrap();
+    let device = BluetoothDevice::create_mock_device(adapter.clone(), String::from("device")).unwrap();
+    device.set_connected(true).unwrap();
+    let service = BluetoothGATTService::create_mock_service(device, String::from("service")).unwrap();
+    service.set_uuid(uuid(SERVICE_UUID)).unwrap();
+    let characteristic =
+        BluetoothGATTCharacteristic::create_mock_characteristic(service, String::from("characteristic")).unwrap();
+    characteristic.set_uuid(uuid(CHARACTERISTIC_UUID)).unwrap();
+    characteristic.set_flags(strings(&["read", "write", "write-without-response", "reliable-write"])).unwrap();
+    characteristic.set_value(vec![1]).unwrap();
+    let descriptor =
+        BluetoothGATTDescriptor::create_mock_descriptor(characteristic.clone(), String::from("descriptor")).unwrap();
+    descriptor.set_uuid(uuid(DESCRIPTOR_UUID)).unwrap();
+    descriptor.set_flags(strings(&["read", "write"])).unwrap();
+    descriptor.set_value(vec![2]).unwrap();
+    (adapter, characteristic, descriptor)
+}
+
+fn set_blocklist(adapter: &BluetoothAdapter, text: &str) {
+    adapter.set_blocklist(Some(Blocklist::parse(text).unwrap()));
+}
+
+fn characteristic_can_read(characteristic: &BluetoothGATTCharacteristic) -> bool {
+    let read = characteristic.read_value();
+    let read_at = characteristic.read_value_at(0);
+    assert_eq!(read.is_ok(), read_at.is_ok());
+    match read {
+        Ok(_) => true,
+        Err(error) => {
+            assert_eq!(error, BluetoothError::Blocklisted);
+            assert_eq!(read_at, Err(BluetoothError::Blocklisted));
+            false
+        },
+    }
+}
+
+fn characteristic_can_write(characteristic: &BluetoothGATTCharacteristic) -> bool {
+    let results = [characteristic.write_value(vec![3]),
+                   characteristic.write_value_with_type(vec![3], WriteType::WithResponse),
+                   characteristic.write_value_with_type(vec![3], WriteType::WithoutResponse),
+                   characteristic.write_long_value(vec![3]),
+                   characteristic.begin_reliable_write().map(|_| ())];
+    if results.iter().all(Result::is_ok) {
+        return true;
+    }
+    assert!(results.iter().all(|result| *result == Err(BluetoothError::Blocklisted)), "{:?}", results);
+    false
+}
+
+fn descriptor_can_read(descriptor: &BluetoothGATTDescriptor) -> bool {
+    match descriptor.read_value() {
+        Ok(_) => true,
+        Err(error) => {
+            assert_eq!(error, BluetoothError::Blocklisted);
+            assert_eq!(descriptor.read_value_at(0), Err(BluetoothError::Blocklisted));
+            false
+        },
+    }
+}
+
+fn descriptor_can_write(descriptor: &BluetoothGATTDescriptor) -> bool {
+    match descriptor.write_value(vec![4]) {
+        Ok(()) => true,
+        Err(error) => {
+            assert_eq!(error, BluetoothError::Blocklisted);
+            false
+        },
+    }
+}
+
+#[test]
+fn parse_entries() {
+    let blocklist = Blocklist::parse(&format!("# The GATT blocklist.\n\n{}\n  {} exclude-reads  \n{}\texclude-writes\n",
+                                              SERVICE_UUID,
+                                              CHARACTERISTIC_UUID,
+                                              DESCRIPTOR_UUID)).unwrap();
+    assert_eq!(blocklist.get(&uuid(SERVICE_UUID)), Some(BlocklistEntry::Exclude));
+    assert_eq!(blocklist.get(&uuid(CHARACTERISTIC_UUID)), Some(BlocklistEntry::ExcludeReads));
+    assert_eq!(blocklist.get(&uuid(DESCRIPTOR_UUID)), Some(BlocklistEntry::ExcludeWrites));
+    assert_eq!(blocklist.get(&BluetoothUuid::from_u16(0x180F)), None);
+    assert_eq!(Blocklist::parse(&format!("{} exclude", SERVICE_UUID)).unwrap().get(&uuid(SERVICE_UUID)),
+               Some(BlocklistEntry::Exclude));
+    assert_eq!(Blocklist::parse(""), Ok(Blocklist::new()));
+    assert_eq!(Blocklist::parse("# Only a comment\n   \n"), Ok(Blocklist::new()));
+}
+
+#[test]
+fn excluded_entries() {
+    let blocklist = Blocklist::parse(&format!("{}\n{} exclude-reads\n{} exclude-writes\n",
+                                              SERVICE_UUID,
+                                              CHARACTERISTIC_UUID,
+                                              DESCRIPTOR_UUID)).unwrap();
+    let service = uuid(SERVICE_UUID);
+    assert!(blocklist.is_excluded(&service));
+    assert!(blocklist.is_excluded_reads(&service));
+    assert!(blocklist.is_excluded_writes(&service));
+    let characteristic = uuid(CHARACTERISTIC_UUID);
+    assert!(!blocklist.is_excluded(&characteristic));
+    assert!(blocklist.is_excluded_reads(&characteristic));
+    assert!(!blocklist.is_excluded_writes(&characteristic));
+    let descriptor = uuid(DESCRIPTOR_UUID);
+    assert!(!blocklist.is_excluded(&descriptor));
+    assert!(!blocklist.is_excluded_reads(&descriptor));
+    assert!(blocklist.is_excluded_writes(&descriptor));
+}
+
+#[test]
+fn parse_errors_name_the_line() {
+    let errors = [
+        ("180d", 1),
+        ("0x180D", 1),
+        (&format!("{}\n\n0000180d00001000800000805f9b34fb", SERVICE_UUID), 3),
+        (&format!("# comment\n{} exclude-all", SERVICE_UUID), 2),
+        (&format!("{} EXCLUDE", SERVICE_UUID), 1),
+        (&format!("{}\n{} exclude exclude-reads", SERVICE_UUID, CHARACTERISTIC_UUID), 2),
+        (&format!("{}\n{}\n0000180g-0000-1000-8000-00805f9b34fb", SERVICE_UUID, DESCRIPTOR_UUID), 3),
+        (&format!("{}\n{} # comment", SERVICE_UUID, DESCRIPTOR_UUID), 2),
+        ("exclude-reads", 1),
+    ];
+    for &(text, line) in &errors {
+        let invalid_line = text.lines().nth(line - 1).unwrap().trim();
+        let message = format!("gatt_blocklist.txt line {}: {}", line, invalid_line);
+        assert_eq!(Blocklist::parse(text), Err(BluetoothError::InvalidArgument(message)));
+    }
+}
+
+#[test]
+fn without_a_blocklist() {
+    let (_adapter, characteristic, descriptor) = create_attributes();
+    assert!(characteristic_can_read(&characteristic));
+    assert!(characteristic_can_write(&characteristic));
+    assert!(descriptor_can_read(&descriptor));
+    assert!(descriptor_can_write(&descriptor));
+}
+
+#[test]
+fn characteristic_excluded_from_reads() {
+    let (adapter, characteristic, descriptor) = create_attributes();
+    set_blocklist(&adapter, &format!("{} exclude-reads", CHARACTERISTIC_UUID));
+    assert!(!characteristic_can_read(&characteristic));
+    assert!(characteristic_can_write(&characteristic));
+    assert!(descriptor_can_read(&descriptor));
+    assert_eq!(characteristic.get_value(), Ok(vec![3]));
+}
+
+#[test]
+fn characteristic_excluded_from_writes() {
+    let (adapter, characteristic, descriptor) = create_attributes();
+    set_blocklist(&adapter, &format!("{} exclude-writes", CHARACTERISTIC_UUID));
+    assert!(characteristic_can_read(&characteristic));
+    assert!(!characteristic_can_write(&characteristic));
+    assert!(descriptor_can_write(&descriptor));
+    // The value was not written.
+    assert_eq!(characteristic.get_value(), Ok(vec![1]));
+}
+
+#[test]
+fn descriptor_excluded_from_reads() {
+    let (adapter, characteristic, descriptor) = create_attributes();
+    set_blocklist(&adapter, &format!("{} exclude-reads", DESCRIPTOR_UUID));
+    assert!(!descriptor_can_read(&descriptor));
+    assert!(descriptor_can_write(&descriptor));
+    assert!(characteristic_can_read(&characteristic));
+    assert_eq!(characteristic.get_gatt_descriptors().unwrap().len(), 1);
+}
+
+#[test]
+fn descriptor_excluded_from_writes() {
+    let (adapter, _characteristic, descriptor) = create_attributes();
+    set_blocklist(&adapter, &format!("{} exclude-writes", DESCRIPTOR_UUID));
+    assert!(descriptor_can_read(&descriptor));
+    assert!(!descriptor_can_write(&descriptor));
+    assert_eq!(descriptor.get_value(), Ok(vec![2]));
+}
+
+#[test]
+fn excluded_attributes_can_be_neither_read_nor_written() {
+    let (adapter, characteristic, descriptor) = create_attributes();
+    set_blocklist(&adapter, &format!("{}\n{}", CHARACTERISTIC_UUID, DESCRIPTOR_UUID));
+    assert!(!characteristic_can_read(&characteristic));
+    assert!(!characteristic_can_write(&characteristic));
+    assert!(!descriptor_can_read(&descriptor));
+    assert!(!descriptor_can_write(&descriptor));
+    // Nor listed.
+    let service = adapter.get_devices().unwrap().remove(0).get_gatt_services().unwrap().remove(0);
+    assert!(service.get_gatt_characteristics().unwrap().is_empty());
+}
+
+#[test]
+fn removing_the_blocklist() {
+    let (adapter, characteristic, descriptor) = create_attributes();
+    set_blocklist(&adapter, &format!("{}\n{}", CHARACTERISTIC_UUID, DESCRIPTOR_UUID));
+    adapter.set_blocklist(None);
+    assert!(characteristic_can_read(&characteristic));
+    assert!(characteristic_can_write(&characteristic));
+    assert!(descriptor_can_read(&descriptor));
+    assert!(descriptor_can_write(&descriptor));
+}
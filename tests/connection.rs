@@ -0,0 +1,171 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+#![cfg(feature = "bluetooth-test")]
+
+extern crate device;
+
+use device::bluetooth::{BluetoothAdapter, BluetoothDevice, BluetoothError};
+use device::connection::{BluetoothConnection, ConnectionEvent, ConnectionOptions, ConnectionState};
+use std::sync::mpsc::Receiver;
+use std::time::{Duration, Instant};
+
+fn create_device() -> (BluetoothAdapter, BluetoothDevice) {
+    let adapter = BluetoothAdapter::init_mock().unwrap();
+    let device = BluetoothDevice::create_mock_device(adapter.clone(), String::from("device")).unwrap();
+    device.set_connectable(true).unwrap();
+    (adapter, device)
+}
+
+fn fast_options() -> ConnectionOptions {
+    ConnectionOptions {
+        timeout: Duration::from_millis(500),
+        backoff: Duration::from_millis(10),
+        max_backoff: Duration::from_millis(40),
+        ..ConnectionOptions::new()
+    }
+}
+
+fn events(receiver: &Receiver<ConnectionEvent>) -> Vec<ConnectionEvent> {
+    receiver.try_iter().collect()
+}
+
+#[test]
+fn connect_and_disconnect() {
+    let (_, device) = create_device();
+    let connection = BluetoothConnection::new(device.clone(), fast_options());
+    let receiver = connection.subscribe();
+    assert_eq!(connection.get_state(), ConnectionState::Disconnected);
+    connection.connect().unwrap();
+    assert_eq!(connection.get_state(), ConnectionState::Connected);
+    assert!(device.is_connected().unwrap());
+    connection.disconnect().unwrap();
+    assert_eq!(connection.get_state(), ConnectionState::Disconnected);
+    assert!(!device.is_connected().unwrap());
+    assert_eq!(events(&receiver), vec![
+        ConnectionEvent::StateChanged(ConnectionState::Connecting),
+        ConnectionEvent::StateChanged(ConnectionState::Connected),
+        ConnectionEvent::StateChanged(ConnectionState::Disconnecting),
+        ConnectionEvent::StateChanged(ConnectionState::Disconnected),
+    ]);
+}
+
+#[test]
+fn initial_state_follows_the_device() {
+    let (_, device) = create_device();
+    device.set_connected(true).unwrap();
+    let connection = BluetoothConnection::new(device, fast_options());
+    assert_eq!(connection.get_state(), ConnectionState::Connected);
+    assert_eq!(connection.connect(), Ok(()));
+}
+
+#[test]
+fn failed_attempts_are_retried_with_backoff() {
+    let (_, device) = create_device();
+    device.set_connectable(false).unwrap();
+    let connection = BluetoothConnection::new(device, ConnectionOptions { retries: 3, ..fast_options() });
+    let receiver = connection.subscribe();
+    let start = Instant::now();
+    assert!(connection.connect().is_err());
+    // 10, 20 and 40 milliseconds between the four attempts.
+    assert!(start.elapsed() >= Duration::from_millis(70));
+    assert_eq!(connection.get_state(), ConnectionState::Disconnected);
+    let failed_attempts: Vec<u32> = events(&receiver).into_iter().filter_map(|event| match event {
+        ConnectionEvent::AttemptFailed(attempt, _) => Some(attempt),
+        _ => None,
+    }).collect();
+    assert_eq!(failed_attempts, vec![1, 2, 3, 4]);
+}
+
+#[test]
+fn backoff_is_limited() {
+    let (_, device) = create_device();
+    device.set_connectable(false).unwrap();
+    let options = ConnectionOptions {
+        retries: 4,
+        backoff: Duration::from_millis(20),
+        max_backoff: Duration::from_millis(20),
+        ..fast_options()
+    };
+    let connection = BluetoothConnection::new(device, options);
+    let start = Instant::now();
+    assert!(connection.connect().is_err());
+    let elapsed = start.elapsed();
+    assert!(elapsed >= Duration::from_millis(80) && elapsed < Duration::from_millis(300), "{:?}", elapsed);
+}
+
+#[test]
+fn slow_connection_times_out() {
+    let (adapter, device) = create_device();
+    adapter.set_mock_delay(Duration::from_millis(300)).unwrap();
+    let connection = BluetoothConnection::new(device, ConnectionOptions {
+        timeout: Duration::from_millis(50),
+        ..fast_options()
+    });
+    assert_eq!(connection.connect(), Err(BluetoothError::Timeout));
+    assert_eq!(connection.get_state(), ConnectionState::Disconnected);
+}
+
+#[test]
+fn late_connection_is_noticed_by_poll() {
+    let (adapter, device) = create_device();
+    adapter.set_mock_delay(Duration::from_millis(100)).unwrap();
+    let connection = BluetoothConnection::new(device.clone(), ConnectionOptions {
+        timeout: Duration::from_millis(10),
+        ..fast_options()
+    });
+    assert_eq!(connection.connect(), Err(BluetoothError::Timeout));
+    let deadline = Instant::now() + Duration::from_secs(5);
+    while !device.is_connected().unwrap() && Instant::now() < deadline {
+        std::thread::sleep(Duration::from_millis(10));
+    }
+    connection.poll().unwrap();
+    assert_eq!(connection.get_state(), ConnectionState::Connected);
+}
+
+#[test]
+fn link_loss_is_reported() {
+    let (_, device) = create_device();
+    let connection = BluetoothConnection::new(device.clone(), fast_options());
+    connection.connect().unwrap();
+    let receiver = connection.subscribe();
+    connection.poll().unwrap();
+    assert_eq!(events(&receiver), vec![]);
+    device.set_connected(false).unwrap();
+    connection.poll().unwrap();
+    assert_eq!(connection.get_state(), ConnectionState::Disconnected);
+    assert_eq!(events(&receiver), vec![
+        ConnectionEvent::StateChanged(ConnectionState::Disconnected),
+        ConnectionEvent::LinkLost,
+    ]);
+}
+
+#[test]
+fn lost_link_is_connected_again() {
+    let (_, device) = create_device();
+    let connection = BluetoothConnection::new(device.clone(), ConnectionOptions {
+        auto_reconnect: true,
+        ..fast_options()
+    });
+    connection.connect().unwrap();
+    device.set_connected(false).unwrap();
+    connection.poll().unwrap();
+    assert_eq!(connection.get_state(), ConnectionState::Connected);
+    assert!(device.is_connected().unwrap());
+}
+
+#[test]
+fn disconnect_is_not_a_link_loss() {
+    let (_, device) = create_device();
+    let connection = BluetoothConnection::new(device, ConnectionOptions {
+        auto_reconnect: true,
+        ..fast_options()
+    });
+    connection.connect().unwrap();
+    connection.disconnect().unwrap();
+    let receiver = connection.subscribe();
+    connection.poll().unwrap();
+    assert_eq!(connection.get_state(), ConnectionState::Disconnected);
+    assert_eq!(events(&receiver), vec![]);
+}
@@ -0,0 +1,196 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+extern crate device;
+
+use device::backend::{AdapterBackend, CharacteristicBackend, DescriptorBackend, DeviceBackend, ServiceBackend};
+use device::bluetooth::{BluetoothAdapter, BluetoothAddress, BluetoothDevice, BluetoothError};
+use std::sync::{Arc, Mutex};
+
+// A backend whose devices report their address as it was set, e.g. in lower case, and which
+// counts the device list requests.
+#[derive(Debug, Default)]
+struct ListAdapter {
+    devices: Mutex<Vec<Arc<ListDevice>>>,
+    list_requests: Mutex<usize>,
+}
+
+#[derive(Debug)]
+struct ListDevice {
+    id: String,
+    address: Mutex<String>,
+}
+
+// The services, characteristics and descriptors of the devices, which have no properties.
+#[derive(Debug)]
+struct StubAttribute {
+    id: String,
+}
+
+impl ListAdapter {
+    fn add(&self, id: &str, address: &str) -> Arc<ListDevice> {
+        let device = Arc::new(ListDevice {
+            id: id.to_owned(),
+            address: Mutex::new(address.to_owned()),
+        });
+        self.devices.lock().unwrap().push(device.clone());
+        device
+    }
+
+    fn remove(&self, id: &str) {
+        self.devices.lock().unwrap().retain(|device| device.id != id);
+    }
+
+    fn list_requests(&self) -> usize {
+        *self.list_requests.lock().unwrap()
+    }
+}
+
+impl ListDevice {
+    fn set_address(&self, address: &str) {
+        *self.address.lock().unwrap() = address.to_owned();
+    }
+}
+
+impl AdapterBackend for ListAdapter {
+    fn get_id(&self) -> String {
+        String::from("list_adapter")
+    }
+
+    fn create_device(self: Arc<Self>, id: String) -> Arc<dyn DeviceBackend> {
+        let devices = self.devices.lock().unwrap();
+        devices.iter().find(|device| device.id == id).unwrap().clone()
+    }
+
+    fn get_device_list(&self) -> Result<Vec<String>, BluetoothError> {
+        *self.list_requests.lock().unwrap() += 1;
+        Ok(self.devices.lock().unwrap().iter().map(|device| device.id.clone()).collect())
+    }
+}
+
+impl DeviceBackend for ListDevice {
+    fn get_id(&self) -> String {
+        self.id.clone()
+    }
+
+    fn create_service(self: Arc<Self>, id: String) -> Arc<dyn ServiceBackend> {
+        Arc::new(StubAttribute { id })
+    }
+
+    fn get_address(&self) -> Result<String, BluetoothError> {
+        Ok(self.address.lock().unwrap().clone())
+    }
+}
+
+impl ServiceBackend for StubAttribute {
+    fn get_id(&self) -> String {
+        self.id.clone()
+    }
+
+    fn create_characteristic(self: Arc<Self>, id: String) -> Arc<dyn CharacteristicBackend> {
+        Arc::new(StubAttribute { id })
+    }
+}
+
+impl CharacteristicBackend for StubAttribute {
+    fn get_id(&self) -> String {
+        self.id.clone()
+    }
+
+    fn create_descriptor(self: Arc<Self>, id: String) -> Arc<dyn DescriptorBackend> {
+        Arc::new(StubAttribute { id })
+    }
+}
+
+impl DescriptorBackend for StubAttribute {
+    fn get_id(&self) -> String {
+        self.id.clone()
+    }
+}
+
+fn create_adapter() -> (Arc<ListAdapter>, BluetoothAdapter) {
+    let backend = Arc::new(ListAdapter::default());
+    let adapter = BluetoothAdapter::init_with_backend(backend.clone());
+    (backend, adapter)
+}
+
+fn address(address: &str) -> BluetoothAddress {
+    BluetoothAddress::parse(address).unwrap()
+}
+
+fn device_id(device: Result<Option<BluetoothDevice>, BluetoothError>) -> Option<String> {
+    device.unwrap().map(|device| device.get_id())
+}
+
+#[test]
+fn address_lookup_ignores_case() {
+    let (backend, adapter) = create_adapter();
+    backend.add("lower", "0a:0b:0c:0d:0e:0f");
+    backend.add("upper", "1A:1B:1C:1D:1E:1F");
+    assert_eq!(device_id(adapter.get_device(address("0A:0B:0C:0D:0E:0F"))), Some(String::from("lower")));
+    assert_eq!(device_id(adapter.get_device(address("0a:0b:0c:0d:0e:0f"))), Some(String::from("lower")));
+    assert_eq!(device_id(adapter.get_device(address("1a-1b-1c-1d-1e-1f"))), Some(String::from("upper")));
+    // Answered from the index after the first request.
+    assert_eq!(backend.list_requests(), 1);
+}
+
+#[test]
+fn lookup_by_id() {
+    let (backend, adapter) = create_adapter();
+    backend.add("device", "0A:0B:0C:0D:0E:0F");
+    assert_eq!(device_id(adapter.get_device_by_id("device")), Some(String::from("device")));
+    assert_eq!(device_id(adapter.get_device_by_id("device")), Some(String::from("device")));
+    assert_eq!(backend.list_requests(), 1);
+    // Ids are not case-insensitive.
+    assert_eq!(device_id(adapter.get_device_by_id("DEVICE")), None);
+    assert_eq!(backend.list_requests(), 2);
+}
+
+#[test]
+fn refresh_after_an_address_change() {
+    let (backend, adapter) = create_adapter();
+    let device = backend.add("device", "0a:0b:0c:0d:0e:0f");
+    assert!(adapter.get_device(address("0A:0B:0C:0D:0E:0F")).unwrap().is_some());
+
+    device.set_address("1a:1b:1c:1d:1e:1f");
+    assert_eq!(device_id(adapter.get_device(address("0A:0B:0C:0D:0E:0F"))), None);
+    assert_eq!(device_id(adapter.get_device(address("1A:1B:1C:1D:1E:1F"))), Some(String::from("device")));
+
+    // The old address now belongs to another device.
+    backend.add("other", "0A:0B:0C:0D:0E:0F");
+    assert_eq!(device_id(adapter.get_device(address("0a:0b:0c:0d:0e:0f"))), Some(String::from("other")));
+    assert_eq!(device_id(adapter.get_device(address("1a:1b:1c:1d:1e:1f"))), Some(String::from("device")));
+}
+
+#[test]
+fn removed_devices_are_not_found() {
+    let (backend, adapter) = create_adapter();
+    backend.add("first", "0A:0B:0C:0D:0E:0F");
+    backend.add("second", "1A:1B:1C:1D:1E:1F");
+    assert_eq!(adapter.get_devices().unwrap().len(), 2);
+
+    backend.remove("first");
+    assert_eq!(adapter.get_devices().unwrap().iter().map(BluetoothDevice::get_id).collect::<Vec<_>>(),
+               vec![String::from("second")]);
+    assert_eq!(device_id(adapter.get_device_by_id("first")), None);
+    assert_eq!(device_id(adapter.get_device(address("0A:0B:0C:0D:0E:0F"))), None);
+    assert_eq!(device_id(adapter.get_device(address("1A:1B:1C:1D:1E:1F"))), Some(String::from("second")));
+
+    // A device added again with the same id is indexed again.
+    backend.add("first", "2A:2B:2C:2D:2E:2F");
+    assert_eq!(device_id(adapter.get_device(address("2a:2b:2c:2d:2e:2f"))), Some(String::from("first")));
+    assert_eq!(device_id(adapter.get_device(address("0A:0B:0C:0D:0E:0F"))), None);
+}
+
+#[test]
+fn devices_without_a_valid_address() {
+    let (backend, adapter) = create_adapter();
+    let device = backend.add("device", "not an address");
+    assert_eq!(adapter.get_devices().unwrap().len(), 1);
+    assert_eq!(device_id(adapter.get_device_by_id("device")), Some(String::from("device")));
+
+    // Indexed by its address once it can be read.
+    device.set_address("0a:0b:0c:0d:0e:0f");
+    assert_eq!(device_id(adapter.get_device(address("0A:0B:0C:0D:0E:0F"))), Some(String::from("device")));
+}
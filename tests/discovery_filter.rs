@@ -0,0 +1,192 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+#![cfg(feature = "bluetooth-test")]
+
+extern crate device;
+
+use device::bluetooth::{BluetoothAdapter, BluetoothDevice, BluetoothError, BluetoothUuid};
+use device::discovery_filter::{DiscoveryFilter, DiscoveryTransport};
+
+const HEART_RATE: u16 = 0x180D;
+const BATTERY: u16 = 0x180F;
+const GLUCOSE: u16 = 0x1808;
+// Major class Audio/Video, minor class Wearable Headset.
+const HEADSET_CLASS: u32 = 0x240404;
+
+fn create_device(adapter: &BluetoothAdapter, id: &str, name: Option<&str>, services: &[u16]) -> BluetoothDevice {
+    let device = BluetoothDevice::create_mock_device(adapter.clone(), id.to_owned()).unwrap();
+    device.set_name(name.map(str::to_owned)).unwrap();
+    device.set_uuids(services.iter().map(|alias| BluetoothUuid::from_u16(*alias)).collect()).unwrap();
+    device
+}
+
+// The ids of the devices a session with the filter discovers.
+fn discovered(adapter: &BluetoothAdapter, filter: DiscoveryFilter) -> Vec<String> {
+    let session = adapter.create_discovery_session_with_filter(filter).unwrap();
+    let mut ids: Vec<String> = session.get_discovered_devices().unwrap().iter().map(BluetoothDevice::get_id).collect();
+    ids.sort();
+    ids
+}
+
+fn ids(ids: &[&str]) -> Vec<String> {
+    ids.iter().map(|id| id.to_string()).collect()
+}
+
+#[test]
+fn uuids() {
+    let adapter = BluetoothAdapter::init_mock().unwrap();
+    create_device(&adapter, "heart_rate", Some("Heart Rate"), &[HEART_RATE, BATTERY]);
+    create_device(&adapter, "glucose", Some("Glucose"), &[GLUCOSE]);
+    create_device(&adapter, "no_services", Some("Beacon"), &[]);
+
+    assert_eq!(discovered(&adapter, DiscoveryFilter::new()), ids(&["glucose", "heart_rate", "no_services"]));
+    let battery = DiscoveryFilter {
+        uuids: vec![BluetoothUuid::from_u16(BATTERY)],
+        ..DiscoveryFilter::default()
+    };
+    assert_eq!(discovered(&adapter, battery), ids(&["heart_rate"]));
+    let either = DiscoveryFilter {
+        uuids: vec![BluetoothUuid::from_u16(GLUCOSE), BluetoothUuid::from_u16(HEART_RATE)],
+        ..DiscoveryFilter::default()
+    };
+    assert_eq!(discovered(&adapter, either), ids(&["glucose", "heart_rate"]));
+}
+
+#[test]
+fn name_and_name_prefix() {
+    let adapter = BluetoothAdapter::init_mock().unwrap();
+    create_device(&adapter, "monitor", Some("Heart Rate Monitor"), &[]);
+    create_device(&adapter, "strap", Some("Heart Rate Strap"), &[]);
+    create_device(&adapter, "unnamed", None, &[]);
+
+    let name = DiscoveryFilter {
+        name: Some(String::from("Heart Rate Strap")),
+        ..DiscoveryFilter::default()
+    };
+    assert_eq!(discovered(&adapter, name), ids(&["strap"]));
+    let name_prefix = DiscoveryFilter {
+        name_prefix: Some(String::from("Heart Rate")),
+        ..DiscoveryFilter::default()
+    };
+    assert_eq!(discovered(&adapter, name_prefix), ids(&["monitor", "strap"]));
+    let both = DiscoveryFilter {
+        name: Some(String::from("Heart Rate Monitor")),
+        name_prefix: Some(String::from("Heart Rate S")),
+        ..DiscoveryFilter::default()
+    };
+    assert!(discovered(&adapter, both).is_empty());
+}
+
+#[test]
+fn matches_name_ignores_the_other_criteria() {
+    let adapter = BluetoothAdapter::init_mock().unwrap();
+    let device = create_device(&adapter, "monitor", Some("Heart Rate Monitor"), &[BATTERY]);
+    let filter = DiscoveryFilter {
+        uuids: vec![BluetoothUuid::from_u16(HEART_RATE)],
+        name_prefix: Some(String::from("Heart Rate")),
+        rssi: Some(-50),
+        ..DiscoveryFilter::default()
+    };
+    // What BlueZ leaves to the session.
+    assert!(filter.matches_name(&device));
+    assert!(!filter.matches(&device));
+    assert!(DiscoveryFilter::new().matches_name(&create_device(&adapter, "unnamed", None, &[])));
+}
+
+#[test]
+fn rssi_threshold() {
+    let adapter = BluetoothAdapter::init_mock().unwrap();
+    create_device(&adapter, "near", None, &[]).set_rssi(-50).unwrap();
+    create_device(&adapter, "edge", None, &[]).set_rssi(-70).unwrap();
+    create_device(&adapter, "far", None, &[]).set_rssi(-90).unwrap();
+    create_device(&adapter, "no_rssi", None, &[]);
+
+    let rssi = DiscoveryFilter {
+        rssi: Some(-70),
+        ..DiscoveryFilter::default()
+    };
+    assert_eq!(discovered(&adapter, rssi), ids(&["edge", "near"]));
+}
+
+#[test]
+fn pathloss_threshold() {
+    let adapter = BluetoothAdapter::init_mock().unwrap();
+    let near = create_device(&adapter, "near", None, &[]);
+    near.set_tx_power(4).unwrap();
+    near.set_rssi(-40).unwrap();
+    let far = create_device(&adapter, "far", None, &[]);
+    far.set_tx_power(-4).unwrap();
+    far.set_rssi(-80).unwrap();
+    create_device(&adapter, "no_tx_power", None, &[]).set_rssi(-40).unwrap();
+
+    let pathloss = DiscoveryFilter {
+        pathloss: Some(44),
+        ..DiscoveryFilter::default()
+    };
+    assert_eq!(discovered(&adapter, pathloss), ids(&["near"]));
+    let rssi_and_pathloss = DiscoveryFilter {
+        rssi: Some(-70),
+        pathloss: Some(44),
+        ..DiscoveryFilter::default()
+    };
+    assert!(matches!(adapter.create_discovery_session_with_filter(rssi_and_pathloss).err(),
+                     Some(BluetoothError::InvalidArgument(_))));
+}
+
+#[test]
+fn transport() {
+    let adapter = BluetoothAdapter::init_mock().unwrap();
+    create_device(&adapter, "headset", None, &[]).set_class(HEADSET_CLASS).unwrap();
+    create_device(&adapter, "sensor", None, &[]);
+
+    let low_energy = DiscoveryFilter {
+        transport: DiscoveryTransport::LowEnergy,
+        ..DiscoveryFilter::default()
+    };
+    assert_eq!(discovered(&adapter, low_energy), ids(&["sensor"]));
+    let br_edr = DiscoveryFilter {
+        transport: DiscoveryTransport::BrEdr,
+        ..DiscoveryFilter::default()
+    };
+    assert_eq!(discovered(&adapter, br_edr), ids(&["headset"]));
+    let auto = DiscoveryFilter {
+        transport: DiscoveryTransport::Auto,
+        ..DiscoveryFilter::default()
+    };
+    assert_eq!(discovered(&adapter, auto), ids(&["headset", "sensor"]));
+}
+
+#[test]
+fn new_devices_are_reported_once() {
+    let adapter = BluetoothAdapter::init_mock().unwrap();
+    let device = create_device(&adapter, "sensor", None, &[]);
+    device.set_rssi(-60).unwrap();
+    let session = adapter.create_discovery_session_with_filter(DiscoveryFilter::new()).unwrap();
+    assert_eq!(session.get_new_devices().unwrap().len(), 1);
+    assert!(session.get_new_devices().unwrap().is_empty());
+    device.set_rssi(-50).unwrap();
+    assert!(session.get_new_devices().unwrap().is_empty());
+
+    create_device(&adapter, "late", None, &[]);
+    let new_devices = session.get_new_devices().unwrap();
+    assert_eq!(new_devices.iter().map(BluetoothDevice::get_id).collect::<Vec<_>>(), ids(&["late"]));
+}
+
+#[test]
+fn duplicate_data() {
+    let adapter = BluetoothAdapter::init_mock().unwrap();
+    let device = create_device(&adapter, "sensor", None, &[]);
+    device.set_rssi(-60).unwrap();
+    let filter = DiscoveryFilter {
+        duplicate_data: true,
+        ..DiscoveryFilter::default()
+    };
+    let session = adapter.create_discovery_session_with_filter(filter).unwrap();
+    assert_eq!(session.get_new_devices().unwrap().len(), 1);
+    assert!(session.get_new_devices().unwrap().is_empty());
+    device.set_rssi(-50).unwrap();
+    assert_eq!(session.get_new_devices().unwrap().len(), 1);
+    assert!(session.get_new_devices().unwrap().is_empty());
+}
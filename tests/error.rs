@@ -0,0 +1,110 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+extern crate device;
+
+use device::error::{self, BluetoothError};
+use std::error::Error;
+use std::fmt;
+
+// Formats like the D-Bus errors of blurz, whose name is only in the debug output.
+struct DBusError {
+    name: &'static str,
+    message: &'static str,
+}
+
+impl fmt::Debug for DBusError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "D-Bus error: {} ({})", self.message, self.name)
+    }
+}
+
+impl fmt::Display for DBusError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+impl Error for DBusError {}
+
+fn from_name(name: &'static str) -> BluetoothError {
+    let error = DBusError {
+        name,
+        message: "D-Bus error",
+    };
+    BluetoothError::from(Box::new(error) as Box<dyn Error>)
+}
+
+fn from_message(message: &str) -> BluetoothError {
+    BluetoothError::from(Box::<dyn Error>::from(message))
+}
+
+#[test]
+fn bluez_error_names() {
+    let names = [
+        ("org.bluez.Error.NotSupported", BluetoothError::NotSupported),
+        ("org.bluez.Error.DoesNotExist", BluetoothError::NotFound),
+        ("org.freedesktop.DBus.Error.UnknownObject", BluetoothError::NotFound),
+        ("org.bluez.Error.NotConnected", BluetoothError::NotConnected),
+        ("org.bluez.Error.NotReady", BluetoothError::InvalidState),
+        ("org.bluez.Error.InProgress", BluetoothError::InvalidState),
+        ("org.bluez.Error.AlreadyExists", BluetoothError::InvalidState),
+        ("org.bluez.Error.AlreadyConnected", BluetoothError::InvalidState),
+        ("org.bluez.Error.InvalidOffset", BluetoothError::GattError(error::ATT_INVALID_OFFSET)),
+        ("org.bluez.Error.InvalidValueLength", BluetoothError::GattError(error::ATT_INVALID_ATTRIBUTE_VALUE_LENGTH)),
+        ("org.bluez.Error.NotPermitted", BluetoothError::PermissionDenied),
+        ("org.bluez.Error.NotAuthorized", BluetoothError::PermissionDenied),
+        ("org.bluez.Error.AuthenticationFailed", BluetoothError::PermissionDenied),
+        ("org.bluez.Error.AuthenticationRejected", BluetoothError::PermissionDenied),
+        ("org.freedesktop.DBus.Error.AccessDenied", BluetoothError::PermissionDenied),
+        ("org.freedesktop.DBus.Error.NoReply", BluetoothError::Timeout),
+        ("org.freedesktop.DBus.Error.Timeout", BluetoothError::Timeout),
+    ];
+    for &(name, ref expected) in &names {
+        assert_eq!(from_name(name), *expected, "{}", name);
+    }
+}
+
+#[test]
+fn backend_messages() {
+    let messages = [
+        ("Bluetooth adapter not found", BluetoothError::NotFound),
+        ("Device not connected.", BluetoothError::NotConnected),
+        ("The device is not connected.", BluetoothError::NotConnected),
+        ("No device exists with the given id.", BluetoothError::NotFound),
+        ("No service exists with the given id.", BluetoothError::NotFound),
+        ("No characteristic exists with the given id.", BluetoothError::NotFound),
+        ("No descriptor exists with the given id.", BluetoothError::NotFound),
+        ("No device found.", BluetoothError::NotFound),
+    ];
+    for &(message, ref expected) in &messages {
+        assert_eq!(from_message(message), *expected, "{}", message);
+    }
+}
+
+#[test]
+fn unknown_errors_keep_their_message() {
+    assert_eq!(from_name("org.bluez.Error.Failed"), BluetoothError::Backend(String::from("D-Bus error")));
+    assert_eq!(from_message("Something went wrong"), BluetoothError::Backend(String::from("Something went wrong")));
+    // Messages have to match exactly.
+    assert_eq!(from_message("Device not connected"), BluetoothError::Backend(String::from("Device not connected")));
+    assert_eq!(from_message("device not connected."),
+               BluetoothError::Backend(String::from("device not connected.")));
+}
+
+#[test]
+fn error_names_win_over_messages() {
+    let error = DBusError {
+        name: "org.bluez.Error.NotReady",
+        message: "No device found.",
+    };
+    assert_eq!(BluetoothError::from(Box::new(error) as Box<dyn Error>), BluetoothError::InvalidState);
+}
+
+#[test]
+fn display() {
+    assert_eq!(BluetoothError::GattError(error::ATT_INVALID_OFFSET).to_string(), "Error! GATT error 0x07!");
+    assert_eq!(BluetoothError::InvalidArgument(String::from("180g")).to_string(), "Error! Invalid argument: 180g");
+    assert_eq!(BluetoothError::Backend(String::from("Failed")).to_string(), "Error! Failed");
+}
@@ -0,0 +1,88 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+#![cfg(feature = "bluetooth-test")]
+
+extern crate device;
+
+use device::bluetooth::{BluetoothAdapter, BluetoothDevice, BluetoothEvent, DeviceProperty};
+use std::sync::mpsc::Receiver;
+
+fn poll(adapter: &BluetoothAdapter, events: &Receiver<BluetoothEvent>) -> Vec<BluetoothEvent> {
+    adapter.poll_events().unwrap();
+    events.try_iter().collect()
+}
+
+#[test]
+fn device_added_and_removed() {
+    let adapter = BluetoothAdapter::init_mock().unwrap();
+    let events = adapter.subscribe();
+    BluetoothDevice::create_mock_device(adapter.clone(), String::from("device")).unwrap();
+    assert_eq!(poll(&adapter, &events), vec![BluetoothEvent::DeviceAdded(String::from("device"))]);
+    assert_eq!(poll(&adapter, &events), vec![]);
+
+    adapter.remove_device(String::from("device")).unwrap();
+    assert_eq!(poll(&adapter, &events), vec![BluetoothEvent::DeviceRemoved(String::from("device"))]);
+}
+
+#[test]
+fn device_property_changed() {
+    let adapter = BluetoothAdapter::init_mock().unwrap();
+    let device = BluetoothDevice::create_mock_device(adapter.clone(), String::from("device")).unwrap();
+    device.set_name(Some(String::from("before"))).unwrap();
+    let events = adapter.subscribe();
+
+    device.set_name(Some(String::from("after"))).unwrap();
+    device.set_rssi(-40).unwrap();
+    device.set_connected(true).unwrap();
+    let id = String::from("device");
+    assert_eq!(poll(&adapter, &events), vec![
+        BluetoothEvent::PropertyChanged(id.clone(), DeviceProperty::Name(String::from("after"))),
+        BluetoothEvent::PropertyChanged(id.clone(), DeviceProperty::Rssi(-40)),
+        BluetoothEvent::PropertyChanged(id.clone(), DeviceProperty::Connected(true)),
+    ]);
+
+    device.set_rssi(-70).unwrap();
+    device.set_connected(false).unwrap();
+    assert_eq!(poll(&adapter, &events), vec![
+        BluetoothEvent::PropertyChanged(id.clone(), DeviceProperty::Rssi(-70)),
+        BluetoothEvent::PropertyChanged(id, DeviceProperty::Connected(false)),
+    ]);
+}
+
+#[test]
+fn adapter_powered_changed() {
+    let adapter = BluetoothAdapter::init_mock().unwrap();
+    let events = adapter.subscribe();
+    adapter.set_powered(true).unwrap();
+    assert_eq!(poll(&adapter, &events), vec![BluetoothEvent::AdapterPoweredChanged(true)]);
+    adapter.set_powered(false).unwrap();
+    assert_eq!(poll(&adapter, &events), vec![BluetoothEvent::AdapterPoweredChanged(false)]);
+}
+
+#[test]
+fn unsubscribed_receivers_get_nothing() {
+    let adapter = BluetoothAdapter::init_mock().unwrap();
+    let early = adapter.subscribe();
+    adapter.set_powered(true).unwrap();
+    adapter.poll_events().unwrap();
+
+    // Subscribing later does not replay the events of earlier polls.
+    let late = adapter.subscribe();
+    adapter.poll_events().unwrap();
+    assert_eq!(late.try_iter().count(), 0);
+    assert_eq!(early.try_iter().count(), 1);
+
+    // Dropped receivers are forgotten, the others still get the events.
+    drop(early);
+    adapter.set_powered(false).unwrap();
+    assert_eq!(poll(&adapter, &late), vec![BluetoothEvent::AdapterPoweredChanged(false)]);
+
+    // Changes of an other adapter do not reach the receivers of this one.
+    let other = BluetoothAdapter::init_mock().unwrap();
+    other.subscribe();
+    other.set_powered(true).unwrap();
+    other.poll_events().unwrap();
+    assert_eq!(late.try_iter().count(), 0);
+}
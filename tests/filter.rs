@@ -0,0 +1,350 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+#![cfg(feature = "bluetooth-test")]
+
+extern crate device;
+
+use device::bluetooth::{BluetoothAdapter, BluetoothDevice, BluetoothError, BluetoothUuid};
+use device::filter::{BluetoothLEScanFilter, BluetoothManufacturerDataFilter, BluetoothServiceDataFilter};
+use device::filter::RequestDeviceOptions;
+use std::collections::{BTreeSet, HashMap};
+
+const HEART_RATE: u16 = 0x180D;
+const BATTERY: u16 = 0x180F;
+const GLUCOSE: u16 = 0x1808;
+const APPLE: u16 = 0x004C;
+const NORDIC: u16 = 0x0059;
+
+fn uuid(alias: u16) -> BluetoothUuid {
+    BluetoothUuid::from_u16(alias)
+}
+
+fn create_device(adapter: &BluetoothAdapter, id: &str, name: Option<&str>, services: &[u16]) -> BluetoothDevice {
+    let device = BluetoothDevice::create_mock_device(adapter.clone(), id.to_owned()).unwrap();
+    device.set_name(name.map(str::to_owned)).unwrap();
+    device.set_uuids(services.iter().map(|alias| uuid(*alias)).collect()).unwrap();
+    device
+}
+
+fn heart_rate_device() -> BluetoothDevice {
+    let adapter = BluetoothAdapter::init_mock().unwrap();
+    let device = create_device(&adapter, "heart_rate", Some("Heart Rate Monitor"), &[HEART_RATE, BATTERY]);
+    let mut manufacturer_data = HashMap::new();
+    manufacturer_data.insert(APPLE, vec![0x02, 0x15, 0xAA, 0x01]);
+    device.set_manufacturer_data(manufacturer_data).unwrap();
+    let mut service_data = HashMap::new();
+    service_data.insert(uuid(BATTERY), vec![0x64]);
+    device.set_service_data(service_data).unwrap();
+    device
+}
+
+fn options(filters: Vec<BluetoothLEScanFilter>) -> RequestDeviceOptions {
+    RequestDeviceOptions {
+        filters: Some(filters),
+        ..Default::default()
+    }
+}
+
+fn services_filter(services: &[u16]) -> BluetoothLEScanFilter {
+    BluetoothLEScanFilter {
+        services: Some(services.iter().map(|alias| uuid(*alias)).collect()),
+        ..Default::default()
+    }
+}
+
+fn name_filter(name: &str) -> BluetoothLEScanFilter {
+    BluetoothLEScanFilter {
+        name: Some(name.to_owned()),
+        ..Default::default()
+    }
+}
+
+fn name_prefix_filter(name_prefix: &str) -> BluetoothLEScanFilter {
+    BluetoothLEScanFilter {
+        name_prefix: Some(name_prefix.to_owned()),
+        ..Default::default()
+    }
+}
+
+fn manufacturer_filter(company_identifier: u16, data_prefix: Vec<u8>, mask: Option<Vec<u8>>) -> BluetoothLEScanFilter {
+    BluetoothLEScanFilter {
+        manufacturer_data: Some(vec![BluetoothManufacturerDataFilter {
+            company_identifier,
+            data_prefix,
+            mask,
+        }]),
+        ..Default::default()
+    }
+}
+
+fn service_data_filter(service: u16, data_prefix: Vec<u8>, mask: Option<Vec<u8>>) -> BluetoothLEScanFilter {
+    BluetoothLEScanFilter {
+        service_data: Some(vec![BluetoothServiceDataFilter {
+            service: uuid(service),
+            data_prefix,
+            mask,
+        }]),
+        ..Default::default()
+    }
+}
+
+fn matches(options: &RequestDeviceOptions, device: &BluetoothDevice) -> bool {
+    options.match_device(device).unwrap().matched
+}
+
+fn is_type_error(options: &RequestDeviceOptions) -> bool {
+    matches!(options.validate(), Err(BluetoothError::InvalidArgument(_)))
+}
+
+#[test]
+fn filters_or_accept_all_devices_is_required() {
+    assert!(is_type_error(&RequestDeviceOptions::default()));
+    assert!(is_type_error(&options(vec![])));
+    let both = RequestDeviceOptions {
+        filters: Some(vec![name_filter("Heart Rate Monitor")]),
+        accept_all_devices: true,
+        ..Default::default()
+    };
+    assert!(is_type_error(&both));
+    let accept_all = RequestDeviceOptions {
+        accept_all_devices: true,
+        ..Default::default()
+    };
+    assert!(accept_all.validate().is_ok());
+}
+
+#[test]
+fn invalid_filters_are_rejected() {
+    assert!(is_type_error(&options(vec![BluetoothLEScanFilter::default()])));
+    assert!(is_type_error(&options(vec![services_filter(&[])])));
+    assert!(is_type_error(&options(vec![name_prefix_filter("")])));
+    assert!(is_type_error(&options(vec![name_filter(&"a".repeat(249))])));
+    assert!(!is_type_error(&options(vec![name_filter(&"a".repeat(248))])));
+    assert!(is_type_error(&options(vec![name_prefix_filter(&"a".repeat(249))])));
+    let empty_manufacturer_data = BluetoothLEScanFilter {
+        manufacturer_data: Some(vec![]),
+        ..Default::default()
+    };
+    assert!(is_type_error(&options(vec![empty_manufacturer_data])));
+    let empty_service_data = BluetoothLEScanFilter {
+        service_data: Some(vec![]),
+        ..Default::default()
+    };
+    assert!(is_type_error(&options(vec![empty_service_data])));
+    assert!(is_type_error(&options(vec![manufacturer_filter(APPLE, vec![0x02, 0x15], Some(vec![0xFF]))])));
+    assert!(is_type_error(&options(vec![service_data_filter(BATTERY, vec![], Some(vec![0xFF]))])));
+}
+
+#[test]
+fn duplicate_keys_are_rejected() {
+    let data_filter = BluetoothManufacturerDataFilter {
+        company_identifier: APPLE,
+        data_prefix: vec![],
+        mask: None,
+    };
+    let duplicate_company = BluetoothLEScanFilter {
+        manufacturer_data: Some(vec![data_filter.clone(), data_filter]),
+        ..Default::default()
+    };
+    assert!(is_type_error(&options(vec![duplicate_company])));
+    let service_filter = BluetoothServiceDataFilter {
+        service: uuid(BATTERY),
+        data_prefix: vec![],
+        mask: None,
+    };
+    let duplicate_service = BluetoothLEScanFilter {
+        service_data: Some(vec![service_filter.clone(), service_filter]),
+        ..Default::default()
+    };
+    assert!(is_type_error(&options(vec![duplicate_service])));
+}
+
+#[test]
+fn invalid_exclusion_filters_are_rejected() {
+    let empty_exclusion = RequestDeviceOptions {
+        filters: Some(vec![name_filter("Heart Rate Monitor")]),
+        exclusion_filters: Some(vec![]),
+        ..Default::default()
+    };
+    assert!(is_type_error(&empty_exclusion));
+    let with_accept_all = RequestDeviceOptions {
+        exclusion_filters: Some(vec![name_filter("Heart Rate Monitor")]),
+        accept_all_devices: true,
+        ..Default::default()
+    };
+    assert!(is_type_error(&with_accept_all));
+    let invalid_exclusion = RequestDeviceOptions {
+        filters: Some(vec![name_filter("Heart Rate Monitor")]),
+        exclusion_filters: Some(vec![BluetoothLEScanFilter::default()]),
+        ..Default::default()
+    };
+    assert!(is_type_error(&invalid_exclusion));
+    assert!(invalid_exclusion.match_device(&heart_rate_device()).is_err());
+}
+
+#[test]
+fn services_must_all_be_advertised() {
+    let device = heart_rate_device();
+    assert!(matches(&options(vec![services_filter(&[HEART_RATE])]), &device));
+    assert!(matches(&options(vec![services_filter(&[HEART_RATE, BATTERY])]), &device));
+    assert!(!matches(&options(vec![services_filter(&[HEART_RATE, GLUCOSE])]), &device));
+    assert!(!matches(&options(vec![services_filter(&[GLUCOSE])]), &device));
+}
+
+#[test]
+fn name_must_be_equal() {
+    let device = heart_rate_device();
+    assert!(matches(&options(vec![name_filter("Heart Rate Monitor")]), &device));
+    assert!(!matches(&options(vec![name_filter("Heart Rate")]), &device));
+    assert!(!matches(&options(vec![name_filter("heart rate monitor")]), &device));
+}
+
+#[test]
+fn name_prefix_must_start_the_name() {
+    let device = heart_rate_device();
+    assert!(matches(&options(vec![name_prefix_filter("Heart")]), &device));
+    assert!(matches(&options(vec![name_prefix_filter("Heart Rate Monitor")]), &device));
+    assert!(!matches(&options(vec![name_prefix_filter("Rate")]), &device));
+    assert!(!matches(&options(vec![name_prefix_filter("Heart Rate Monitors")]), &device));
+}
+
+#[test]
+fn nameless_device_does_not_match_name_filters() {
+    let adapter = BluetoothAdapter::init_mock().unwrap();
+    let device = create_device(&adapter, "nameless", None, &[HEART_RATE]);
+    assert!(!matches(&options(vec![name_filter("")]), &device));
+    assert!(!matches(&options(vec![name_prefix_filter("H")]), &device));
+    assert!(matches(&options(vec![services_filter(&[HEART_RATE])]), &device));
+}
+
+#[test]
+fn every_member_of_a_filter_must_match() {
+    let device = heart_rate_device();
+    let mut filter = services_filter(&[HEART_RATE]);
+    filter.name_prefix = Some(String::from("Heart"));
+    assert!(matches(&options(vec![filter.clone()]), &device));
+    filter.name = Some(String::from("Glucose Meter"));
+    assert!(!matches(&options(vec![filter]), &device));
+}
+
+#[test]
+fn any_filter_may_match() {
+    let device = heart_rate_device();
+    let filters = vec![name_filter("Glucose Meter"), services_filter(&[BATTERY])];
+    assert!(matches(&options(filters), &device));
+    let filters = vec![name_filter("Glucose Meter"), services_filter(&[GLUCOSE])];
+    assert!(!matches(&options(filters), &device));
+}
+
+#[test]
+fn manufacturer_data_prefix_and_mask() {
+    let device = heart_rate_device();
+    assert!(matches(&options(vec![manufacturer_filter(APPLE, vec![], None)]), &device));
+    assert!(matches(&options(vec![manufacturer_filter(APPLE, vec![0x02, 0x15], None)]), &device));
+    assert!(matches(&options(vec![manufacturer_filter(APPLE, vec![0x02, 0x15, 0xAA, 0x01], None)]), &device));
+    // The prefix is longer than the data.
+    assert!(!matches(&options(vec![manufacturer_filter(APPLE, vec![0x02, 0x15, 0xAA, 0x01, 0x00], None)]),
+                     &device));
+    assert!(!matches(&options(vec![manufacturer_filter(APPLE, vec![0x02, 0x16], None)]), &device));
+    // Only the masked bits are compared.
+    assert!(matches(&options(vec![manufacturer_filter(APPLE, vec![0x02, 0x16], Some(vec![0xFF, 0xF0]))]),
+                    &device));
+    assert!(matches(&options(vec![manufacturer_filter(APPLE, vec![0x00, 0x00, 0xA0], Some(vec![0x00, 0x00, 0xF0]))]),
+                    &device));
+    assert!(!matches(&options(vec![manufacturer_filter(APPLE, vec![0x00, 0x00, 0xB0], Some(vec![0x00, 0x00, 0xF0]))]),
+                     &device));
+    assert!(!matches(&options(vec![manufacturer_filter(NORDIC, vec![], None)]), &device));
+}
+
+#[test]
+fn service_data_prefix_and_mask() {
+    let device = heart_rate_device();
+    assert!(matches(&options(vec![service_data_filter(BATTERY, vec![], None)]), &device));
+    assert!(matches(&options(vec![service_data_filter(BATTERY, vec![0x64], None)]), &device));
+    assert!(!matches(&options(vec![service_data_filter(BATTERY, vec![0x65], None)]), &device));
+    assert!(matches(&options(vec![service_data_filter(BATTERY, vec![0x65], Some(vec![0xFE]))]), &device));
+    assert!(!matches(&options(vec![service_data_filter(HEART_RATE, vec![], None)]), &device));
+}
+
+#[test]
+fn device_without_advertisement_data_does_not_match_data_filters() {
+    let adapter = BluetoothAdapter::init_mock().unwrap();
+    let device = create_device(&adapter, "plain", Some("Heart Rate Monitor"), &[HEART_RATE]);
+    assert!(!matches(&options(vec![manufacturer_filter(APPLE, vec![], None)]), &device));
+    assert!(!matches(&options(vec![service_data_filter(BATTERY, vec![], None)]), &device));
+}
+
+#[test]
+fn exclusion_filters_remove_matching_devices() {
+    let device = heart_rate_device();
+    let mut request = options(vec![services_filter(&[HEART_RATE])]);
+    request.exclusion_filters = Some(vec![name_filter("Glucose Meter")]);
+    assert!(matches(&request, &device));
+    request.exclusion_filters = Some(vec![name_filter("Glucose Meter"), manufacturer_filter(APPLE, vec![0x02], None)]);
+    assert!(!matches(&request, &device));
+}
+
+#[test]
+fn accept_all_devices_matches_every_device() {
+    let adapter = BluetoothAdapter::init_mock().unwrap();
+    let nameless = create_device(&adapter, "nameless", None, &[]);
+    let request = RequestDeviceOptions {
+        accept_all_devices: true,
+        ..Default::default()
+    };
+    assert!(matches(&request, &nameless));
+    assert!(matches(&request, &heart_rate_device()));
+}
+
+#[test]
+fn allowed_services_are_the_filter_and_optional_services() {
+    let device = heart_rate_device();
+    let mut request = options(vec![services_filter(&[HEART_RATE]), services_filter(&[GLUCOSE]), name_filter("x")]);
+    request.optional_services = vec![uuid(BATTERY)];
+    let result = request.match_device(&device).unwrap();
+    assert!(result.matched);
+    let expected: BTreeSet<BluetoothUuid> = [HEART_RATE, GLUCOSE, BATTERY].iter().map(|alias| uuid(*alias)).collect();
+    assert_eq!(result.allowed_services, expected);
+}
+
+#[test]
+fn allowed_services_of_accept_all_devices_are_the_optional_services() {
+    let request = RequestDeviceOptions {
+        accept_all_devices: true,
+        optional_services: vec![uuid(BATTERY)],
+        ..Default::default()
+    };
+    let result = request.match_device(&heart_rate_device()).unwrap();
+    assert_eq!(result.allowed_services, [uuid(BATTERY)].iter().cloned().collect());
+}
+
+#[test]
+fn allowed_services_do_not_depend_on_the_match() {
+    let request = options(vec![services_filter(&[GLUCOSE])]);
+    let result = request.match_device(&heart_rate_device()).unwrap();
+    assert!(!result.matched);
+    assert_eq!(result.allowed_services, [uuid(GLUCOSE)].iter().cloned().collect());
+}
+
+#[test]
+fn allowed_manufacturer_data() {
+    let mut request = options(vec![manufacturer_filter(APPLE, vec![], None)]);
+    request.optional_manufacturer_data = vec![NORDIC];
+    let result = request.match_device(&heart_rate_device()).unwrap();
+    assert_eq!(result.allowed_manufacturer_data, [APPLE, NORDIC].iter().cloned().collect());
+}
+
+#[test]
+fn matching_devices_of_an_adapter() {
+    let adapter = BluetoothAdapter::init_mock().unwrap();
+    create_device(&adapter, "heart_rate", Some("Heart Rate Monitor"), &[HEART_RATE]);
+    create_device(&adapter, "glucose", Some("Glucose Meter"), &[GLUCOSE]);
+    create_device(&adapter, "both", Some("Health Hub"), &[HEART_RATE, GLUCOSE]);
+    let mut request = options(vec![services_filter(&[HEART_RATE]), name_prefix_filter("Gluc")]);
+    request.exclusion_filters = Some(vec![name_filter("Health Hub")]);
+    let mut ids: Vec<String> = request.get_matching_devices(&adapter).unwrap().iter().map(|device| device.get_id()).collect();
+    ids.sort();
+    assert_eq!(ids, vec![String::from("glucose"), String::from("heart_rate")]);
+}
@@ -0,0 +1,242 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+#![cfg(feature = "bluetooth-test")]
+
+extern crate device;
+
+use device::blocklist::Blocklist;
+use device::bluetooth::{BluetoothAddress, BluetoothDevice, BluetoothError, BluetoothGATTService, BluetoothUuid};
+use device::fixture;
+use std::collections::HashMap;
+
+const HEART_RATE_FIXTURE: &str = r#"{
+    "id": "hci0",
+    "name": "Fixture adapter",
+    "powered": true,
+    "devices": [
+        {
+            "id": "heart_rate",
+            "address": "09:09:09:09:09:09",
+            "name": "Heart Rate Device ❤",
+            "connectable": true,
+            "rssi": -60,
+            "uuids": ["180d", "0000180f-0000-1000-8000-00805f9b34fb"],
+            "manufacturer_data": {"0x004C": [1, 2], "17": [3]},
+            "services": [
+                {
+                    "uuid": "180d",
+                    "characteristics": [
+                        {
+                            "uuid": "2a37",
+                            "flags": ["notify"],
+                            "value": [0, 60],
+                            "descriptors": [{"uuid": "2902", "flags": ["read", "write"], "value": [0, 0]}]
+                        },
+                        {"id": "body_sensor_location", "uuid": "2a38", "flags": ["read"], "value": [1]}
+                    ]
+                }
+            ]
+        },
+        {"address": "07:07:07:07:07:07", "name": null}
+    ]
+}"#;
+
+fn services(device: &BluetoothDevice) -> Vec<BluetoothGATTService> {
+    device.set_connected(true).unwrap();
+    device.get_gatt_services().unwrap()
+}
+
+#[test]
+fn load_heart_rate_fixture() {
+    let adapter = fixture::load_adapter(HEART_RATE_FIXTURE).unwrap();
+    assert_eq!(adapter.get_id(), "hci0");
+    assert_eq!(adapter.get_name(), Ok(String::from("Fixture adapter")));
+    assert_eq!(adapter.is_powered(), Ok(true));
+
+    let device = adapter.get_device(BluetoothAddress::parse("09:09:09:09:09:09").unwrap()).unwrap().unwrap();
+    assert_eq!(device.get_id(), "heart_rate");
+    assert_eq!(device.get_name(), Ok(String::from("Heart Rate Device \u{2764}")));
+    assert_eq!(device.get_rssi(), Ok(-60));
+    assert_eq!(device.get_uuids(), Ok(vec![BluetoothUuid::from_u16(0x180D), BluetoothUuid::from_u16(0x180F)]));
+    let mut manufacturer_data = HashMap::new();
+    manufacturer_data.insert(0x004C, vec![1, 2]);
+    manufacturer_data.insert(17, vec![3]);
+    assert_eq!(device.get_manufacturer_data(), Ok(manufacturer_data));
+
+    let service = services(&device).remove(0);
+    assert_eq!(service.get_id(), "heart_rate/service0000");
+    let characteristics = service.get_gatt_characteristics().unwrap();
+    assert_eq!(characteristics[0].get_id(), "heart_rate/service0000/char0000");
+    assert_eq!(characteristics[0].get_value(), Ok(vec![0, 60]));
+    assert_eq!(characteristics[1].get_id(), "body_sensor_location");
+    let descriptor = characteristics[0].get_gatt_descriptors().unwrap().remove(0);
+    assert_eq!(descriptor.get_uuid(), Ok(BluetoothUuid::from_u16(0x2902)));
+    assert_eq!(descriptor.get_flags(), Ok(vec![String::from("read"), String::from("write")]));
+
+    let unnamed = adapter.get_device_by_id("hci0/dev0001").unwrap().unwrap();
+    assert!(unnamed.get_name().is_err());
+}
+
+#[test]
+fn dump_and_load_again() {
+    let adapter = fixture::load_adapter(HEART_RATE_FIXTURE).unwrap();
+    let dump = fixture::dump_adapter(&adapter).unwrap();
+    assert!(dump.contains("\"value\": [0, 60]"));
+    assert!(dump.contains("\"0x0011\": [3]"));
+
+    let reloaded = fixture::load_adapter(&dump).unwrap();
+    assert_eq!(fixture::dump_adapter(&reloaded), Ok(dump));
+    // Dumping does not connect the devices.
+    let device = reloaded.get_device_by_id("heart_rate").unwrap().unwrap();
+    assert_eq!(device.is_connected(), Ok(false));
+}
+
+#[test]
+fn dump_ignores_the_blocklist() {
+    let adapter = fixture::load_adapter(HEART_RATE_FIXTURE).unwrap();
+    let dump = fixture::dump_adapter(&adapter).unwrap();
+    let blocklist = "00002a38-0000-1000-8000-00805f9b34fb\n00002902-0000-1000-8000-00805f9b34fb exclude";
+    adapter.set_blocklist(Some(Blocklist::parse(blocklist).unwrap()));
+    let device = adapter.get_device_by_id("heart_rate").unwrap().unwrap();
+    let characteristics = services(&device).remove(0).get_gatt_characteristics().unwrap();
+    assert_eq!(characteristics.len(), 1);
+    assert!(characteristics[0].get_gatt_descriptors().unwrap().is_empty());
+    device.set_connected(false).unwrap();
+
+    assert_eq!(fixture::dump_adapter(&adapter), Ok(dump.clone()));
+    let reloaded = fixture::load_adapter(&dump).unwrap();
+    reloaded.set_blocklist(Some(Blocklist::parse(blocklist).unwrap()));
+    assert_eq!(fixture::dump_adapter(&reloaded), Ok(dump));
+}
+
+#[test]
+fn dump_escapes_names() {
+    let names = ["caf\u{e9} \u{2764} \u{1F600}", "\u{0}\u{1}\u{1f} \u{7f}", "\"quoted\" \\ back\nslash\r\t"];
+    let adapter = fixture::load_adapter("{}").unwrap();
+    adapter.set_name(String::from(names[0])).unwrap();
+    for (index, name) in names.iter().enumerate() {
+        let device = BluetoothDevice::create_mock_device(adapter.clone(), format!("device{}", index)).unwrap();
+        device.set_name(Some(String::from(*name))).unwrap();
+    }
+    let dump = fixture::dump_adapter(&adapter).unwrap();
+    assert!(dump.contains("\"caf\u{e9} \u{2764} \u{1F600}\""));
+    // Only the control characters below 0x20 are escaped.
+    assert!(dump.contains("\"\\u0000\\u0001\\u001f \u{7f}\""));
+    assert!(dump.contains(r#""\"quoted\" \\ back\nslash\r\t""#));
+
+    let reloaded = fixture::load_adapter(&dump).unwrap();
+    assert_eq!(reloaded.get_name(), Ok(String::from(names[0])));
+    for (index, name) in names.iter().enumerate() {
+        let device = reloaded.get_device_by_id(&format!("device{}", index)).unwrap().unwrap();
+        assert_eq!(device.get_name(), Ok(String::from(*name)));
+    }
+    assert_eq!(fixture::dump_adapter(&reloaded), Ok(dump));
+}
+
+#[test]
+fn invalid_fixtures() {
+    let errors = [
+        ("{\"devices\": [{\"services\": [{\"uuid\": \"heart rate\"}]}]}", "adapter.devices[0].services[0].uuid"),
+        ("{\"devices\": [{\"rssi\": 40000}]}", "adapter.devices[0].rssi"),
+        ("{\"devices\": [{\"colour\": \"red\"}]}", "unknown field 'colour'"),
+        ("{\"name\": \"adapter\",}", "at byte 19"),
+        ("{\"powered\": true} true", "after the document"),
+    ];
+    for &(fixture, message) in &errors {
+        match fixture::load_adapter(fixture) {
+            Err(BluetoothError::InvalidArgument(error)) => assert!(error.contains(message), "{}", error),
+            result => panic!("{:?}", result),
+        }
+    }
+}
+
+fn assert_invalid(fixture: &str, message: &str) {
+    match fixture::load_adapter(fixture) {
+        Err(BluetoothError::InvalidArgument(error)) => assert!(error.contains(message), "{}: {}", fixture, error),
+        result => panic!("{}: {:?}", fixture, result),
+    }
+}
+
+fn adapter_name(fixture: &str) -> String {
+    fixture::load_adapter(fixture).unwrap().get_name().unwrap()
+}
+
+#[test]
+fn string_escapes() {
+    assert_eq!(adapter_name(r#"{"name": "\"\\\/\b\f\n\r\t"}"#), "\"\\/\u{8}\u{c}\n\r\t");
+    assert_eq!(adapter_name(r#"{"name": "caf\u00e9 \u00E9\u0000"}"#), "caf\u{e9} \u{e9}\u{0}");
+    assert_eq!(adapter_name(r#"{"name": "café ❤"}"#), "caf\u{e9} \u{2764}");
+    assert_eq!(adapter_name(r#"{"name": ""}"#), "");
+    assert_invalid(r#"{"name": "\x41"}"#, "invalid escape");
+    assert_invalid(r#"{"name": "\u12"}"#, "invalid escape");
+    assert_invalid(r#"{"name": "\u12g4"}"#, "invalid escape");
+    assert_invalid("{\"name\": \"line\nbreak\"}", "unterminated string");
+    assert_invalid(r#"{"name": "open"#, "unterminated string");
+    assert_invalid(r#"{"name": "open\"#, "unterminated string");
+}
+
+#[test]
+fn surrogate_pairs() {
+    assert_eq!(adapter_name(r#"{"name": "\ud83d\udc93"}"#), "\u{1f493}");
+    assert_eq!(adapter_name(r#"{"name": "\ud800\udc00\udbff\udfff"}"#), "\u{10000}\u{10ffff}");
+    // Lone surrogates are not characters.
+    assert_invalid(r#"{"name": "\ud83d"}"#, "invalid escape");
+    assert_invalid(r#"{"name": "\ud83d."}"#, "invalid escape");
+    assert_invalid(r#"{"name": "\udc93"}"#, "invalid escape");
+    assert_invalid(r#"{"name": "\ud83dA"}"#, "invalid escape");
+    assert_invalid(r#"{"name": "\ud83d\ud83d"}"#, "invalid escape");
+}
+
+#[test]
+fn nesting() {
+    let adapter = fixture::load_adapter(" \t\r\n{ \"devices\" : [ ] , \"uuids\" : [ ] } \n").unwrap();
+    assert!(adapter.get_devices().unwrap().is_empty());
+    assert_eq!(adapter.get_uuids(), Ok(vec![]));
+    let adapter = fixture::load_adapter(r#"{"id": "hci0", "devices": [{"services": [{"characteristics": [{"descriptors": [{
+        "value": [1, 2, 3]
+    }]}]}]}]}"#).unwrap();
+    let device = adapter.get_devices().unwrap().remove(0);
+    let characteristic = services(&device).remove(0).get_gatt_characteristics().unwrap().remove(0);
+    let descriptor = characteristic.get_gatt_descriptors().unwrap().remove(0);
+    assert_eq!(descriptor.get_id(), "hci0/dev0000/service0000/char0000/desc0000");
+    assert_eq!(descriptor.get_value(), Ok(vec![1, 2, 3]));
+
+    assert_invalid("", "expected a value");
+    assert_invalid("{", "expected '\"'");
+    assert_invalid(r#"{"devices": [{}"#, "expected ',' or ']'");
+    assert_invalid(r#"{"uuids": ["180d" "180f"]}"#, "expected ',' or ']'");
+    assert_invalid(r#"{"uuids": ["180d",]}"#, "expected a value");
+    assert_invalid(r#"{"name" "adapter"}"#, "expected ':'");
+    assert_invalid(r#"{"name": "adapter" "alias": "adapter"}"#, "expected ',' or '}'");
+    assert_invalid(r#"{"name": "adapter", "name": "adapter"}"#, "duplicate field 'name'");
+    assert_invalid(r#"{"powered": tru}"#, "expected a value");
+    assert_invalid(r#"[]"#, "adapter");
+    assert_invalid(r#"{"devices": {}}"#, "adapter.devices");
+}
+
+#[test]
+fn numbers() {
+    let rssi = |fixture: &str| {
+        let adapter = fixture::load_adapter(fixture).unwrap();
+        adapter.get_devices().unwrap().remove(0).get_rssi().unwrap()
+    };
+    assert_eq!(rssi(r#"{"devices": [{"rssi": 0}]}"#), 0);
+    assert_eq!(rssi(r#"{"devices": [{"rssi": -0}]}"#), 0);
+    assert_eq!(rssi(r#"{"devices": [{"rssi": -32768}]}"#), -32768);
+    assert_eq!(rssi(r#"{"devices": [{"rssi": 32767}]}"#), 32767);
+    assert_invalid(r#"{"devices": [{"rssi": -32769}]}"#, "adapter.devices[0].rssi");
+    assert_invalid(r#"{"devices": [{"rssi": 01}]}"#, "expected an integer");
+    assert_invalid(r#"{"devices": [{"rssi": -01}]}"#, "expected an integer");
+    assert_invalid(r#"{"devices": [{"rssi": -}]}"#, "expected an integer");
+    assert_invalid(r#"{"devices": [{"rssi": 1.5}]}"#, "expected an integer");
+    assert_invalid(r#"{"devices": [{"rssi": 1e3}]}"#, "expected an integer");
+    assert_invalid(r#"{"devices": [{"rssi": +1}]}"#, "expected a value");
+    assert_invalid(r#"{"devices": [{"rssi": 9223372036854775808}]}"#, "expected an integer");
+    assert_invalid(r#"{"devices": [{"rssi": "-60"}]}"#, "adapter.devices[0].rssi");
+    assert_invalid(r#"{"devices": [{"services": [{"characteristics": [{"value": [256]}]}]}]}"#,
+                   "adapter.devices[0].services[0].characteristics[0].value");
+    assert_invalid(r#"{"devices": [{"services": [{"characteristics": [{"value": [-1]}]}]}]}"#,
+                   "adapter.devices[0].services[0].characteristics[0].value");
+}
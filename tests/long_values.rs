@@ -0,0 +1,254 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+#![cfg(feature = "bluetooth-test")]
+
+extern crate device;
+
+use device::bluetooth::{BluetoothAdapter, BluetoothDevice, BluetoothError, BluetoothGATTCharacteristic};
+use device::bluetooth::{BluetoothGATTDescriptor, BluetoothGATTService};
+use device::error::{ATT_INVALID_ATTRIBUTE_VALUE_LENGTH, ATT_INVALID_OFFSET};
+
+fn create_device() -> BluetoothDevice {
+    let adapter = BluetoothAdapter::init_mock().unwrap();
+    let device = BluetoothDevice::create_mock_device(adapter, String::from("device")).unwrap();
+    device.set_connected(true).unwrap();
+    device
+}
+
+fn create_characteristic_on(device: &BluetoothDevice, flags: &[&str]) -> BluetoothGATTCharacteristic {
+    let service = BluetoothGATTService::create_mock_service(device.clone(), String::from("service")).unwrap();
+    let characteristic =
+        BluetoothGATTCharacteristic::create_mock_characteristic(service, String::from("characteristic")).unwrap();
+    characteristic.set_flags(flags.iter().map(|flag| String::from(*flag)).collect()).unwrap();
+    characteristic
+}
+
+fn create_characteristic(flags: &[&str]) -> BluetoothGATTCharacteristic {
+    create_characteristic_on(&create_device(), flags)
+}
+
+fn long_value(length: usize) -> Vec<u8> {
+    (0..length).map(|index| index as u8).collect()
+}
+
+#[test]
+fn read_value_at_is_limited_by_the_mtu() {
+    let characteristic = create_characteristic(&["read"]);
+    characteristic.set_value(long_value(50)).unwrap();
+    // The default ATT_MTU is 23, so a Read Blob Response carries 22 bytes.
+    assert_eq!(characteristic.read_value_at(0).unwrap(), long_value(50)[..22].to_vec());
+    assert_eq!(characteristic.read_value_at(44).unwrap(), long_value(50)[44..].to_vec());
+    assert_eq!(characteristic.read_value_at(50).unwrap(), vec![]);
+    assert_eq!(characteristic.read_value_at(51), Err(BluetoothError::GattError(ATT_INVALID_OFFSET)));
+}
+
+#[test]
+fn read_value_at_offsets_assemble_the_value() {
+    let characteristic = create_characteristic(&["read"]);
+    characteristic.set_value(long_value(100)).unwrap();
+    let mut value = vec![];
+    loop {
+        let part = characteristic.read_value_at(value.len() as u16).unwrap();
+        let length = part.len();
+        value.extend(part);
+        if length < 22 {
+            break;
+        }
+    }
+    assert_eq!(value, long_value(100));
+}
+
+#[test]
+fn descriptor_read_value_at() {
+    let characteristic = create_characteristic(&["read"]);
+    let descriptor =
+        BluetoothGATTDescriptor::create_mock_descriptor(characteristic, String::from("descriptor")).unwrap();
+    descriptor.set_value(long_value(30)).unwrap();
+    assert_eq!(descriptor.read_value_at(10).unwrap(), long_value(30)[10..].to_vec());
+}
+
+#[test]
+fn write_long_value() {
+    let characteristic = create_characteristic(&["write"]);
+    characteristic.set_value(long_value(100)).unwrap();
+    characteristic.write_long_value(long_value(40)).unwrap();
+    assert_eq!(characteristic.get_value().unwrap(), long_value(40));
+    assert_eq!(characteristic.write_long_value(long_value(513)),
+               Err(BluetoothError::GattError(ATT_INVALID_ATTRIBUTE_VALUE_LENGTH)));
+    assert_eq!(characteristic.get_value().unwrap(), long_value(40));
+}
+
+#[test]
+fn reliable_write_requires_the_property() {
+    let characteristic = create_characteristic(&["write"]);
+    assert_eq!(characteristic.begin_reliable_write().err(), Some(BluetoothError::NotSupported));
+}
+
+#[test]
+fn prepared_writes_are_limited_by_the_mtu() {
+    let characteristic = create_characteristic(&["write", "reliable-write"]);
+    let value = long_value(40);
+    let mut reliable_write = characteristic.begin_reliable_write().unwrap();
+    reliable_write.prepare_write(0, value.clone()).unwrap();
+    // A Prepare Write Request carries ATT_MTU - 5 bytes.
+    assert_eq!(reliable_write.get_prepared_writes(), &[
+        (0, value[..18].to_vec()),
+        (18, value[18..36].to_vec()),
+        (36, value[36..].to_vec()),
+    ]);
+    reliable_write.execute().unwrap();
+    assert_eq!(characteristic.get_value().unwrap(), value);
+}
+
+#[test]
+fn reliable_write_applies_the_parts_at_their_offsets() {
+    let characteristic = create_characteristic(&["write", "reliable-write"]);
+    characteristic.set_value(vec![1, 2, 3, 4]).unwrap();
+    let mut reliable_write = characteristic.begin_reliable_write().unwrap();
+    reliable_write.prepare_write(2, vec![5, 6]).unwrap();
+    reliable_write.prepare_write(4, vec![7]).unwrap();
+    reliable_write.execute().unwrap();
+    assert_eq!(characteristic.get_value().unwrap(), vec![1, 2, 5, 6, 7]);
+}
+
+#[test]
+fn overlapping_parts_keep_the_bytes_after_them() {
+    let characteristic = create_characteristic(&["write", "reliable-write"]);
+    let mut reliable_write = characteristic.begin_reliable_write().unwrap();
+    reliable_write.prepare_write(0, vec![1, 2, 3, 4]).unwrap();
+    reliable_write.prepare_write(1, vec![9]).unwrap();
+    reliable_write.prepare_write(3, vec![8, 7]).unwrap();
+    reliable_write.execute().unwrap();
+    assert_eq!(characteristic.get_value().unwrap(), vec![1, 9, 3, 8, 7]);
+}
+
+#[test]
+fn short_part_at_offset_0_keeps_the_tail() {
+    let characteristic = create_characteristic(&["read", "write", "reliable-write"]);
+    characteristic.set_value(vec![1, 2, 3, 4, 5, 6]).unwrap();
+    let mut reliable_write = characteristic.begin_reliable_write().unwrap();
+    reliable_write.prepare_write(0, vec![7, 8]).unwrap();
+    reliable_write.execute().unwrap();
+    assert_eq!(characteristic.get_value().unwrap(), vec![7, 8, 3, 4, 5, 6]);
+}
+
+#[test]
+fn write_long_value_replaces_the_whole_value() {
+    let characteristic = create_characteristic(&["read", "write", "reliable-write"]);
+    characteristic.set_value(long_value(100)).unwrap();
+    characteristic.write_long_value(vec![7, 8]).unwrap();
+    assert_eq!(characteristic.get_value().unwrap(), vec![7, 8]);
+}
+
+#[test]
+fn unreadable_value_is_written_with_the_parts_alone() {
+    // The mock value can not be read before it is set.
+    let characteristic = create_characteristic(&["write", "reliable-write"]);
+    let mut reliable_write = characteristic.begin_reliable_write().unwrap();
+    reliable_write.prepare_write(1, vec![1]).unwrap();
+    assert_eq!(reliable_write.execute(), Err(BluetoothError::GattError(ATT_INVALID_OFFSET)));
+    let mut reliable_write = characteristic.begin_reliable_write().unwrap();
+    reliable_write.prepare_write(0, vec![1]).unwrap();
+    reliable_write.execute().unwrap();
+    assert_eq!(characteristic.get_value().unwrap(), vec![1]);
+}
+
+#[test]
+fn parts_given_out_of_order() {
+    let characteristic = create_characteristic(&["write", "reliable-write"]);
+    characteristic.set_value(vec![1, 2, 3, 4, 5, 6]).unwrap();
+    let mut reliable_write = characteristic.begin_reliable_write().unwrap();
+    reliable_write.prepare_write(4, vec![9, 9]).unwrap();
+    reliable_write.prepare_write(0, vec![7]).unwrap();
+    reliable_write.prepare_write(2, vec![8]).unwrap();
+    reliable_write.execute().unwrap();
+    assert_eq!(characteristic.get_value().unwrap(), vec![7, 2, 8, 4, 9, 9]);
+}
+
+#[test]
+fn reliable_write_with_an_invalid_offset_writes_nothing() {
+    let characteristic = create_characteristic(&["write", "reliable-write"]);
+    characteristic.set_value(vec![1, 2]).unwrap();
+    let mut reliable_write = characteristic.begin_reliable_write().unwrap();
+    reliable_write.prepare_write(0, vec![3]).unwrap();
+    reliable_write.prepare_write(5, vec![4]).unwrap();
+    assert_eq!(reliable_write.execute(), Err(BluetoothError::GattError(ATT_INVALID_OFFSET)));
+    assert_eq!(characteristic.get_value().unwrap(), vec![1, 2]);
+}
+
+#[test]
+fn aborted_reliable_write_writes_nothing() {
+    let characteristic = create_characteristic(&["write", "reliable-write"]);
+    characteristic.set_value(vec![1, 2]).unwrap();
+    let mut reliable_write = characteristic.begin_reliable_write().unwrap();
+    reliable_write.prepare_write(0, vec![3, 4, 5]).unwrap();
+    reliable_write.abort();
+    assert_eq!(characteristic.get_value().unwrap(), vec![1, 2]);
+}
+
+#[test]
+fn request_mtu_is_limited_by_the_server_mtu() {
+    let device = create_device();
+    assert_eq!(device.get_mtu(), Ok(23));
+    assert_eq!(device.request_mtu(100), Ok(100));
+    device.set_server_mtu(64).unwrap();
+    assert_eq!(device.request_mtu(100), Ok(64));
+    assert_eq!(device.get_mtu(), Ok(64));
+    assert!(device.request_mtu(22).is_err());
+}
+
+#[test]
+fn mtu_is_reset_by_a_new_connection() {
+    let device = create_device();
+    device.set_mtu(185).unwrap();
+    device.disconnect().unwrap();
+    assert_eq!(device.get_mtu(), Err(BluetoothError::NotConnected));
+    assert_eq!(device.request_mtu(185), Err(BluetoothError::NotConnected));
+    device.set_connectable(true).unwrap();
+    device.connect().unwrap();
+    assert_eq!(device.get_mtu(), Ok(23));
+}
+
+#[test]
+fn transfers_use_the_exchanged_mtu() {
+    let device = create_device();
+    let characteristic = create_characteristic_on(&device, &["read", "write", "reliable-write"]);
+    characteristic.set_value(long_value(100)).unwrap();
+    device.request_mtu(50).unwrap();
+    assert_eq!(characteristic.read_value_at(0).unwrap().len(), 49);
+    let mut reliable_write = characteristic.begin_reliable_write().unwrap();
+    reliable_write.prepare_write(0, long_value(100)).unwrap();
+    let lengths: Vec<usize> = reliable_write.get_prepared_writes().iter().map(|(_, part)| part.len()).collect();
+    assert_eq!(lengths, vec![45, 45, 10]);
+}
+
+#[test]
+fn mtu_is_forgotten_with_the_device() {
+    let adapter = BluetoothAdapter::init_mock().unwrap();
+    let device = BluetoothDevice::create_mock_device(adapter.clone(), String::from("device")).unwrap();
+    device.set_connected(true).unwrap();
+    device.set_server_mtu(64).unwrap();
+    device.set_mtu(64).unwrap();
+    adapter.remove_device(String::from("device")).unwrap();
+
+    let device = BluetoothDevice::create_mock_device(adapter, String::from("device")).unwrap();
+    device.set_connected(true).unwrap();
+    assert_eq!(device.get_mtu(), Ok(23));
+    assert_eq!(device.request_mtu(100), Ok(100));
+}
+
+#[test]
+fn mtu_is_reset_when_the_mock_device_disconnects() {
+    let device = create_device();
+    device.request_mtu(185).unwrap();
+    device.set_connected(false).unwrap();
+    device.set_connected(true).unwrap();
+    assert_eq!(device.get_mtu(), Ok(23));
+    // The server MTU is a property of the device, not of the connection.
+    device.set_server_mtu(64).unwrap();
+    device.set_connected(false).unwrap();
+    device.set_connected(true).unwrap();
+    assert_eq!(device.request_mtu(185), Ok(64));
+}
@@ -0,0 +1,80 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+#![cfg(feature = "bluetooth-test")]
+
+extern crate device;
+
+use device::bluetooth::{BluetoothAdapter, BluetoothDevice, BluetoothError};
+use device::manager::AdapterManager;
+
+#[test]
+fn adapters_of_different_backends() {
+    let manager = AdapterManager::new();
+    let system_id = BluetoothAdapter::init().ok().map(|adapter| manager.add_adapter(adapter).unwrap());
+    let heart_rate = manager.add_mock_adapter(String::from("heart_rate")).unwrap();
+    manager.add_mock_adapter(String::from("empty")).unwrap();
+    BluetoothDevice::create_mock_device(heart_rate, String::from("device")).unwrap();
+
+    let mock = manager.get_adapter("heart_rate").unwrap();
+    assert!(mock.is_mock());
+    assert_eq!(mock.get_devices().unwrap().len(), 1);
+    assert_eq!(manager.get_adapter("empty").unwrap().get_devices().unwrap().len(), 0);
+    if let Some(system_id) = system_id {
+        let system_adapter = manager.get_adapter(&system_id).unwrap();
+        assert!(!system_adapter.is_mock());
+        assert_eq!(system_adapter.set_name(String::from("name")), Err(BluetoothError::NotSupported));
+        assert_eq!(manager.get_adapters().len(), 3);
+    }
+}
+
+#[test]
+fn default_adapter() {
+    let manager = AdapterManager::new();
+    assert!(manager.get_default_adapter().is_none());
+    manager.add_mock_adapter(String::from("first")).unwrap();
+    manager.add_mock_adapter(String::from("second")).unwrap();
+    assert_eq!(manager.get_default_adapter().unwrap().get_id(), "first");
+
+    assert_eq!(manager.set_default_adapter("second"), Ok(Some(String::from("first"))));
+    assert_eq!(manager.get_default_adapter_id(), Some(String::from("second")));
+    assert_eq!(manager.set_default_adapter("third"), Err(BluetoothError::NotFound));
+
+    manager.remove_adapter("second").unwrap();
+    assert!(manager.get_default_adapter().is_none());
+    assert_eq!(manager.get_adapter_ids(), vec![String::from("first")]);
+}
+
+#[test]
+fn adapter_ids_are_unique() {
+    let manager = AdapterManager::new();
+    manager.add_mock_adapter(String::from("adapter")).unwrap();
+    assert_eq!(manager.add_mock_adapter(String::from("adapter")).err(), Some(BluetoothError::InvalidState));
+    assert_eq!(manager.remove_adapter("other").err(), Some(BluetoothError::NotFound));
+}
+
+#[test]
+fn adapter_can_be_replaced() {
+    let manager = AdapterManager::new();
+    let original = manager.add_mock_adapter(String::from("adapter")).unwrap();
+    original.set_name(String::from("original")).unwrap();
+    let replacement = BluetoothAdapter::init_mock().unwrap();
+    replacement.set_name(String::from("replacement")).unwrap();
+    replacement.set_id(String::from("adapter"));
+
+    let replaced = manager.replace_adapter("adapter", replacement).unwrap();
+    assert_eq!(replaced.get_name(), Ok(String::from("original")));
+    assert_eq!(manager.get_default_adapter().unwrap().get_name(), Ok(String::from("replacement")));
+}
+
+#[test]
+fn replacement_needs_the_same_id() {
+    let manager = AdapterManager::new();
+    manager.add_mock_adapter(String::from("adapter")).unwrap();
+    let other = BluetoothAdapter::init_mock().unwrap();
+    other.set_id(String::from("other"));
+    assert!(matches!(manager.replace_adapter("adapter", other.clone()), Err(BluetoothError::InvalidArgument(_))));
+    assert_eq!(manager.get_adapter_ids(), vec![String::from("adapter")]);
+    assert_eq!(manager.replace_adapter("other", other).err(), Some(BluetoothError::NotFound));
+}
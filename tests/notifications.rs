@@ -0,0 +1,82 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+#![cfg(feature = "bluetooth-test")]
+
+extern crate device;
+
+use device::bluetooth::{BluetoothAdapter, BluetoothDevice, BluetoothError, BluetoothEvent};
+use device::bluetooth::{BluetoothGATTCharacteristic, BluetoothGATTService};
+
+fn create_characteristic() -> (BluetoothAdapter, BluetoothGATTCharacteristic) {
+    let adapter = BluetoothAdapter::init_mock().unwrap();
+    let device = BluetoothDevice::create_mock_device(adapter.clone(), String::from("device")).unwrap();
+    device.set_connected(true).unwrap();
+    let service = BluetoothGATTService::create_mock_service(device, String::from("service")).unwrap();
+    let characteristic =
+        BluetoothGATTCharacteristic::create_mock_characteristic(service, String::from("characteristic")).unwrap();
+    characteristic.set_flags(vec![String::from("notify")]).unwrap();
+    characteristic.set_value(vec![0]).unwrap();
+    (adapter, characteristic)
+}
+
+#[test]
+fn pushed_notifications_arrive_in_order() {
+    let (_adapter, characteristic) = create_characteristic();
+    characteristic.start_notify().unwrap();
+    let first = characteristic.get_notifications().unwrap();
+    let second = characteristic.get_notifications().unwrap();
+    for value in 1..4 {
+        characteristic.push_notification(vec![value]).unwrap();
+    }
+    // A value equal to the current one is delivered too.
+    characteristic.push_notification(vec![3]).unwrap();
+    for receiver in &[first, second] {
+        let values: Vec<Vec<u8>> = receiver.try_iter().collect();
+        assert_eq!(values, vec![vec![1], vec![2], vec![3], vec![3]]);
+    }
+    assert_eq!(characteristic.get_value(), Ok(vec![3]));
+}
+
+#[test]
+fn pushed_notifications_reach_the_adapter_subscribers() {
+    let (adapter, characteristic) = create_characteristic();
+    let events = adapter.subscribe();
+    characteristic.start_notify().unwrap();
+    characteristic.push_notification(vec![7]).unwrap();
+    assert_eq!(events.try_recv(), Ok(BluetoothEvent::CharacteristicValueChanged(characteristic.get_id(), vec![7])));
+}
+
+#[test]
+fn push_without_start_notify_leaves_the_value() {
+    let (_adapter, characteristic) = create_characteristic();
+    assert_eq!(characteristic.push_notification(vec![1]), Err(BluetoothError::InvalidState));
+    assert_eq!(characteristic.get_value(), Ok(vec![0]));
+    assert_eq!(characteristic.get_notifications().err(), Some(BluetoothError::InvalidState));
+}
+
+#[test]
+fn no_notifications_after_stop_notify() {
+    let (_adapter, characteristic) = create_characteristic();
+    characteristic.start_notify().unwrap();
+    let receiver = characteristic.get_notifications().unwrap();
+    characteristic.push_notification(vec![1]).unwrap();
+    characteristic.stop_notify().unwrap();
+    assert_eq!(characteristic.push_notification(vec![2]), Err(BluetoothError::InvalidState));
+    // The receiver is disconnected once the notifications stopped.
+    assert_eq!(receiver.iter().collect::<Vec<_>>(), vec![vec![1]]);
+    assert_eq!(characteristic.get_value(), Ok(vec![1]));
+}
+
+#[test]
+fn no_notifications_after_the_device_is_removed() {
+    let (adapter, characteristic) = create_characteristic();
+    characteristic.start_notify().unwrap();
+    let receiver = characteristic.get_notifications().unwrap();
+    characteristic.push_notification(vec![1]).unwrap();
+    adapter.remove_device(String::from("device")).unwrap();
+    assert_eq!(characteristic.push_notification(vec![2]), Err(BluetoothError::InvalidState));
+    assert_eq!(receiver.iter().collect::<Vec<_>>(), vec![vec![1]]);
+    assert!(adapter.get_device_by_id("device").unwrap().is_none());
+}
@@ -0,0 +1,155 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+#![cfg(feature = "bluetooth-test")]
+
+extern crate device;
+
+use device::bluetooth::{BluetoothAdapter, BluetoothDevice, BluetoothError, BluetoothGATTCharacteristic};
+use device::bluetooth::{BluetoothGATTDescriptor, BluetoothGATTService};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+const OPERATION_DURATION: Duration = Duration::from_millis(40);
+
+fn create_device(adapter: &BluetoothAdapter, id: &str) -> BluetoothDevice {
+    let device = BluetoothDevice::create_mock_device(adapter.clone(), String::from(id)).unwrap();
+    device.set_connected(true).unwrap();
+    device.set_mock_operation_duration(OPERATION_DURATION).unwrap();
+    device
+}
+
+fn create_characteristics(device: &BluetoothDevice, count: usize) -> Vec<BluetoothGATTCharacteristic> {
+    let service = BluetoothGATTService::create_mock_service(device.clone(), String::from("service")).unwrap();
+    (0..count).map(|index| {
+        let id = format!("characteristic_{}", index);
+        let characteristic = BluetoothGATTCharacteristic::create_mock_characteristic(service.clone(), id).unwrap();
+        characteristic.set_value(vec![index as u8]).unwrap();
+        characteristic
+    }).collect()
+}
+
+fn read_concurrently(characteristics: Vec<BluetoothGATTCharacteristic>) -> Vec<Result<Vec<u8>, BluetoothError>> {
+    let threads: Vec<JoinHandle<_>> = characteristics.into_iter()
+        .map(|characteristic| thread::spawn(move || characteristic.read_value()))
+        .collect();
+    threads.into_iter().map(|thread| thread.join().unwrap()).collect()
+}
+
+fn wait_for_pending(device: &BluetoothDevice, count: usize) {
+    let deadline = Instant::now() + Duration::from_secs(5);
+    while device.get_pending_operations() < count {
+        assert!(Instant::now() < deadline, "{} pending operations", device.get_pending_operations());
+        thread::sleep(Duration::from_millis(1));
+    }
+}
+
+#[test]
+fn mock_rejects_overlapping_operations_without_queue() {
+    let adapter = BluetoothAdapter::init_mock().unwrap();
+    let device = create_device(&adapter, "device");
+    device.set_operation_queue_enabled(false).unwrap();
+    let results = read_concurrently(create_characteristics(&device, 4));
+    assert!(results.contains(&Err(BluetoothError::InvalidState)), "{:?}", results);
+}
+
+#[test]
+fn queued_operations_do_not_overlap() {
+    let adapter = BluetoothAdapter::init_mock().unwrap();
+    let device = create_device(&adapter, "device");
+    let start = Instant::now();
+    let results = read_concurrently(create_characteristics(&device, 4));
+    assert_eq!(results, (0..4).map(|index| Ok(vec![index])).collect::<Vec<_>>());
+    assert!(start.elapsed() >= OPERATION_DURATION * 4);
+}
+
+#[test]
+fn characteristic_and_descriptor_operations_share_the_queue() {
+    let adapter = BluetoothAdapter::init_mock().unwrap();
+    let device = create_device(&adapter, "device");
+    let characteristic = create_characteristics(&device, 1).remove(0);
+    let descriptor =
+        BluetoothGATTDescriptor::create_mock_descriptor(characteristic.clone(), String::from("descriptor")).unwrap();
+    let threads = vec![
+        thread::spawn(move || descriptor.write_value(vec![1])),
+        thread::spawn(move || characteristic.write_value(vec![2])),
+        {
+            let characteristic = create_characteristics(&device, 2).remove(1);
+            thread::spawn(move || characteristic.start_notify())
+        },
+    ];
+    for thread in threads {
+        assert_eq!(thread.join().unwrap(), Ok(()));
+    }
+}
+
+#[test]
+fn devices_have_their_own_queue() {
+    let adapter = BluetoothAdapter::init_mock().unwrap();
+    let mut characteristics = vec![];
+    for id in &["first", "second", "third", "fourth"] {
+        characteristics.extend(create_characteristics(&create_device(&adapter, id), 1));
+    }
+    let start = Instant::now();
+    assert!(read_concurrently(characteristics).iter().all(Result::is_ok));
+    assert!(start.elapsed() < OPERATION_DURATION * 4);
+}
+
+#[test]
+fn device_added_again_keeps_its_queue() {
+    let adapter = BluetoothAdapter::init_mock().unwrap();
+    let removed = create_characteristics(&create_device(&adapter, "device"), 1).remove(0);
+    adapter.remove_device(String::from("device")).unwrap();
+    let added = create_characteristics(&create_device(&adapter, "device"), 2).remove(1);
+    let start = Instant::now();
+    assert_eq!(read_concurrently(vec![removed, added]), vec![Ok(vec![0]), Ok(vec![1])]);
+    assert!(start.elapsed() >= OPERATION_DURATION * 2);
+}
+
+#[test]
+fn operations_run_in_queued_order() {
+    let adapter = BluetoothAdapter::init_mock().unwrap();
+    let device = create_device(&adapter, "device");
+    device.set_mock_operation_duration(Duration::from_millis(100)).unwrap();
+    let characteristics = create_characteristics(&device, 5);
+    let mut threads = vec![];
+    for (index, characteristic) in characteristics.into_iter().enumerate() {
+        threads.push(thread::spawn(move || {
+            characteristic.read_value().unwrap();
+            (index, Instant::now())
+        }));
+        // Every thread queues its operation before the next one is started. The first
+        // operation runs right away, the others wait.
+        if index == 0 {
+            thread::sleep(Duration::from_millis(10));
+        } else {
+            wait_for_pending(&device, index);
+        }
+    }
+    let mut finished: Vec<(usize, Instant)> = threads.into_iter().map(|thread| thread.join().unwrap()).collect();
+    finished.sort_by_key(|&(_, instant)| instant);
+    assert_eq!(finished.iter().map(|&(index, _)| index).collect::<Vec<_>>(), vec![0, 1, 2, 3, 4]);
+}
+
+#[test]
+fn pending_operations_can_be_cancelled() {
+    let adapter = BluetoothAdapter::init_mock().unwrap();
+    let device = create_device(&adapter, "device");
+    device.set_mock_operation_duration(Duration::from_millis(200)).unwrap();
+    let mut characteristics = create_characteristics(&device, 4);
+    let running = characteristics.remove(0);
+    let running = thread::spawn(move || running.read_value());
+    thread::sleep(Duration::from_millis(20));
+    let mut waiting = vec![];
+    for (index, characteristic) in characteristics.into_iter().enumerate() {
+        waiting.push(thread::spawn(move || characteristic.read_value()));
+        wait_for_pending(&device, index + 1);
+    }
+    assert_eq!(device.cancel_pending_operations(), 3);
+    for thread in waiting {
+        assert_eq!(thread.join().unwrap(), Err(BluetoothError::Cancelled));
+    }
+    assert_eq!(running.join().unwrap(), Ok(vec![0]));
+    assert_eq!(device.get_pending_operations(), 0);
+}
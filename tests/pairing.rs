@@ -0,0 +1,165 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+#![cfg(feature = "bluetooth-test")]
+
+extern crate device;
+
+use device::bluetooth::{BluetoothAdapter, BluetoothDevice, BluetoothError, BluetoothUuid};
+use device::pairing::{get_association_model, AgentRequest, AssociationModel, IoCapability, ScriptedAgent};
+use std::sync::Arc;
+
+const DEVICE_ID: &str = "device";
+const PASSKEY: u32 = 123456;
+
+const IO_CAPABILITIES: [IoCapability; 5] = [
+    IoCapability::DisplayOnly,
+    IoCapability::DisplayYesNo,
+    IoCapability::KeyboardOnly,
+    IoCapability::NoInputNoOutput,
+    IoCapability::KeyboardDisplay,
+];
+
+fn create_device(io_capability: IoCapability) -> (BluetoothAdapter, BluetoothDevice) {
+    let adapter = BluetoothAdapter::init_mock().unwrap();
+    let device = BluetoothDevice::create_mock_device(adapter.clone(), String::from(DEVICE_ID)).unwrap();
+    device.set_io_capability(io_capability).unwrap();
+    device.set_pairing_passkey(PASSKEY).unwrap();
+    (adapter, device)
+}
+
+fn register(adapter: &BluetoothAdapter, agent: ScriptedAgent) -> Arc<ScriptedAgent> {
+    let agent = Arc::new(agent);
+    adapter.register_agent(agent.clone()).unwrap();
+    agent
+}
+
+fn cooperative_agent(io_capability: IoCapability) -> ScriptedAgent {
+    let mut agent = ScriptedAgent::new(io_capability);
+    agent.passkey = Some(PASSKEY);
+    agent.confirm = true;
+    agent
+}
+
+fn expected_requests(model: AssociationModel) -> Vec<AgentRequest> {
+    let id = String::from(DEVICE_ID);
+    match model {
+        AssociationModel::JustWorks => vec![],
+        AssociationModel::NumericComparison => vec![AgentRequest::RequestConfirmation(id, PASSKEY)],
+        AssociationModel::PasskeyInput | AssociationModel::PasskeyBothInput => vec![AgentRequest::RequestPasskey(id)],
+        AssociationModel::PasskeyDisplay =>
+            (0..7).map(|entered| AgentRequest::DisplayPasskey(id.clone(), PASSKEY, entered)).collect(),
+    }
+}
+
+#[test]
+fn association_models() {
+    use device::pairing::IoCapability::*;
+    assert_eq!(get_association_model(DisplayYesNo, DisplayYesNo), AssociationModel::NumericComparison);
+    assert_eq!(get_association_model(KeyboardOnly, DisplayOnly), AssociationModel::PasskeyInput);
+    assert_eq!(get_association_model(DisplayOnly, KeyboardOnly), AssociationModel::PasskeyDisplay);
+    assert_eq!(get_association_model(KeyboardOnly, KeyboardOnly), AssociationModel::PasskeyBothInput);
+    assert_eq!(get_association_model(KeyboardDisplay, NoInputNoOutput), AssociationModel::JustWorks);
+    assert_eq!(get_association_model(DisplayYesNo, DisplayOnly), AssociationModel::JustWorks);
+}
+
+#[test]
+fn pairing_with_every_io_capability() {
+    for &local in IO_CAPABILITIES.iter() {
+        for &remote in IO_CAPABILITIES.iter() {
+            let (adapter, device) = create_device(remote);
+            let agent = register(&adapter, cooperative_agent(local));
+            assert_eq!(device.pair(), Ok(()), "{:?} with {:?}", local, remote);
+            assert!(device.is_paired().unwrap());
+            assert_eq!(agent.get_requests(), expected_requests(get_association_model(local, remote)),
+                       "{:?} with {:?}", local, remote);
+        }
+    }
+}
+
+#[test]
+fn rejected_confirmation_fails() {
+    let (adapter, device) = create_device(IoCapability::DisplayYesNo);
+    let mut agent = cooperative_agent(IoCapability::DisplayYesNo);
+    agent.confirm = false;
+    register(&adapter, agent);
+    assert_eq!(device.pair(), Err(BluetoothError::PermissionDenied));
+    assert!(!device.is_paired().unwrap());
+}
+
+#[test]
+fn wrong_passkey_fails() {
+    let (adapter, device) = create_device(IoCapability::DisplayOnly);
+    let mut agent = cooperative_agent(IoCapability::KeyboardOnly);
+    agent.passkey = Some(654321);
+    register(&adapter, agent);
+    assert_eq!(device.pair(), Err(BluetoothError::PermissionDenied));
+    assert!(!device.is_paired().unwrap());
+}
+
+#[test]
+fn legacy_pairing_requests_the_pin_code() {
+    let (adapter, device) = create_device(IoCapability::KeyboardOnly);
+    device.set_legacy_pairing(true).unwrap();
+    device.set_pairing_pin_code(String::from("1234")).unwrap();
+    let mut agent = ScriptedAgent::new(IoCapability::KeyboardDisplay);
+    agent.pin_code = Some(String::from("0000"));
+    let agent = register(&adapter, agent);
+    assert_eq!(device.pair(), Err(BluetoothError::PermissionDenied));
+
+    adapter.unregister_agent().unwrap();
+    let mut agent_with_pin = ScriptedAgent::new(IoCapability::KeyboardDisplay);
+    agent_with_pin.pin_code = Some(String::from("1234"));
+    let agent_with_pin = register(&adapter, agent_with_pin);
+    assert_eq!(device.pair(), Ok(()));
+    assert_eq!(agent.get_requests(), vec![AgentRequest::RequestPinCode(String::from(DEVICE_ID))]);
+    assert_eq!(agent_with_pin.get_requests(), vec![AgentRequest::RequestPinCode(String::from(DEVICE_ID))]);
+}
+
+#[test]
+fn pairing_without_agent_is_just_works() {
+    let (adapter, device) = create_device(IoCapability::KeyboardDisplay);
+    assert_eq!(device.pair(), Ok(()));
+    assert!(adapter.get_agent().is_none());
+
+    let (_, legacy_device) = create_device(IoCapability::KeyboardDisplay);
+    legacy_device.set_legacy_pairing(true).unwrap();
+    assert_eq!(legacy_device.pair(), Err(BluetoothError::PermissionDenied));
+}
+
+#[test]
+fn rejected_pairing_cancels_the_agent() {
+    let (adapter, device) = create_device(IoCapability::DisplayYesNo);
+    device.set_pairing_rejected(true).unwrap();
+    let agent = register(&adapter, cooperative_agent(IoCapability::DisplayYesNo));
+    assert_eq!(device.pair(), Err(BluetoothError::PermissionDenied));
+    assert_eq!(agent.get_requests(), vec![
+        AgentRequest::RequestConfirmation(String::from(DEVICE_ID), PASSKEY),
+        AgentRequest::Cancel,
+    ]);
+}
+
+#[test]
+fn agent_registration() {
+    let (adapter, _) = create_device(IoCapability::NoInputNoOutput);
+    assert_eq!(adapter.unregister_agent(), Err(BluetoothError::NotFound));
+    register(&adapter, ScriptedAgent::new(IoCapability::DisplayOnly));
+    assert_eq!(adapter.register_agent(Arc::new(ScriptedAgent::new(IoCapability::DisplayOnly))),
+               Err(BluetoothError::InvalidState));
+    assert_eq!(adapter.unregister_agent(), Ok(()));
+}
+
+#[test]
+fn service_authorization() {
+    let heart_rate = BluetoothUuid::from_u16(0x180D);
+    let (adapter, device) = create_device(IoCapability::NoInputNoOutput);
+    assert_eq!(device.request_service_authorization(heart_rate), Err(BluetoothError::PermissionDenied));
+    let mut agent = ScriptedAgent::new(IoCapability::NoInputNoOutput);
+    agent.authorize = true;
+    let agent = register(&adapter, agent);
+    assert_eq!(device.request_service_authorization(heart_rate), Ok(()));
+    device.set_trusted(true).unwrap();
+    assert_eq!(device.request_service_authorization(heart_rate), Ok(()));
+    assert_eq!(agent.get_requests(), vec![AgentRequest::AuthorizeService(String::from(DEVICE_ID), heart_rate)]);
+}
@@ -0,0 +1,148 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+#![cfg(feature = "bluetooth-test")]
+
+extern crate device;
+
+use device::blocklist::Blocklist;
+use device::bluetooth::{BluetoothAdapter, BluetoothAddress, BluetoothDevice, BluetoothError, BluetoothGATTService};
+use device::bluetooth::BluetoothUuid;
+use device::preset;
+
+fn uuid(uuid: &str) -> BluetoothUuid {
+    BluetoothUuid::parse(uuid).unwrap()
+}
+
+fn strings(values: &[&str]) -> Vec<String> {
+    values.iter().map(|value| value.to_string()).collect()
+}
+
+fn heart_rate_device(adapter: &BluetoothAdapter) -> BluetoothDevice {
+    let address = BluetoothAddress::parse(preset::HEART_RATE_DEVICE_ADDRESS).unwrap();
+    adapter.get_device(address).unwrap().unwrap()
+}
+
+fn connected_services(device: &BluetoothDevice) -> Vec<BluetoothGATTService> {
+    device.connect().unwrap();
+    device.get_gatt_services().unwrap()
+}
+
+#[test]
+fn every_preset() {
+    for name in preset::PRESETS {
+        let adapter = BluetoothAdapter::init_preset(name).unwrap();
+        assert_eq!(adapter.get_name(), Ok(name.to_string()));
+    }
+    let not_present = BluetoothAdapter::init_preset(preset::NOT_PRESENT_ADAPTER).unwrap();
+    assert_eq!(not_present.is_present(), Ok(false));
+    let not_powered = BluetoothAdapter::init_preset(preset::NOT_POWERED_ADAPTER).unwrap();
+    assert_eq!(not_powered.is_powered(), Ok(false));
+    let empty = BluetoothAdapter::init_preset(preset::EMPTY_ADAPTER).unwrap();
+    assert_eq!(empty.is_powered(), Ok(true));
+    assert!(empty.get_devices().unwrap().is_empty());
+    assert_eq!(BluetoothAdapter::init_preset("FooAdapter").err(),
+               Some(BluetoothError::InvalidArgument(String::from("FooAdapter"))));
+}
+
+#[test]
+fn heart_rate_adapter() {
+    let adapter = BluetoothAdapter::init_preset(preset::HEART_RATE_ADAPTER).unwrap();
+    assert_eq!(adapter.get_devices().unwrap().len(), 1);
+    let device = heart_rate_device(&adapter);
+    assert_eq!(device.get_name(), Ok(String::from(preset::HEART_RATE_DEVICE_NAME)));
+    assert_eq!(device.get_uuids(),
+               Ok(vec![uuid(preset::GENERIC_ACCESS_SERVICE_UUID), uuid(preset::HEART_RATE_SERVICE_UUID)]));
+
+    let services = connected_services(&device);
+    assert_eq!(services.len(), 2);
+    let generic_access = services[0].get_gatt_characteristics().unwrap();
+    assert_eq!(generic_access.len(), 2);
+    assert_eq!(generic_access[0].read_value(), Ok(preset::HEART_RATE_DEVICE_NAME.as_bytes().to_vec()));
+    let descriptors = generic_access[0].get_gatt_descriptors().unwrap();
+    assert_eq!(descriptors.len(), 4);
+    assert_eq!(descriptors[2].get_uuid(), Ok(uuid(preset::CHARACTERISTIC_USER_DESCRIPTION_UUID)));
+    let flags: Vec<Vec<String>> = descriptors.iter().map(|descriptor| descriptor.get_flags().unwrap()).collect();
+    assert_eq!(flags, vec![strings(&["read", "write"]), strings(&["read", "write"]), vec![], vec![]]);
+
+    let heart_rate = services[1].get_gatt_characteristics().unwrap();
+    assert_eq!(heart_rate.len(), 3);
+    assert_eq!(heart_rate[0].get_uuid(), Ok(uuid(preset::HEART_RATE_MEASUREMENT_CHARACTERISTIC_UUID)));
+    assert_eq!(heart_rate[1].read_value(), Ok(vec![49]));
+    assert_eq!(heart_rate[2].read_value(), Ok(vec![50]));
+}
+
+#[test]
+fn heart_rate_variants() {
+    let no_name = BluetoothAdapter::init_preset(preset::NO_NAME_HEART_RATE_ADAPTER).unwrap();
+    assert!(heart_rate_device(&no_name).get_name().is_err());
+    let empty_name = BluetoothAdapter::init_preset(preset::EMPTY_NAME_HEART_RATE_ADAPTER).unwrap();
+    assert_eq!(heart_rate_device(&empty_name).get_name(), Ok(String::new()));
+
+    let missing_service = BluetoothAdapter::init_preset(preset::MISSING_SERVICE_HEART_RATE_ADAPTER).unwrap();
+    assert!(connected_services(&heart_rate_device(&missing_service)).is_empty());
+
+    let missing_characteristic = BluetoothAdapter::init_preset(preset::MISSING_CHARACTERISTIC_HEART_RATE_ADAPTER);
+    for service in connected_services(&heart_rate_device(&missing_characteristic.unwrap())) {
+        assert!(service.get_gatt_characteristics().unwrap().is_empty());
+    }
+
+    let missing_descriptors = BluetoothAdapter::init_preset(preset::MISSING_DESCRIPTOR_HEART_RATE_ADAPTER).unwrap();
+    let services = connected_services(&heart_rate_device(&missing_descriptors));
+    let device_name = services[0].get_gatt_characteristics().unwrap().remove(0);
+    assert!(device_name.get_gatt_descriptors().unwrap().is_empty());
+    assert_eq!(services[1].get_gatt_characteristics().unwrap().len(), 3);
+
+    let two_services = BluetoothAdapter::init_preset(preset::TWO_HEART_RATE_SERVICES_ADAPTER).unwrap();
+    let services = connected_services(&heart_rate_device(&two_services));
+    assert_eq!(services.len(), 3);
+    assert_eq!(services[1].get_uuid(), services[2].get_uuid());
+    assert_eq!(services[1].get_gatt_characteristics().unwrap().len(), 2);
+    assert_eq!(services[2].get_gatt_characteristics().unwrap()[0].read_value(), Ok(vec![50]));
+}
+
+#[test]
+fn glucose_heart_rate_adapter() {
+    let adapter = BluetoothAdapter::init_preset(preset::GLUCOSE_HEART_RATE_ADAPTER).unwrap();
+    assert_eq!(adapter.get_devices().unwrap().len(), 2);
+    let glucose = adapter.get_device(BluetoothAddress::parse(preset::GLUCOSE_DEVICE_ADDRESS).unwrap()).unwrap().unwrap();
+    assert_eq!(glucose.get_name(), Ok(String::from(preset::GLUCOSE_DEVICE_NAME)));
+    assert_eq!(glucose.get_manufacturer_data().unwrap().get(&17), Some(&vec![1, 2, 3]));
+    let heart_rate_service_data = heart_rate_device(&adapter).get_service_data().unwrap();
+    assert_eq!(heart_rate_service_data.get(&uuid(preset::HEART_RATE_SERVICE_UUID)), Some(&vec![1, 2, 3]));
+}
+
+#[test]
+fn blocklist_test_adapter() {
+    let adapter = BluetoothAdapter::init_preset(preset::BLOCKLIST_TEST_ADAPTER).unwrap();
+    let address = BluetoothAddress::parse(preset::CONNECTABLE_DEVICE_ADDRESS).unwrap();
+    let device = adapter.get_device(address).unwrap().unwrap();
+    assert_eq!(connected_services(&device).len(), 5);
+
+    let blocklist = format!("{} exclude-reads\n{}\n",
+                            preset::BLOCKLIST_EXCLUDE_READS_CHARACTERISTIC_UUID,
+                            preset::BLOCKLIST_DESCRIPTOR_UUID);
+    adapter.set_blocklist(Some(Blocklist::parse(&blocklist).unwrap()));
+    let service = device.get_gatt_services().unwrap().remove(0);
+    assert_eq!(service.get_uuid(), Ok(uuid(preset::BLOCKLIST_TEST_SERVICE_UUID)));
+    let characteristic = service.get_gatt_characteristics().unwrap().remove(0);
+    assert_eq!(characteristic.read_value(), Err(BluetoothError::Blocklisted));
+    let descriptors = characteristic.get_gatt_descriptors().unwrap();
+    assert_eq!(descriptors.len(), 1);
+    assert_eq!(descriptors[0].get_flags(), Ok(strings(&["read", "write"])));
+
+    // The blocklisted descriptor is read-only.
+    adapter.set_blocklist(None);
+    let descriptors = characteristic.get_gatt_descriptors().unwrap();
+    assert_eq!(descriptors[1].get_uuid(), Ok(uuid(preset::BLOCKLIST_DESCRIPTOR_UUID)));
+    assert_eq!(descriptors[1].get_flags(), Ok(strings(&["read"])));
+}
+
+#[test]
+fn unicode_device_adapter() {
+    let adapter = BluetoothAdapter::init_preset(preset::UNICODE_DEVICE_ADAPTER).unwrap();
+    let address = BluetoothAddress::parse(preset::UNICODE_DEVICE_ADDRESS).unwrap();
+    let device = adapter.get_device(address).unwrap().unwrap();
+    assert_eq!(device.get_name(), Ok(String::from(preset::UNICODE_DEVICE_NAME)));
+}
@@ -0,0 +1,166 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+extern crate device;
+
+use device::properties::{CharacteristicProperties, DescriptorPermissions};
+
+// The characteristic flags of BlueZ, in the order of `CharacteristicProperties`.
+const BLUEZ_CHARACTERISTIC_FLAGS: &[(&str, CharacteristicProperties)] = &[
+    ("broadcast", CharacteristicProperties::BROADCAST),
+    ("read", CharacteristicProperties::READ),
+    ("write-without-response", CharacteristicProperties::WRITE_WITHOUT_RESPONSE),
+    ("write", CharacteristicProperties::WRITE),
+    ("notify", CharacteristicProperties::NOTIFY),
+    ("indicate", CharacteristicProperties::INDICATE),
+    ("authenticated-signed-writes", CharacteristicProperties::AUTHENTICATED_SIGNED_WRITES),
+    ("extended-properties", CharacteristicProperties::EXTENDED_PROPERTIES),
+    ("reliable-write", CharacteristicProperties::RELIABLE_WRITE),
+    ("writable-auxiliaries", CharacteristicProperties::WRITABLE_AUXILIARIES),
+    ("encrypt-read", CharacteristicProperties::ENCRYPT_READ),
+    ("encrypt-write", CharacteristicProperties::ENCRYPT_WRITE),
+    ("encrypt-authenticated-read", CharacteristicProperties::ENCRYPT_AUTHENTICATED_READ),
+    ("encrypt-authenticated-write", CharacteristicProperties::ENCRYPT_AUTHENTICATED_WRITE),
+    ("secure-read", CharacteristicProperties::SECURE_READ),
+    ("secure-write", CharacteristicProperties::SECURE_WRITE),
+    ("authorize", CharacteristicProperties::AUTHORIZE),
+];
+
+// The PROPERTY_* constants of BluetoothGattCharacteristic, without the prefix.
+const ANDROID_CHARACTERISTIC_FLAGS: &[(&str, CharacteristicProperties)] = &[
+    ("BROADCAST", CharacteristicProperties::BROADCAST),
+    ("READ", CharacteristicProperties::READ),
+    ("WRITE_NO_RESPONSE", CharacteristicProperties::WRITE_WITHOUT_RESPONSE),
+    ("WRITE", CharacteristicProperties::WRITE),
+    ("NOTIFY", CharacteristicProperties::NOTIFY),
+    ("INDICATE", CharacteristicProperties::INDICATE),
+    ("SIGNED_WRITE", CharacteristicProperties::AUTHENTICATED_SIGNED_WRITES),
+    ("EXTENDED_PROPS", CharacteristicProperties::EXTENDED_PROPERTIES),
+];
+
+const BLUEZ_DESCRIPTOR_FLAGS: &[(&str, DescriptorPermissions)] = &[
+    ("read", DescriptorPermissions::READ),
+    ("write", DescriptorPermissions::WRITE),
+    ("encrypt-read", DescriptorPermissions::ENCRYPT_READ),
+    ("encrypt-write", DescriptorPermissions::ENCRYPT_WRITE),
+    ("encrypt-authenticated-read", DescriptorPermissions::ENCRYPT_AUTHENTICATED_READ),
+    ("encrypt-authenticated-write", DescriptorPermissions::ENCRYPT_AUTHENTICATED_WRITE),
+    ("secure-read", DescriptorPermissions::SECURE_READ),
+    ("secure-write", DescriptorPermissions::SECURE_WRITE),
+    ("authorize", DescriptorPermissions::AUTHORIZE),
+];
+
+// The PERMISSION_* constants of BluetoothGattDescriptor, without the prefix.
+const ANDROID_DESCRIPTOR_FLAGS: &[(&str, DescriptorPermissions)] = &[
+    ("READ", DescriptorPermissions::READ),
+    ("READ_ENCRYPTED", DescriptorPermissions::ENCRYPT_READ),
+    ("READ_ENCRYPTED_MITM", DescriptorPermissions::ENCRYPT_AUTHENTICATED_READ),
+    ("WRITE", DescriptorPermissions::WRITE),
+    ("WRITE_ENCRYPTED", DescriptorPermissions::ENCRYPT_WRITE),
+    ("WRITE_ENCRYPTED_MITM", DescriptorPermissions::ENCRYPT_AUTHENTICATED_WRITE),
+];
+
+#[test]
+fn bluez_characteristic_flags() {
+    for &(flag, property) in BLUEZ_CHARACTERISTIC_FLAGS {
+        assert_eq!(CharacteristicProperties::from_flag(flag), Some(property), "{}", flag);
+        assert_eq!(property.to_flags(), vec![flag]);
+    }
+    let all: Vec<&str> = BLUEZ_CHARACTERISTIC_FLAGS.iter().map(|&(flag, _)| flag).collect();
+    assert_eq!(CharacteristicProperties::from_flags(&all), CharacteristicProperties::all());
+}
+
+#[test]
+fn android_characteristic_flags() {
+    for &(flag, property) in ANDROID_CHARACTERISTIC_FLAGS {
+        assert_eq!(CharacteristicProperties::from_flag(flag), Some(property), "{}", flag);
+    }
+    let properties = CharacteristicProperties::from_flags(&["READ", "WRITE_NO_RESPONSE", "NOTIFY"]);
+    assert_eq!(properties,
+               CharacteristicProperties::READ | CharacteristicProperties::WRITE_WITHOUT_RESPONSE |
+               CharacteristicProperties::NOTIFY);
+    // The BlueZ names are returned.
+    assert_eq!(properties.to_flags(), vec!["read", "write-without-response", "notify"]);
+}
+
+#[test]
+fn bluez_descriptor_flags() {
+    for &(flag, permission) in BLUEZ_DESCRIPTOR_FLAGS {
+        assert_eq!(DescriptorPermissions::from_flag(flag), Some(permission), "{}", flag);
+        assert_eq!(permission.to_flags(), vec![flag]);
+    }
+    let all: Vec<&str> = BLUEZ_DESCRIPTOR_FLAGS.iter().map(|&(flag, _)| flag).collect();
+    assert_eq!(DescriptorPermissions::from_flags(&all), DescriptorPermissions::all());
+}
+
+#[test]
+fn android_descriptor_flags() {
+    for &(flag, permission) in ANDROID_DESCRIPTOR_FLAGS {
+        assert_eq!(DescriptorPermissions::from_flag(flag), Some(permission), "{}", flag);
+    }
+    assert_eq!(DescriptorPermissions::from_flags(&["READ_ENCRYPTED_MITM", "WRITE"]).to_flags(),
+               vec!["write", "encrypt-authenticated-read"]);
+}
+
+#[test]
+fn flag_names_ignore_case_and_whitespace() {
+    assert_eq!(CharacteristicProperties::from_flag(" Write-Without-Response\n"),
+               Some(CharacteristicProperties::WRITE_WITHOUT_RESPONSE));
+    assert_eq!(CharacteristicProperties::from_flag("write_without_response"),
+               Some(CharacteristicProperties::WRITE_WITHOUT_RESPONSE));
+    assert_eq!(DescriptorPermissions::from_flag("Read_Encrypted"), Some(DescriptorPermissions::ENCRYPT_READ));
+}
+
+#[test]
+fn unknown_flags_are_ignored() {
+    for flag in &["", "writes", "write without response", "PROPERTY_READ", "WRITE_SIGNED", "read,write"] {
+        assert_eq!(CharacteristicProperties::from_flag(flag), None, "{}", flag);
+    }
+    // Characteristic properties are not descriptor permissions.
+    assert_eq!(DescriptorPermissions::from_flag("notify"), None);
+    assert_eq!(DescriptorPermissions::from_flag("PERMISSION_WRITE_SIGNED"), None);
+    let properties = CharacteristicProperties::from_flags(&["read", "unknown", "notify", "WRITE_SIGNED"]);
+    assert_eq!(properties, CharacteristicProperties::READ | CharacteristicProperties::NOTIFY);
+    assert!(CharacteristicProperties::from_flags::<&str>(&[]).is_empty());
+    assert!(CharacteristicProperties::from_flags(&["unknown"]).is_empty());
+}
+
+#[test]
+fn unknown_bits_are_dropped() {
+    let properties = CharacteristicProperties::from_bits_truncate(0xFFFF_FFFF);
+    assert_eq!(properties, CharacteristicProperties::all());
+    assert_eq!(properties.bits(), 0x1_FFFF);
+    assert_eq!(DescriptorPermissions::from_bits_truncate(0xFFFF).bits(), 0x1FF);
+}
+
+#[test]
+fn round_trip() {
+    for bits in 0..=0x1FFu16 {
+        let permissions = DescriptorPermissions::from_bits_truncate(bits);
+        assert_eq!(DescriptorPermissions::from_flags(&permissions.to_flags()), permissions);
+    }
+    for &(_, property) in BLUEZ_CHARACTERISTIC_FLAGS {
+        let properties = CharacteristicProperties::READ | property;
+        assert_eq!(CharacteristicProperties::from_flags(&properties.to_flags()), properties);
+    }
+    let all = CharacteristicProperties::all();
+    assert_eq!(CharacteristicProperties::from_flags(&all.to_flags()), all);
+    // Duplicate and differently named flags collapse into one.
+    let flags = ["write-without-response", "WRITE_NO_RESPONSE", "write-no-response"];
+    assert_eq!(CharacteristicProperties::from_flags(&flags).to_flags(), vec!["write-without-response"]);
+}
+
+#[test]
+fn set_operations() {
+    let mut properties = CharacteristicProperties::READ | CharacteristicProperties::WRITE;
+    assert!(properties.contains(CharacteristicProperties::READ));
+    assert!(!properties.contains(CharacteristicProperties::READ | CharacteristicProperties::NOTIFY));
+    assert!(properties.intersects(CharacteristicProperties::READ | CharacteristicProperties::NOTIFY));
+    properties.insert(CharacteristicProperties::NOTIFY);
+    properties.remove(CharacteristicProperties::WRITE);
+    assert_eq!(properties, CharacteristicProperties::READ | CharacteristicProperties::NOTIFY);
+    assert_eq!(properties - CharacteristicProperties::READ, CharacteristicProperties::NOTIFY);
+    assert_eq!(properties & CharacteristicProperties::NOTIFY, CharacteristicProperties::NOTIFY);
+    assert_eq!(format!("{:?}", properties), "CharacteristicProperties(read | notify)");
+}
@@ -0,0 +1,128 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+extern crate device;
+
+use device::error::BluetoothError;
+use device::uuid::BluetoothUuid;
+
+const HEART_RATE: &str = "0000180d-0000-1000-8000-00805f9b34fb";
+const VENDOR: &str = "f000aa00-0451-4000-b000-000000000000";
+
+fn invalid(uuid: &str) -> Result<BluetoothUuid, BluetoothError> {
+    Err(BluetoothError::InvalidArgument(uuid.to_owned()))
+}
+
+#[test]
+fn parse_16_bit_aliases() {
+    let heart_rate = BluetoothUuid::from_u16(0x180D);
+    assert_eq!(BluetoothUuid::parse("180d"), Ok(heart_rate));
+    assert_eq!(BluetoothUuid::parse("180D"), Ok(heart_rate));
+    assert_eq!(BluetoothUuid::parse("0x180d"), Ok(heart_rate));
+    assert_eq!(BluetoothUuid::parse("0X180D"), Ok(heart_rate));
+    assert_eq!(BluetoothUuid::parse(" 180d\n"), Ok(heart_rate));
+    assert_eq!(BluetoothUuid::parse("0000"), Ok(BluetoothUuid::from_u16(0)));
+    assert_eq!(heart_rate.to_string(), HEART_RATE);
+}
+
+#[test]
+fn parse_32_bit_aliases() {
+    assert_eq!(BluetoothUuid::parse("0000180d"), Ok(BluetoothUuid::from_u16(0x180D)));
+    assert_eq!(BluetoothUuid::parse("12345678"), Ok(BluetoothUuid::from_u32(0x1234_5678)));
+    assert_eq!(BluetoothUuid::parse("0xABCDEF01"), Ok(BluetoothUuid::from_u32(0xABCD_EF01)));
+    assert_eq!(BluetoothUuid::parse("ffffffff").unwrap().to_string(), "ffffffff-0000-1000-8000-00805f9b34fb");
+}
+
+#[test]
+fn parse_128_bit_uuids() {
+    let vendor = BluetoothUuid::from_u128(0xf000aa00_0451_4000_b000_000000000000);
+    assert_eq!(BluetoothUuid::parse(VENDOR), Ok(vendor));
+    assert_eq!(BluetoothUuid::parse("f000aa0004514000b000000000000000"), Ok(vendor));
+    assert_eq!(BluetoothUuid::parse(HEART_RATE), Ok(BluetoothUuid::from_u16(0x180D)));
+    assert_eq!(VENDOR.parse::<BluetoothUuid>(), Ok(vendor));
+    assert_eq!(vendor.to_string(), VENDOR);
+}
+
+#[test]
+fn parse_mixed_case() {
+    let vendor = BluetoothUuid::parse(VENDOR).unwrap();
+    assert_eq!(BluetoothUuid::parse("F000AA00-0451-4000-B000-000000000000"), Ok(vendor));
+    assert_eq!(BluetoothUuid::parse("f000Aa00-0451-4000-b000-000000000000"), Ok(vendor));
+    assert_eq!(BluetoothUuid::parse("F000aA0004514000B000000000000000"), Ok(vendor));
+    assert_eq!(BluetoothUuid::parse("0x2a37"), BluetoothUuid::parse("0x2A37"));
+    // Formatting is always lowercase.
+    assert_eq!(BluetoothUuid::parse("0000180D-0000-1000-8000-00805F9B34FB").unwrap().to_string(), HEART_RATE);
+}
+
+#[test]
+fn misplaced_dashes() {
+    for uuid in &["0000180d0-000-1000-8000-00805f9b34fb",
+                  "0000180d-00001-000-8000-00805f9b34fb",
+                  "0000180d-0000-1000-80000-0805f9b34fb",
+                  "0000180d-0000-1000-8000-00805f9b34f-",
+                  "-000180d-0000-1000-8000-00805f9b34fb",
+                  "0000180d--000-1000-8000-00805f9b34fb",
+                  "0000180d-0000-1000-8000-00805f9b34f",
+                  "0000180d-0000-10008000-00805f9b34fb",
+                  "18-d",
+                  "0000-180d"] {
+        assert_eq!(BluetoothUuid::parse(uuid), invalid(uuid));
+    }
+}
+
+#[test]
+fn invalid_characters_and_lengths() {
+    for uuid in &["180g",
+                  "0x18 d",
+                  "+180",
+                  "0x",
+                  "",
+                  "18d",
+                  "0x18d",
+                  "180d0",
+                  "0x0000180d0",
+                  "0000180g-0000-1000-8000-00805f9b34fb",
+                  "0000180d-0000-1000-8000-00805f9b34fg",
+                  "{0000180d-0000-1000-8000-00805f9b34fb}",
+                  "0x0000180d00001000800000805f9b34fb",
+                  "0000180d00001000800000805f9b34fb0"] {
+        assert_eq!(BluetoothUuid::parse(uuid), invalid(uuid));
+    }
+}
+
+#[test]
+fn aliases_of_base_uuids() {
+    let heart_rate = BluetoothUuid::from_u16(0x180D);
+    assert!(heart_rate.is_base_uuid());
+    assert_eq!(heart_rate.to_u16(), Some(0x180D));
+    assert_eq!(heart_rate.to_u32(), Some(0x180D));
+    let alias_32 = BluetoothUuid::from_u32(0x1234_5678);
+    assert!(alias_32.is_base_uuid());
+    assert_eq!(alias_32.to_u16(), None);
+    assert_eq!(alias_32.to_u32(), Some(0x1234_5678));
+}
+
+#[test]
+fn aliases_of_other_uuids() {
+    let vendor = BluetoothUuid::parse(VENDOR).unwrap();
+    assert!(!vendor.is_base_uuid());
+    assert_eq!(vendor.to_u16(), None);
+    assert_eq!(vendor.to_u32(), None);
+    // Differs from the heart rate service UUID only in the last bit of the base UUID.
+    let almost_base = BluetoothUuid::parse("0000180d-0000-1000-8000-00805f9b34fa").unwrap();
+    assert!(!almost_base.is_base_uuid());
+    assert_eq!(almost_base.to_u16(), None);
+    assert_eq!(almost_base.to_u32(), None);
+    assert_eq!(BluetoothUuid::from_u128(0).to_u16(), None);
+}
+
+#[test]
+fn bytes_round_trip() {
+    let vendor = BluetoothUuid::parse(VENDOR).unwrap();
+    let bytes = vendor.to_bytes();
+    assert_eq!(bytes[0], 0xF0);
+    assert_eq!(bytes[15], 0x00);
+    assert_eq!(BluetoothUuid::from_bytes(bytes), vendor);
+    assert_eq!(BluetoothUuid::from_u128(vendor.as_u128()), vendor);
+}
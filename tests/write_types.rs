@@ -0,0 +1,81 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+#![cfg(feature = "bluetooth-test")]
+
+extern crate device;
+
+use device::bluetooth::{BluetoothAdapter, BluetoothDevice, BluetoothError, BluetoothGATTCharacteristic};
+use device::bluetooth::{BluetoothGATTService, WriteType};
+
+fn create_characteristic(flags: &[&str]) -> BluetoothGATTCharacteristic {
+    let adapter = BluetoothAdapter::init_mock().unwrap();
+    let device = BluetoothDevice::create_mock_device(adapter, String::from("device")).unwrap();
+    device.set_connected(true).unwrap();
+    let service = BluetoothGATTService::create_mock_service(device, String::from("service")).unwrap();
+    let characteristic =
+        BluetoothGATTCharacteristic::create_mock_characteristic(service, String::from("characteristic")).unwrap();
+    characteristic.set_flags(flags.iter().map(|flag| String::from(*flag)).collect()).unwrap();
+    characteristic.set_value(vec![0]).unwrap();
+    characteristic
+}
+
+// Whether each write type is allowed, and the value was written when it is.
+fn allowed_write_types(flags: &[&str]) -> (bool, bool) {
+    let characteristic = create_characteristic(flags);
+    let mut allowed = vec![];
+    for &(write_type, value) in &[(WriteType::WithResponse, 1), (WriteType::WithoutResponse, 2)] {
+        match characteristic.write_value_with_type(vec![value], write_type) {
+            Ok(()) => {
+                assert_eq!(characteristic.get_value(), Ok(vec![value]));
+                allowed.push(true);
+            },
+            Err(error) => {
+                assert_eq!(error, BluetoothError::NotSupported);
+                assert!(characteristic.get_value() != Ok(vec![value]));
+                allowed.push(false);
+            },
+        }
+    }
+    (allowed[0], allowed[1])
+}
+
+#[test]
+fn with_response_needs_the_write_flag() {
+    assert_eq!(allowed_write_types(&["write"]), (true, false));
+    assert_eq!(allowed_write_types(&["read", "write", "notify"]), (true, false));
+}
+
+#[test]
+fn without_response_needs_the_write_without_response_flag() {
+    assert_eq!(allowed_write_types(&["write-without-response"]), (false, true));
+    assert_eq!(allowed_write_types(&["read", "write-without-response"]), (false, true));
+}
+
+#[test]
+fn android_flag_names() {
+    assert_eq!(allowed_write_types(&["WRITE"]), (true, false));
+    assert_eq!(allowed_write_types(&["WRITE_NO_RESPONSE"]), (false, true));
+}
+
+#[test]
+fn both_flags_allow_both_write_types() {
+    assert_eq!(allowed_write_types(&["write", "write-without-response"]), (true, true));
+}
+
+#[test]
+fn other_write_flags_allow_neither_write_type() {
+    assert_eq!(allowed_write_types(&[]), (false, false));
+    assert_eq!(allowed_write_types(&["read", "notify", "indicate"]), (false, false));
+    assert_eq!(allowed_write_types(&["authenticated-signed-writes"]), (false, false));
+    assert_eq!(allowed_write_types(&["reliable-write", "writable-auxiliaries"]), (false, false));
+}
+
+#[test]
+fn write_value_ignores_the_flags() {
+    // Only `write_value_with_type` validates the write type.
+    let characteristic = create_characteristic(&["read"]);
+    assert_eq!(characteristic.write_value(vec![3]), Ok(()));
+    assert_eq!(characteristic.get_value(), Ok(vec![3]));
+}